@@ -0,0 +1,113 @@
+//! Chain extension giving the recorder access to `pallet-assets`
+//!
+//! Only compiled with the `assets-extension` feature. The runtime must expose
+//! a chain extension with ID `ASSETS_EXTENSION_ID` implementing the functions
+//! below, where `transfer_approved` moves funds the owner has approved for the
+//! calling contract via `pallet_assets::approve_transfer`.
+use crate::AssetId;
+use ink::env::{DefaultEnvironment, Environment};
+
+type AccountId = <DefaultEnvironment as Environment>::AccountId;
+type Balance = <DefaultEnvironment as Environment>::Balance;
+
+/// Declares the extension's IDs once, as constants and in the attributes of
+/// `PalletAssets`, which only accept literals
+macro_rules! pallet_assets {
+    (
+        extension = $extension:tt;
+        balance_of = $balance_of:tt;
+        allowance = $allowance:tt;
+        transfer_approved = $transfer_approved:tt;
+    ) => {
+        /// Chain extension ID registered by the runtime for `pallet-assets`
+        #[cfg_attr(not(test), allow(dead_code))]
+        pub const ASSETS_EXTENSION_ID: u16 = $extension;
+
+        /// Function IDs of the `pallet-assets` chain extension
+        #[cfg_attr(not(test), allow(dead_code))]
+        pub mod functions {
+            pub const BALANCE_OF: u16 = $balance_of;
+            pub const ALLOWANCE: u16 = $allowance;
+            pub const TRANSFER_APPROVED: u16 = $transfer_approved;
+        }
+
+        #[ink::chain_extension(extension = $extension)]
+        pub trait PalletAssets {
+            type ErrorCode = AssetsError;
+
+            /// Balance of `owner` in `asset_id`
+            #[ink(function = $balance_of, handle_status = false)]
+            fn balance_of(asset_id: AssetId, owner: AccountId) -> Balance;
+
+            /// Amount of `asset_id` that `owner` has approved `delegate` to transfer
+            #[ink(function = $allowance, handle_status = false)]
+            fn allowance(asset_id: AssetId, owner: AccountId, delegate: AccountId) -> Balance;
+
+            /// Transfers `amount` of `asset_id` from `owner` to `dest` using the caller's approval
+            #[ink(function = $transfer_approved)]
+            fn transfer_approved(
+                asset_id: AssetId,
+                owner: AccountId,
+                dest: AccountId,
+                amount: Balance,
+            ) -> Result<(), AssetsError>;
+        }
+    };
+}
+
+pallet_assets! {
+    extension = 0x0a55;
+    balance_of = 1;
+    allowance = 2;
+    transfer_approved = 3;
+}
+
+/// Errors reported by the `pallet-assets` chain extension
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[ink::scale_derive(Encode, Decode, TypeInfo)]
+pub enum AssetsError {
+    /// The asset does not exist
+    UnknownAsset,
+    /// The owner's balance is too low
+    InsufficientBalance,
+    /// The owner has not approved enough for the contract
+    InsufficientAllowance,
+    /// The asset or account is frozen
+    Frozen,
+    /// Any other dispatch error
+    Failed,
+    /// The extension's output could not be decoded
+    DecodeFailed,
+}
+
+impl AssetsError {
+    /// Status code the runtime returns for this error
+    pub const fn status_code(self) -> u32 {
+        match self {
+            Self::UnknownAsset => 1,
+            Self::InsufficientBalance => 2,
+            Self::InsufficientAllowance => 3,
+            Self::Frozen => 4,
+            Self::Failed | Self::DecodeFailed => 5,
+        }
+    }
+}
+
+impl ink::env::chain_extension::FromStatusCode for AssetsError {
+    fn from_status_code(status_code: u32) -> Result<(), Self> {
+        match status_code {
+            0 => Ok(()),
+            1 => Err(Self::UnknownAsset),
+            2 => Err(Self::InsufficientBalance),
+            3 => Err(Self::InsufficientAllowance),
+            4 => Err(Self::Frozen),
+            _ => Err(Self::Failed),
+        }
+    }
+}
+
+impl From<ink::scale::Error> for AssetsError {
+    fn from(_: ink::scale::Error) -> Self {
+        Self::DecodeFailed
+    }
+}
@@ -11,16 +11,134 @@
 /// - Implements security measures and access controls
 /// - Emits events for off-chain monitoring
 
-#[ink::contract]
+#[ink::contract(env = crate::EchoPayEnvironment)]
 mod payment_recorder {
     use ink::storage::Mapping;
     use ink::prelude::vec::Vec;
     use ink::prelude::string::String;
+    use ink::env::call::{build_call, ExecutionInput, Selector};
+    use ink::env::hash::Blake2x256;
+    use crate::AssetId;
+
+    /// Maximum length of a voice command in bytes
+    const MAX_VOICE_COMMAND_LEN: usize = 200;
+    /// Maximum length of a currency symbol in bytes
+    const MAX_CURRENCY_LEN: usize = 10;
+    /// Maximum length of a BCP 47 locale tag in bytes
+    const MAX_LOCALE_LEN: usize = 35;
+    /// Maximum number of recipients in a single split payment
+    const MAX_SPLIT_RECIPIENTS: usize = 16;
+    /// Maximum length of an invoice memo or refund reason in bytes
+    const MAX_MEMO_LEN: usize = 200;
+    /// Maximum number of guardians a user can nominate
+    const MAX_GUARDIANS: usize = 10;
+    /// Maximum number of amount tiers in a confidence policy
+    const MAX_CONFIDENCE_TIERS: usize = 8;
+    /// Default time a user has to confirm a low-confidence payment (1 hour)
+    const DEFAULT_CONFIRMATION_WINDOW: Timestamp = 60 * 60 * 1000;
+    /// Maximum number of entries in a user's allowlist or blocklist
+    const MAX_RECIPIENT_LIST_LEN: usize = 50;
+    /// Domain separator prefixed to every signed payment intent
+    const INTENT_DOMAIN: &[u8] = b"echopay:payment-intent:v1";
+    /// Default upper bound on the number of payments in one batch
+    const DEFAULT_MAX_BATCH_SIZE: u32 = 20;
+    /// Default time after a payment during which the payer can dispute it (30 days)
+    const DEFAULT_DISPUTE_WINDOW: Timestamp = 30 * 24 * 60 * 60 * 1000;
+    /// Default time an arbiter has to resolve a dispute (14 days)
+    const DEFAULT_DISPUTE_RESOLUTION_PERIOD: Timestamp = 14 * 24 * 60 * 60 * 1000;
+    /// Default time a council proposal stays open for approval (7 days)
+    const DEFAULT_PROPOSAL_LIFETIME: Timestamp = 7 * 24 * 60 * 60 * 1000;
+    /// Maximum number of council members
+    const MAX_COUNCIL_MEMBERS: usize = 16;
+    /// Default waiting period before a timelocked admin operation can be executed (48 hours)
+    const DEFAULT_TIMELOCK_DELAY: Timestamp = 2 * 24 * 60 * 60 * 1000;
+    /// Default waiting period before a raised spending limit takes effect (24 hours)
+    const DEFAULT_LIMIT_INCREASE_DELAY: Timestamp = 24 * 60 * 60 * 1000;
+    /// Length of a day in milliseconds
+    const DAY: Timestamp = 24 * 60 * 60 * 1000;
+
+    /// Contract-wide unique identifier of a payment record
+    pub type PaymentId = u64;
+    /// Identifier shared by all records created from one split payment
+    pub type SplitId = u64;
+    /// Identifier of an invoice issued by a merchant
+    pub type InvoiceId = u64;
+    /// Identifier of a dispute opened against a payment
+    pub type DisputeId = u64;
+    /// Identifier of a council proposal
+    pub type ProposalId = u64;
+    /// Identifier of a timelocked admin operation
+    pub type OperationId = u64;
+    /// Key of a spending limit: (user, currency, period)
+    pub type LimitKey = (AccountId, String, LimitPeriod);
+    /// Key of a user's recipient list: (user, list)
+    pub type RecipientListKey = (AccountId, RecipientList);
+
+    /// The asset a payment was made in
+    #[derive(Clone, Copy)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(feature = "std", derive(Debug, PartialEq, Eq))]
+    pub enum PaymentAsset {
+        /// The chain's native balance, described by `currency`
+        Native,
+        /// A registered PSP22 token contract
+        Psp22(AccountId),
+        /// A registered `pallet-assets` asset, paid through the chain extension
+        PalletAsset(AssetId),
+    }
+
+    /// A PSP22 token or `pallet-assets` asset accepted by the recorder
+    #[derive(Clone)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(
+        feature = "std",
+        derive(Debug, PartialEq, Eq, ink::storage::traits::StorageLayout)
+    )]
+    pub struct TokenInfo {
+        /// Currency symbol recorded with payments in this token (e.g. "USDC")
+        pub symbol: String,
+        /// Number of decimals of the token's smallest unit
+        pub decimals: u8,
+    }
+
+    /// Errors returned by a PSP22 token contract
+    #[derive(Debug, PartialEq, Eq)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    pub enum Psp22Error {
+        Custom(String),
+        InsufficientBalance,
+        InsufficientAllowance,
+        ZeroRecipientAddress,
+        ZeroSenderAddress,
+        SafeTransferCheckFailed(String),
+    }
+
+    /// Settlement state of a recorded payment
+    #[derive(Clone, Copy)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(feature = "std", derive(Debug, PartialEq, Eq))]
+    pub enum PaymentStatus {
+        /// Recorded and not refunded
+        Completed,
+        /// Part of the amount has been refunded by the recipient
+        PartiallyRefunded,
+        /// The full amount has been refunded by the recipient
+        Refunded,
+        /// The payer has opened a dispute that is awaiting an arbiter
+        Disputed,
+        /// Recognized below the sender's confidence policy and awaiting `confirm_pending`
+        PendingConfirmation,
+        /// Not confirmed within the confirmation window; never counted as paid
+        Expired,
+    }
 
     /// Represents a recorded payment transaction
+    #[derive(Clone)]
     #[ink::scale_derive(Encode, Decode, TypeInfo)]
     #[cfg_attr(feature = "std", derive(Debug, PartialEq, Eq))]
     pub struct PaymentRecord {
+        /// Contract-wide unique payment identifier
+        pub id: PaymentId,
         /// The recipient's account ID
         pub recipient: AccountId,
         /// The payment amount in smallest unit (Planck for DOT)
@@ -29,12 +147,588 @@ mod payment_recorder {
         pub voice_command: String,
         /// Currency type (DOT, WND, etc.)
         pub currency: String,
+        /// Asset the payment was made in
+        pub asset: PaymentAsset,
         /// Network where payment was made
         pub network: String,
         /// Timestamp when the payment was recorded
         pub timestamp: Timestamp,
         /// Voice recognition confidence score (0-100)
         pub confidence: u8,
+        /// Split payment this record belongs to, if any
+        pub split_id: Option<SplitId>,
+        /// Invoice this record paid, if any
+        pub invoice_id: Option<InvoiceId>,
+        /// Settlement state
+        pub status: PaymentStatus,
+        /// Total amount refunded so far
+        pub refunded_amount: Balance,
+        /// Delegate that submitted the payment on the sender's behalf, if any
+        pub submitted_by: Option<AccountId>,
+        /// Locale the voice command was spoken in, e.g. "es-ES", if given
+        pub locale: Option<String>,
+    }
+
+    /// A refund returned by the recipient of a payment
+    #[derive(Clone)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(feature = "std", derive(Debug, PartialEq, Eq))]
+    pub struct RefundRecord {
+        /// The original recipient who returned the funds
+        pub refunder: AccountId,
+        /// Amount refunded in smallest unit
+        pub amount: Balance,
+        /// Why the payment was refunded
+        pub reason: String,
+        /// Timestamp when the refund was recorded
+        pub timestamp: Timestamp,
+        /// Whether the refund was transferred by the contract
+        pub transferred: bool,
+    }
+
+    /// Window over which a spending limit applies
+    #[derive(Clone, Copy)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(feature = "std", derive(Debug, PartialEq, Eq))]
+    pub enum LimitPeriod {
+        /// Calendar day (UTC), resetting at midnight
+        Day,
+        /// Seven-day window aligned to the Unix epoch
+        Week,
+    }
+
+    impl LimitPeriod {
+        /// Length of the period in milliseconds
+        pub fn duration(self) -> Timestamp {
+            match self {
+                Self::Day => DAY,
+                Self::Week => 7 * DAY,
+            }
+        }
+    }
+
+    /// Stricter confidence requirement for payments of at least `min_amount`
+    #[derive(Clone)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(
+        feature = "std",
+        derive(Debug, PartialEq, Eq, ink::storage::traits::StorageLayout)
+    )]
+    pub struct ConfidenceTier {
+        /// Smallest amount the tier applies to
+        pub min_amount: Balance,
+        /// Confidence required for such payments (0-100)
+        pub min_confidence: u8,
+    }
+
+    /// A user's minimum recognition confidence for voice payments
+    ///
+    /// A payment needs the highest confidence among `min_confidence` and the
+    /// tiers its amount reaches; below that it waits for confirmation.
+    #[derive(Clone)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(
+        feature = "std",
+        derive(Debug, PartialEq, Eq, ink::storage::traits::StorageLayout)
+    )]
+    pub struct ConfidencePolicy {
+        /// Confidence required for every payment (0-100)
+        pub min_confidence: u8,
+        /// Amount tiers with stricter requirements
+        pub tiers: Vec<ConfidenceTier>,
+    }
+
+    impl ConfidencePolicy {
+        /// Confidence a payment of `amount` must reach
+        pub fn required_confidence(&self, amount: Balance) -> u8 {
+            self.tiers
+                .iter()
+                .filter(|tier| amount >= tier.min_amount)
+                .map(|tier| tier.min_confidence)
+                .fold(self.min_confidence, u8::max)
+        }
+    }
+
+    /// A limit change waiting for `limit_increase_delay` to pass
+    #[derive(Clone)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(
+        feature = "std",
+        derive(Debug, PartialEq, Eq, ink::storage::traits::StorageLayout)
+    )]
+    pub struct PendingLimit {
+        /// The new limit, or `None` to remove the limit
+        pub amount: Option<Balance>,
+        /// Timestamp from which the new limit applies
+        pub effective_at: Timestamp,
+    }
+
+    /// A user's cap on voice payments in one currency over one period
+    #[derive(Clone)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(
+        feature = "std",
+        derive(Debug, PartialEq, Eq, ink::storage::traits::StorageLayout)
+    )]
+    pub struct SpendingLimit {
+        /// Maximum amount that can be recorded per period
+        pub amount: Balance,
+        /// A raise or removal that has not taken effect yet
+        pub pending: Option<PendingLimit>,
+    }
+
+    /// Accounts a user trusts to freeze and unfreeze their voice payments
+    #[derive(Clone, Default)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(
+        feature = "std",
+        derive(Debug, PartialEq, Eq, ink::storage::traits::StorageLayout)
+    )]
+    pub struct GuardianSet {
+        /// The nominated guardians
+        pub guardians: Vec<AccountId>,
+        /// Number of guardian approvals needed to unfreeze (M of N)
+        pub threshold: u32,
+    }
+
+    /// A user's permission for a delegate to record payments on their behalf
+    #[derive(Clone)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(
+        feature = "std",
+        derive(Debug, PartialEq, Eq, ink::storage::traits::StorageLayout)
+    )]
+    pub struct DelegateAuthorization {
+        /// Currency the delegate may record payments in
+        pub currency: String,
+        /// Timestamp after which the authorization is no longer valid
+        pub expires_at: Timestamp,
+        /// Maximum amount of a single payment
+        pub max_per_payment: Balance,
+        /// Maximum total amount over the authorization's lifetime
+        pub total_cap: Balance,
+        /// Total amount recorded by the delegate so far
+        pub spent: Balance,
+    }
+
+    /// A payment authorized off-chain by the signer's wallet
+    ///
+    /// The wallet signs `(INTENT_DOMAIN, contract address, intent)` SCALE
+    /// encoded, so any relayer can submit it with `record_signed_intent`.
+    #[derive(Clone)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(feature = "std", derive(Debug, PartialEq, Eq))]
+    pub struct PaymentIntent {
+        /// The account that signed the intent and is recorded as the sender
+        pub signer: AccountId,
+        /// The recipient's account ID
+        pub recipient: AccountId,
+        /// The payment amount in smallest unit (Planck for DOT)
+        pub amount: Balance,
+        /// Currency type (DOT, WND, etc.)
+        pub currency: String,
+        /// Network where payment was made
+        pub network: String,
+        /// Voice recognition confidence score (0-100)
+        pub confidence: u8,
+        /// BLAKE2-256 hash of the voice command
+        pub command_hash: Hash,
+        /// Must equal the signer's next intent nonce
+        pub nonce: u64,
+        /// Timestamp after which the intent can no longer be submitted
+        pub expires_at: Timestamp,
+        /// BCP 47 tag of the language the voice command was spoken in
+        pub locale: Option<String>,
+    }
+
+    /// Signature over a payment intent
+    #[derive(Clone)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(feature = "std", derive(Debug, PartialEq, Eq))]
+    pub enum IntentSignature {
+        /// sr25519 signature; the signer's AccountId is the public key
+        Sr25519([u8; 64]),
+        /// Recoverable secp256k1 signature over the BLAKE2-256 hash of the
+        /// payload; the signer's AccountId is the BLAKE2-256 hash of the
+        /// compressed public key
+        Ecdsa([u8; 65]),
+    }
+
+    /// Why and by whom a user's payments were frozen
+    #[derive(Clone)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(
+        feature = "std",
+        derive(Debug, PartialEq, Eq, ink::storage::traits::StorageLayout)
+    )]
+    pub struct FreezeState {
+        /// The guardian who froze the account
+        pub frozen_by: AccountId,
+        /// Why the account was frozen
+        pub reason: String,
+        /// Timestamp when the account was frozen
+        pub frozen_at: Timestamp,
+        /// Guardians who have approved unfreezing so far
+        pub unfreeze_approvals: Vec<AccountId>,
+    }
+
+    /// A user's list of recipients that voice payments are restricted by
+    #[derive(Clone, Copy)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(
+        feature = "std",
+        derive(Debug, PartialEq, Eq, ink::storage::traits::StorageLayout)
+    )]
+    pub enum RecipientList {
+        /// Trusted recipients, enforced while allowlist-only mode is on
+        Allowlist,
+        /// Recipients the user never wants to pay
+        Blocklist,
+    }
+
+    /// A privileged duty that can be granted to accounts
+    ///
+    /// The owner implicitly holds every role.
+    #[derive(Clone, Copy)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(
+        feature = "std",
+        derive(Debug, PartialEq, Eq, ink::storage::traits::StorageLayout)
+    )]
+    pub enum Role {
+        /// Manages arbiters and, by default, grants and revokes every role
+        Admin,
+        /// Pauses and resumes payment recording
+        Pauser,
+        /// Updates settings and the token and asset registries
+        ConfigManager,
+        /// Reads payments across all users
+        Auditor,
+        /// Submits signed payment intents on behalf of their signers
+        Relayer,
+    }
+
+    /// A privileged operation, performed directly by the owner or through a council proposal
+    #[derive(Clone)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(
+        feature = "std",
+        derive(Debug, PartialEq, Eq, ink::storage::traits::StorageLayout)
+    )]
+    pub enum AdminAction {
+        /// Pause or resume payment recording
+        SetPaused(bool),
+        /// Replace the administrative settings
+        UpdateConfig(ContractConfig),
+        /// Upgrade the contract to new code
+        SetCodeHash(Hash),
+        /// Nominate a new owner, who takes over by calling `accept_ownership`
+        TransferOwnership(AccountId),
+        /// Grant or revoke the arbiter role
+        SetArbiter(AccountId, bool),
+        /// Register a PSP22 token
+        RegisterToken(AccountId, TokenInfo),
+        /// Remove a PSP22 token from the registry
+        UnregisterToken(AccountId),
+        /// Register a `pallet-assets` asset
+        RegisterAsset(AssetId, TokenInfo),
+        /// Remove a `pallet-assets` asset from the registry
+        UnregisterAsset(AssetId),
+        /// Replace the council's members and threshold
+        UpdateCouncil(Council),
+        /// Grant a role to an account
+        GrantRole(Role, AccountId),
+        /// Revoke a role from an account
+        RevokeRole(Role, AccountId),
+        /// Change which role may grant and revoke a role
+        SetRoleAdmin(Role, Role),
+        /// Cancel a scheduled operation before it is executed
+        CancelOperation(OperationId),
+        /// Add an account to, or remove it from, the global deny list
+        SetDenied(AccountId, bool),
+    }
+
+    impl AdminAction {
+        /// Whether the action waits in the timelock queue before taking effect
+        pub fn is_timelocked(&self) -> bool {
+            matches!(
+                self,
+                AdminAction::UpdateConfig(_)
+                    | AdminAction::SetCodeHash(_)
+                    | AdminAction::TransferOwnership(_)
+                    | AdminAction::UpdateCouncil(_)
+            )
+        }
+    }
+
+    /// Lifecycle of a timelocked admin operation
+    #[derive(Clone, Copy)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(
+        feature = "std",
+        derive(Debug, PartialEq, Eq, ink::storage::traits::StorageLayout)
+    )]
+    pub enum OperationStatus {
+        /// Waiting for its delay to pass
+        Pending,
+        /// The action has been performed
+        Executed,
+        /// Cancelled before it was executed
+        Cancelled,
+    }
+
+    /// An admin action queued behind the timelock
+    #[derive(Clone)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(
+        feature = "std",
+        derive(Debug, PartialEq, Eq, ink::storage::traits::StorageLayout)
+    )]
+    pub struct ScheduledOperation {
+        /// The operation's identifier
+        pub id: OperationId,
+        /// The action to perform
+        pub action: AdminAction,
+        /// The account that scheduled the operation
+        pub scheduled_by: AccountId,
+        /// Timestamp from which the operation can be executed
+        pub ready_at: Timestamp,
+        /// Whether the operation is pending, executed or cancelled
+        pub status: OperationStatus,
+    }
+
+    /// Members that jointly administer the contract in place of a single owner
+    #[derive(Clone)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(
+        feature = "std",
+        derive(Debug, PartialEq, Eq, ink::storage::traits::StorageLayout)
+    )]
+    pub struct Council {
+        /// Accounts that can propose and approve admin actions
+        pub members: Vec<AccountId>,
+        /// Number of approvals needed to execute a proposal (M of N)
+        pub threshold: u32,
+    }
+
+    /// An admin action awaiting council approval
+    #[derive(Clone)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(
+        feature = "std",
+        derive(Debug, PartialEq, Eq, ink::storage::traits::StorageLayout)
+    )]
+    pub struct Proposal {
+        /// The proposal's identifier
+        pub id: ProposalId,
+        /// The member who proposed the action
+        pub proposer: AccountId,
+        /// The action to perform once approved
+        pub action: AdminAction,
+        /// Members who have approved, including the proposer
+        pub approvals: Vec<AccountId>,
+        /// Timestamp after which the proposal can no longer be executed
+        pub expires_at: Timestamp,
+        /// Whether the action has been performed
+        pub executed: bool,
+    }
+
+    /// How an arbiter settled a dispute
+    #[derive(Clone, Copy)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(
+        feature = "std",
+        derive(Debug, PartialEq, Eq, ink::storage::traits::StorageLayout)
+    )]
+    pub enum DisputeOutcome {
+        /// The payer was right; escrowed funds are refunded to the payer
+        RefundPayer,
+        /// The claim was rejected; escrowed funds go back to the recipient
+        RejectClaim,
+    }
+
+    /// Lifecycle of a dispute
+    #[derive(Clone, Copy)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(
+        feature = "std",
+        derive(Debug, PartialEq, Eq, ink::storage::traits::StorageLayout)
+    )]
+    pub enum DisputeStatus {
+        /// Awaiting a decision by an arbiter
+        Open,
+        /// Decided by an arbiter
+        Resolved(DisputeOutcome),
+        /// Closed without a decision after the deadline; escrow returned to the recipient
+        Lapsed,
+    }
+
+    /// A payer's claim against a recorded payment
+    #[derive(Clone)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(
+        feature = "std",
+        derive(Debug, PartialEq, Eq, ink::storage::traits::StorageLayout)
+    )]
+    pub struct Dispute {
+        /// The dispute's identifier
+        pub id: DisputeId,
+        /// The disputed payment
+        pub payment_id: PaymentId,
+        /// The account that made the payment and opened the dispute
+        pub payer: AccountId,
+        /// The account that received the payment
+        pub recipient: AccountId,
+        /// Amount still refundable when the dispute was opened
+        pub disputed_amount: Balance,
+        /// Hash of the off-chain evidence submitted by the payer
+        pub evidence_hash: Hash,
+        /// Timestamp when the dispute was opened
+        pub opened_at: Timestamp,
+        /// Timestamp by which an arbiter must resolve the dispute
+        pub deadline: Timestamp,
+        /// Funds the recipient has placed in escrow with the contract
+        pub escrowed: Balance,
+        /// Current status
+        pub status: DisputeStatus,
+    }
+
+    /// Lifecycle of an invoice
+    #[derive(Clone, Copy)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(
+        feature = "std",
+        derive(Debug, PartialEq, Eq, ink::storage::traits::StorageLayout)
+    )]
+    pub enum InvoiceStatus {
+        /// Awaiting payment
+        Open,
+        /// Paid by a customer
+        Paid,
+        /// Not paid before `expires_at`; reported by queries, never stored
+        Expired,
+        /// Withdrawn by the merchant
+        Cancelled,
+        /// Paid below the payer's confidence policy; becomes `Paid` when the
+        /// payment is confirmed and `Open` again if it expires
+        AwaitingConfirmation,
+    }
+
+    /// A payment request issued by a merchant
+    #[derive(Clone)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(
+        feature = "std",
+        derive(Debug, PartialEq, Eq, ink::storage::traits::StorageLayout)
+    )]
+    pub struct Invoice {
+        /// The invoice's identifier
+        pub id: InvoiceId,
+        /// The account that issued the invoice and receives the payment
+        pub merchant: AccountId,
+        /// Amount due in smallest unit
+        pub amount: Balance,
+        /// Currency type (DOT, WND, etc.)
+        pub currency: String,
+        /// Description shown to the customer
+        pub memo: String,
+        /// Timestamp when the invoice was created
+        pub created_at: Timestamp,
+        /// Timestamp after which the invoice can no longer be paid
+        pub expires_at: Timestamp,
+        /// Current status
+        pub status: InvoiceStatus,
+        /// The account that paid the invoice
+        pub payer: Option<AccountId>,
+        /// The payment record created when the invoice was paid
+        pub payment_id: Option<PaymentId>,
+    }
+
+    /// A single payment to record, as submitted in a batch
+    #[derive(Clone)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(feature = "std", derive(Debug, PartialEq, Eq))]
+    pub struct PaymentInput {
+        /// The recipient's account ID
+        pub recipient: AccountId,
+        /// The payment amount in smallest unit (Planck for DOT)
+        pub amount: Balance,
+        /// The original voice command that initiated this payment
+        pub voice_command: String,
+        /// Currency type (DOT, WND, etc.)
+        pub currency: String,
+        /// Network where payment was made
+        pub network: String,
+        /// Voice recognition confidence score (0-100)
+        pub confidence: u8,
+        /// BCP 47 tag of the language the voice command was spoken in
+        pub locale: Option<String>,
+    }
+
+    /// Administrative settings of the contract
+    #[derive(Clone)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(
+        feature = "std",
+        derive(Debug, PartialEq, Eq, ink::storage::traits::StorageLayout)
+    )]
+    pub struct ContractConfig {
+        /// Maximum number of payments accepted by `record_payments_batch`
+        pub max_batch_size: u32,
+        /// Time after a payment during which the payer can dispute it
+        pub dispute_window: Timestamp,
+        /// Time an arbiter has to resolve a dispute once opened
+        pub dispute_resolution_period: Timestamp,
+        /// Waiting period before a raised or removed spending limit takes effect
+        pub limit_increase_delay: Timestamp,
+        /// Time a council proposal stays open for approval
+        pub proposal_lifetime: Timestamp,
+        /// Waiting period before a scheduled admin operation can be executed
+        pub timelock_delay: Timestamp,
+        /// Time a user has to confirm a payment recorded below their confidence policy
+        pub confirmation_window: Timestamp,
+    }
+
+    impl Default for ContractConfig {
+        fn default() -> Self {
+            Self {
+                max_batch_size: DEFAULT_MAX_BATCH_SIZE,
+                dispute_window: DEFAULT_DISPUTE_WINDOW,
+                dispute_resolution_period: DEFAULT_DISPUTE_RESOLUTION_PERIOD,
+                limit_increase_delay: DEFAULT_LIMIT_INCREASE_DELAY,
+                proposal_lifetime: DEFAULT_PROPOSAL_LIFETIME,
+                timelock_delay: DEFAULT_TIMELOCK_DELAY,
+                confirmation_window: DEFAULT_CONFIRMATION_WINDOW,
+            }
+        }
+    }
+
+    /// A payment divided between several recipients by one voice command
+    #[derive(Clone)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    #[cfg_attr(
+        feature = "std",
+        derive(Debug, PartialEq, Eq, ink::storage::traits::StorageLayout)
+    )]
+    pub struct SplitPayment {
+        /// The account that paid every share
+        pub payer: AccountId,
+        /// Each recipient with the share they received
+        pub shares: Vec<(AccountId, Balance)>,
+        /// Payment records created for the shares, in the same order
+        pub payment_ids: Vec<PaymentId>,
+        /// Sum of all shares
+        pub total_amount: Balance,
+        /// The original voice command that initiated the split
+        pub voice_command: String,
+        /// Currency type (DOT, WND, etc.)
+        pub currency: String,
+        /// Network where payment was made
+        pub network: String,
+        /// Timestamp when the split was recorded
+        pub timestamp: Timestamp,
+        /// Whether the shares were transferred by the contract
+        pub transferred: bool,
     }
 
     /// The main contract storage
@@ -44,379 +738,5559 @@ mod payment_recorder {
         payment_history: Mapping<AccountId, Vec<PaymentRecord>>,
         /// Contract owner for administrative functions
         owner: AccountId,
-        /// Total number of payments recorded
+        /// Total number of payments recorded, also the next payment ID
         total_payments: u64,
+        /// Locates a payment by ID as (sender, index in sender's history)
+        payment_locations: Mapping<PaymentId, (AccountId, u32)>,
+        /// Administrative settings
+        config: ContractConfig,
+        /// PSP22 token contracts accepted for token payments
+        registered_tokens: Mapping<AccountId, TokenInfo>,
+        /// `pallet-assets` assets accepted for asset payments
+        registered_assets: Mapping<AssetId, TokenInfo>,
+        /// Split payments by their identifier
+        split_payments: Mapping<SplitId, SplitPayment>,
+        /// Identifier assigned to the next split payment
+        next_split_id: SplitId,
+        /// Invoices by their identifier
+        invoices: Mapping<InvoiceId, Invoice>,
+        /// Invoices issued by each merchant, oldest first
+        merchant_invoices: Mapping<AccountId, Vec<InvoiceId>>,
+        /// Identifier assigned to the next invoice
+        next_invoice_id: InvoiceId,
+        /// Refunds recorded against each payment, oldest first
+        refunds: Mapping<PaymentId, Vec<RefundRecord>>,
+        /// Accounts allowed to resolve disputes
+        arbiters: Mapping<AccountId, ()>,
+        /// Disputes by their identifier
+        disputes: Mapping<DisputeId, Dispute>,
+        /// Identifier assigned to the next dispute
+        next_dispute_id: DisputeId,
+        /// Spending limits keyed by (user, currency, period)
+        spending_limits: Mapping<LimitKey, SpendingLimit>,
+        /// Amount spent in the current window as (window start, spent), keyed like the limits
+        spending_windows: Mapping<LimitKey, (Timestamp, Balance)>,
+        /// Guardians nominated by each user
+        guardians: Mapping<AccountId, GuardianSet>,
+        /// Users whose voice payments are currently frozen
+        frozen_accounts: Mapping<AccountId, FreezeState>,
+        /// Delegate authorizations keyed by (user, delegate)
+        delegates: Mapping<(AccountId, AccountId), DelegateAuthorization>,
+        /// Next expected signed intent nonce of each signer
+        intent_nonces: Mapping<AccountId, u64>,
+        /// Whether payment recording is paused
+        paused: bool,
+        /// Council administering the contract, if one has been established
+        council: Option<Council>,
+        /// Council proposals by their identifier
+        proposals: Mapping<ProposalId, Proposal>,
+        /// Identifier assigned to the next proposal
+        next_proposal_id: ProposalId,
+        /// Position of each granted role and its holder in `role_holders`
+        roles: Mapping<(Role, AccountId), u32>,
+        /// Granted roles and their holders by position, so they can be revoked together
+        role_holders: Mapping<u32, (Role, AccountId)>,
+        /// Number of granted roles
+        role_holders_len: u32,
+        /// Role allowed to grant and revoke each role, when not `Admin`
+        role_admins: Mapping<Role, Role>,
+        /// Timelocked admin operations by their identifier
+        operations: Mapping<OperationId, ScheduledOperation>,
+        /// Position of each operation that is neither executed nor cancelled in `pending_operations`
+        pending_positions: Mapping<OperationId, u32>,
+        /// Pending operations by position, for pagination
+        pending_operations: Mapping<u32, OperationId>,
+        /// Number of pending operations
+        pending_operations_len: u32,
+        /// Identifier assigned to the next scheduled operation
+        next_operation_id: OperationId,
+        /// Account nominated to take over ownership
+        pending_owner: Option<AccountId>,
+        /// Each user's allowlist and blocklist
+        recipient_lists: Mapping<RecipientListKey, Vec<AccountId>>,
+        /// Users who only pay recipients on their allowlist
+        allowlist_only: Mapping<AccountId, ()>,
+        /// Position of each globally denied account in `deny_list`
+        denied_accounts: Mapping<AccountId, u32>,
+        /// Globally denied accounts by position, for pagination
+        deny_list: Mapping<u32, AccountId>,
+        /// Number of globally denied accounts
+        deny_list_len: u32,
+        /// Each user's confidence policy
+        confidence_policies: Mapping<AccountId, ConfidencePolicy>,
+        /// Confirmation deadline of each payment awaiting confirmation
+        pending_confirmations: Mapping<PaymentId, Timestamp>,
+        /// Locale each user speaks voice commands in
+        command_locales: Mapping<AccountId, String>,
+        /// Delegate whose cap a payment awaiting confirmation is charged to on confirmation
+        pending_delegate_charges: Mapping<PaymentId, AccountId>,
     }
 
     /// Events emitted by the contract
     #[ink(event)]
     pub struct PaymentRecorded {
+        #[ink(topic)]
+        payment_id: PaymentId,
         #[ink(topic)]
         sender: AccountId,
         #[ink(topic)]
         recipient: AccountId,
         amount: Balance,
+        currency: String,
         voice_command: String,
+        locale: Option<String>,
         timestamp: Timestamp,
     }
 
-    /// Contract errors
-    #[derive(Debug, PartialEq, Eq)]
-    #[ink::scale_derive(Encode, Decode, TypeInfo)]
-    pub enum Error {
-        /// Unauthorized access attempt
-        Unauthorized,
-        /// Invalid payment amount (zero or negative)
-        InvalidAmount,
-        /// Voice command string is empty or too long
-        InvalidVoiceCommand,
-        /// Currency string is invalid
-        InvalidCurrency,
-        /// Confidence score is out of valid range
-        InvalidConfidence,
+    /// Emitted when a payment is recorded below the sender's confidence policy
+    #[ink(event)]
+    pub struct PaymentPendingConfirmation {
+        #[ink(topic)]
+        payment_id: PaymentId,
+        #[ink(topic)]
+        sender: AccountId,
+        confidence: u8,
+        expires_at: Timestamp,
     }
 
-    /// Contract result type
-    pub type Result<T> = core::result::Result<T, Error>;
+    /// Emitted when the sender confirms a pending payment
+    #[ink(event)]
+    pub struct PaymentConfirmed {
+        #[ink(topic)]
+        payment_id: PaymentId,
+        #[ink(topic)]
+        sender: AccountId,
+    }
 
-    impl PaymentRecorder {
-        /// Creates a new PaymentRecorder contract instance
-        #[ink(constructor)]
-        pub fn new() -> Self {
-            Self {
-                payment_history: Mapping::new(),
-                owner: Self::env().caller(),
-                total_payments: 0,
-            }
-        }
+    /// Emitted when a pending payment's confirmation window passes unconfirmed
+    #[ink(event)]
+    pub struct PendingPaymentExpired {
+        #[ink(topic)]
+        payment_id: PaymentId,
+        #[ink(topic)]
+        sender: AccountId,
+    }
 
-        /// Records a new payment transaction
-        /// 
-        /// # Arguments
-        /// * `recipient` - The recipient's AccountId
-        /// * `amount` - The payment amount in smallest unit
-        /// * `voice_command` - The original voice command
-        /// * `currency` - Currency type (e.g., "DOT", "WND")
-        /// * `network` - Network name (e.g., "polkadot", "rococo")
-        /// * `confidence` - Voice recognition confidence (0-100)
-        /// 
-        /// # Returns
-        /// * `Result<()>` - Success or error
-        #[ink(message)]
-        pub fn record_payment(
-            &mut self,
-            recipient: AccountId,
-            amount: Balance,
-            voice_command: String,
-            currency: String,
-            network: String,
-            confidence: u8,
-        ) -> Result<()> {
-            // Validate inputs
-            if amount == 0 {
-                return Err(Error::InvalidAmount);
-            }
+    /// Emitted when a user sets or clears their confidence policy
+    #[ink(event)]
+    pub struct ConfidencePolicyChanged {
+        #[ink(topic)]
+        user: AccountId,
+        policy: Option<ConfidencePolicy>,
+    }
 
-            if voice_command.is_empty() || voice_command.len() > 200 {
-                return Err(Error::InvalidVoiceCommand);
-            }
+    /// Emitted when a user sets or clears the locale of their voice commands
+    #[ink(event)]
+    pub struct CommandLocaleChanged {
+        #[ink(topic)]
+        user: AccountId,
+        locale: Option<String>,
+    }
 
-            if currency.is_empty() || currency.len() > 10 {
-                return Err(Error::InvalidCurrency);
-            }
+    /// Emitted once per split payment, after the individual `PaymentRecorded` events
+    #[ink(event)]
+    pub struct SplitPaymentRecorded {
+        #[ink(topic)]
+        split_id: SplitId,
+        #[ink(topic)]
+        sender: AccountId,
+        total_amount: Balance,
+        recipients: u32,
+        transferred: bool,
+    }
 
-            if confidence > 100 {
-                return Err(Error::InvalidConfidence);
-            }
+    /// Emitted when a merchant issues an invoice
+    #[ink(event)]
+    pub struct InvoiceCreated {
+        #[ink(topic)]
+        invoice_id: InvoiceId,
+        #[ink(topic)]
+        merchant: AccountId,
+        amount: Balance,
+        currency: String,
+        expires_at: Timestamp,
+    }
 
-            let sender = self.env().caller();
-            let timestamp = self.env().block_timestamp();
+    /// Emitted when a customer pays an invoice
+    #[ink(event)]
+    pub struct InvoicePaid {
+        #[ink(topic)]
+        invoice_id: InvoiceId,
+        #[ink(topic)]
+        payer: AccountId,
+        payment_id: PaymentId,
+        transferred: bool,
+    }
 
-            // Create payment record
-            let record = PaymentRecord {
-                recipient,
-                amount,
-                voice_command: voice_command.clone(),
-                currency,
-                network,
-                timestamp,
-                confidence,
-            };
+    /// Emitted when a merchant cancels an open invoice
+    #[ink(event)]
+    pub struct InvoiceCancelled {
+        #[ink(topic)]
+        invoice_id: InvoiceId,
+    }
 
-            // Get or create payment history for sender
-            let mut history = self.payment_history.get(&sender).unwrap_or_default();
-            history.push(record);
-            
-            // Update storage
-            self.payment_history.insert(&sender, &history);
-            self.total_payments += 1;
+    /// Emitted when the recipient of a payment refunds part or all of it
+    #[ink(event)]
+    pub struct PaymentRefunded {
+        #[ink(topic)]
+        original_sender: AccountId,
+        #[ink(topic)]
+        refunder: AccountId,
+        payment_id: PaymentId,
+        amount: Balance,
+        total_refunded: Balance,
+        reason: String,
+    }
 
-            // Emit event
-            self.env().emit_event(PaymentRecorded {
-                sender,
-                recipient,
-                amount,
-                voice_command,
-                timestamp,
-            });
+    /// Emitted when the owner grants or revokes the arbiter role
+    #[ink(event)]
+    pub struct ArbiterUpdated {
+        #[ink(topic)]
+        account: AccountId,
+        enabled: bool,
+    }
 
-            Ok(())
+    /// Emitted when a payer disputes a payment
+    #[ink(event)]
+    pub struct DisputeOpened {
+        #[ink(topic)]
+        dispute_id: DisputeId,
+        #[ink(topic)]
+        payer: AccountId,
+        #[ink(topic)]
+        recipient: AccountId,
+        payment_id: PaymentId,
+        evidence_hash: Hash,
+        deadline: Timestamp,
+    }
+
+    /// Emitted when the recipient adds funds to a dispute's escrow
+    #[ink(event)]
+    pub struct DisputeEscrowFunded {
+        #[ink(topic)]
+        dispute_id: DisputeId,
+        amount: Balance,
+        escrowed: Balance,
+    }
+
+    /// Emitted when a dispute is resolved or lapses
+    #[ink(event)]
+    pub struct DisputeClosed {
+        #[ink(topic)]
+        dispute_id: DisputeId,
+        status: DisputeStatus,
+        refunded: Balance,
+    }
+
+    /// Emitted when a user sets, schedules or removes a spending limit
+    #[ink(event)]
+    pub struct SpendingLimitChanged {
+        #[ink(topic)]
+        user: AccountId,
+        currency: String,
+        period: LimitPeriod,
+        amount: Option<Balance>,
+        effective_at: Timestamp,
+    }
+
+    /// Emitted when a user adds a recipient to, or removes one from, one of their lists
+    #[ink(event)]
+    pub struct RecipientListUpdated {
+        #[ink(topic)]
+        user: AccountId,
+        list: RecipientList,
+        #[ink(topic)]
+        recipient: AccountId,
+        added: bool,
+    }
+
+    /// Emitted when a user turns allowlist-only mode on or off
+    #[ink(event)]
+    pub struct AllowlistOnlyChanged {
+        #[ink(topic)]
+        user: AccountId,
+        enabled: bool,
+    }
+
+    /// Emitted when an account is added to or removed from the global deny list
+    #[ink(event)]
+    pub struct DenyListUpdated {
+        #[ink(topic)]
+        account: AccountId,
+        denied: bool,
+    }
+
+    /// Emitted when a user authorizes a delegate to record payments for them
+    #[ink(event)]
+    pub struct DelegateAuthorized {
+        #[ink(topic)]
+        user: AccountId,
+        #[ink(topic)]
+        delegate: AccountId,
+        currency: String,
+        expires_at: Timestamp,
+        max_per_payment: Balance,
+        total_cap: Balance,
+    }
+
+    /// Emitted when a user revokes a delegate
+    #[ink(event)]
+    pub struct DelegateRevoked {
+        #[ink(topic)]
+        user: AccountId,
+        #[ink(topic)]
+        delegate: AccountId,
+    }
+
+    /// Emitted when a relayer submits a signed payment intent
+    #[ink(event)]
+    pub struct SignedIntentRecorded {
+        #[ink(topic)]
+        signer: AccountId,
+        #[ink(topic)]
+        relayer: AccountId,
+        nonce: u64,
+        payment_id: PaymentId,
+    }
+
+    /// Emitted when payment recording is paused or resumed
+    #[ink(event)]
+    pub struct PausedChanged {
+        paused: bool,
+    }
+
+    /// Emitted when a council takes over, or its membership changes
+    #[ink(event)]
+    pub struct CouncilUpdated {
+        members: Vec<AccountId>,
+        threshold: u32,
+    }
+
+    /// Emitted when a council member proposes an admin action
+    #[ink(event)]
+    pub struct ProposalCreated {
+        #[ink(topic)]
+        proposal_id: ProposalId,
+        #[ink(topic)]
+        proposer: AccountId,
+        action: AdminAction,
+        expires_at: Timestamp,
+    }
+
+    /// Emitted when a council member approves a proposal
+    #[ink(event)]
+    pub struct ProposalApproved {
+        #[ink(topic)]
+        proposal_id: ProposalId,
+        #[ink(topic)]
+        member: AccountId,
+        approvals: u32,
+    }
+
+    /// Emitted when an approved proposal's action is performed
+    #[ink(event)]
+    pub struct ProposalExecuted {
+        #[ink(topic)]
+        proposal_id: ProposalId,
+    }
+
+    /// Emitted when an admin action is queued behind the timelock
+    #[ink(event)]
+    pub struct OperationScheduled {
+        #[ink(topic)]
+        operation_id: OperationId,
+        #[ink(topic)]
+        scheduled_by: AccountId,
+        action: AdminAction,
+        ready_at: Timestamp,
+    }
+
+    /// Emitted when a scheduled operation is executed
+    #[ink(event)]
+    pub struct OperationExecuted {
+        #[ink(topic)]
+        operation_id: OperationId,
+    }
+
+    /// Emitted when a scheduled operation is cancelled
+    #[ink(event)]
+    pub struct OperationCancelled {
+        #[ink(topic)]
+        operation_id: OperationId,
+    }
+
+    /// Emitted when a new owner is nominated
+    #[ink(event)]
+    pub struct OwnershipTransferStarted {
+        #[ink(topic)]
+        previous_owner: AccountId,
+        #[ink(topic)]
+        pending_owner: AccountId,
+    }
+
+    /// Emitted when the nominated owner accepts ownership
+    #[ink(event)]
+    pub struct OwnershipTransferred {
+        #[ink(topic)]
+        previous_owner: AccountId,
+        #[ink(topic)]
+        new_owner: AccountId,
+    }
+
+    /// Emitted when an account is granted a role
+    #[ink(event)]
+    pub struct RoleGranted {
+        #[ink(topic)]
+        role: Role,
+        #[ink(topic)]
+        account: AccountId,
+        sender: AccountId,
+    }
+
+    /// Emitted when an account loses a role
+    #[ink(event)]
+    pub struct RoleRevoked {
+        #[ink(topic)]
+        role: Role,
+        #[ink(topic)]
+        account: AccountId,
+        sender: AccountId,
+    }
+
+    /// Emitted when the role allowed to manage a role changes
+    #[ink(event)]
+    pub struct RoleAdminChanged {
+        #[ink(topic)]
+        role: Role,
+        previous_admin: Role,
+        new_admin: Role,
+    }
+
+    /// Emitted when a user nominates a guardian
+    #[ink(event)]
+    pub struct GuardianAdded {
+        #[ink(topic)]
+        user: AccountId,
+        #[ink(topic)]
+        guardian: AccountId,
+    }
+
+    /// Emitted when a user removes a guardian
+    #[ink(event)]
+    pub struct GuardianRemoved {
+        #[ink(topic)]
+        user: AccountId,
+        #[ink(topic)]
+        guardian: AccountId,
+    }
+
+    /// Emitted when a guardian freezes a user's voice payments
+    #[ink(event)]
+    pub struct AccountFrozen {
+        #[ink(topic)]
+        user: AccountId,
+        #[ink(topic)]
+        guardian: AccountId,
+        reason: String,
+    }
+
+    /// Emitted when a guardian approves unfreezing a user
+    #[ink(event)]
+    pub struct UnfreezeApproved {
+        #[ink(topic)]
+        user: AccountId,
+        #[ink(topic)]
+        guardian: AccountId,
+        approvals: u32,
+        threshold: u32,
+    }
+
+    /// Emitted when enough guardians have approved unfreezing a user
+    #[ink(event)]
+    pub struct AccountUnfrozen {
+        #[ink(topic)]
+        user: AccountId,
+    }
+
+    /// Contract errors
+    #[derive(Debug, PartialEq, Eq)]
+    #[ink::scale_derive(Encode, Decode, TypeInfo)]
+    pub enum Error {
+        /// Unauthorized access attempt
+        Unauthorized,
+        /// Invalid payment amount (zero or negative)
+        InvalidAmount,
+        /// Voice command string is empty or too long
+        InvalidVoiceCommand,
+        /// Currency string is invalid
+        InvalidCurrency,
+        /// Confidence score is out of valid range
+        InvalidConfidence,
+        /// Split has no recipients, too many recipients, a zero share or a duplicate recipient
+        InvalidSplit,
+        /// Summing the amounts overflowed
+        AmountOverflow,
+        /// Value sent with the call does not match the amount to pay
+        TransferredValueMismatch,
+        /// Transferring funds to a recipient failed
+        TransferFailed,
+        /// Batch contains more payments than `max_batch_size`
+        BatchTooLarge,
+        /// An item of an all-or-nothing batch failed; nothing was recorded
+        BatchItemFailed { index: u32 },
+        /// Configuration values are out of range
+        InvalidConfig,
+        /// Token contract is not registered with the recorder
+        TokenNotRegistered,
+        /// The token contract rejected or failed the transfer
+        TokenTransferFailed,
+        /// Asset is not registered with the recorder
+        AssetNotRegistered,
+        /// Payer's asset balance is lower than the amount
+        InsufficientAssetBalance,
+        /// The `pallet-assets` transfer failed or did not credit the recipient
+        AssetTransferFailed,
+        /// Contract was built without the `assets-extension` feature
+        AssetsUnsupported,
+        /// Invoice memo is too long
+        InvalidMemo,
+        /// Expiry timestamp is not in the future
+        InvalidExpiry,
+        /// No invoice exists with the given ID
+        InvoiceNotFound,
+        /// Invoice has already been paid or cancelled
+        InvoiceNotOpen,
+        /// Invoice expired before it was paid
+        InvoiceExpired,
+        /// No payment exists at the given position
+        PaymentNotFound,
+        /// Refund would exceed the amount still refundable
+        RefundExceedsPayment,
+        /// Refund reason is too long
+        InvalidReason,
+        /// No dispute exists with the given ID
+        DisputeNotFound,
+        /// The payment is too old, fully refunded or already disputed
+        PaymentNotDisputable,
+        /// Dispute has already been resolved or has lapsed
+        DisputeNotOpen,
+        /// The dispute's deadline has passed
+        DisputeDeadlinePassed,
+        /// The dispute's deadline has not passed yet
+        DisputeDeadlineNotReached,
+        /// Escrow would exceed the disputed amount
+        EscrowExceedsDispute,
+        /// Payment would exceed one of the sender's spending limits
+        SpendingLimitExceeded { remaining: Balance },
+        /// The sender's voice payments are frozen by a guardian
+        AccountFrozen,
+        /// The account is not frozen
+        AccountNotFrozen,
+        /// Guardian is already nominated, or is the user themself
+        InvalidGuardian,
+        /// User already has `MAX_GUARDIANS` guardians
+        TooManyGuardians,
+        /// Threshold is zero or larger than the number of guardians
+        InvalidThreshold,
+        /// Guardian has already approved unfreezing
+        AlreadyApproved,
+        /// Caller is not an authorized delegate of the user for this currency
+        DelegateNotAuthorized,
+        /// The delegate's authorization has expired
+        DelegateExpired,
+        /// Payment exceeds the delegate's per-payment or total cap
+        DelegateLimitExceeded,
+        /// Intent was not signed by its signer
+        InvalidSignature,
+        /// Intent nonce is not the signer's next nonce
+        InvalidNonce,
+        /// Intent expired before it was submitted
+        IntentExpired,
+        /// Voice command does not match the intent's command hash
+        CommandHashMismatch,
+        /// Payment recording is paused
+        ContractPaused,
+        /// Switching to the new contract code failed
+        UpgradeFailed,
+        /// Council has no members, duplicate members, too many members or an invalid threshold
+        InvalidCouncil,
+        /// No proposal exists with the given ID
+        ProposalNotFound,
+        /// Proposal expired before it was executed
+        ProposalExpired,
+        /// Proposal has already been executed
+        ProposalAlreadyExecuted,
+        /// Proposal does not have enough approvals yet
+        ThresholdNotMet,
+        /// No scheduled operation exists with the given ID
+        OperationNotFound,
+        /// Operation has already been executed or cancelled
+        OperationNotPending,
+        /// Operation's timelock delay has not passed yet
+        OperationNotReady,
+        /// Sender only pays allowlisted recipients and this one is not on the list
+        RecipientNotAllowed,
+        /// Recipient is on the sender's blocklist or the global deny list
+        RecipientBlocked,
+        /// Allowlist or blocklist already holds `MAX_RECIPIENT_LIST_LEN` entries
+        RecipientListFull,
+        /// Confidence policy has a threshold above 100 or too many tiers
+        InvalidConfidencePolicy,
+        /// Confidence is below the sender's policy and the payment cannot wait for confirmation
+        ConfidenceTooLow,
+        /// Payment is not awaiting confirmation
+        PaymentNotPending,
+        /// Confirmation window of the payment has passed
+        ConfirmationExpired,
+        /// Payment is still awaiting confirmation
+        PaymentNotConfirmed,
+        /// Locale is not a well-formed BCP 47 tag such as "pt-BR"
+        InvalidLocale,
+        /// Payment can still be confirmed, so it has not expired yet
+        ConfirmationWindowOpen,
+    }
+
+    /// Contract result type
+    pub type Result<T> = core::result::Result<T, Error>;
+
+    impl PaymentRecorder {
+        /// Creates a new PaymentRecorder contract instance
+        #[ink(constructor)]
+        pub fn new() -> Self {
+            Self {
+                payment_history: Mapping::new(),
+                owner: Self::env().caller(),
+                total_payments: 0,
+                payment_locations: Mapping::new(),
+                config: ContractConfig::default(),
+                registered_tokens: Mapping::new(),
+                registered_assets: Mapping::new(),
+                split_payments: Mapping::new(),
+                next_split_id: 0,
+                invoices: Mapping::new(),
+                merchant_invoices: Mapping::new(),
+                next_invoice_id: 0,
+                refunds: Mapping::new(),
+                arbiters: Mapping::new(),
+                disputes: Mapping::new(),
+                next_dispute_id: 0,
+                spending_limits: Mapping::new(),
+                spending_windows: Mapping::new(),
+                guardians: Mapping::new(),
+                frozen_accounts: Mapping::new(),
+                delegates: Mapping::new(),
+                intent_nonces: Mapping::new(),
+                paused: false,
+                council: None,
+                proposals: Mapping::new(),
+                next_proposal_id: 0,
+                roles: Mapping::new(),
+                role_holders: Mapping::new(),
+                role_holders_len: 0,
+                role_admins: Mapping::new(),
+                operations: Mapping::new(),
+                pending_positions: Mapping::new(),
+                pending_operations: Mapping::new(),
+                pending_operations_len: 0,
+                next_operation_id: 0,
+                pending_owner: None,
+                recipient_lists: Mapping::new(),
+                allowlist_only: Mapping::new(),
+                denied_accounts: Mapping::new(),
+                deny_list: Mapping::new(),
+                deny_list_len: 0,
+                confidence_policies: Mapping::new(),
+                pending_confirmations: Mapping::new(),
+                command_locales: Mapping::new(),
+                pending_delegate_charges: Mapping::new(),
+            }
         }
 
-        /// Retrieves payment history for a specific user
+        /// Records a new payment transaction
         /// 
         /// # Arguments
-        /// * `user` - The user's AccountId
+        /// * `recipient` - The recipient's AccountId
+        /// * `amount` - The payment amount in smallest unit
+        /// * `voice_command` - The original voice command
+        /// * `currency` - Currency type (e.g., "DOT", "WND")
+        /// * `network` - Network name (e.g., "polkadot", "rococo")
+        /// * `confidence` - Voice recognition confidence (0-100)
+        /// * `locale` - BCP 47 tag of the language the command was spoken in, if known
         /// 
         /// # Returns
-        /// * `Vec<PaymentRecord>` - List of payment records
+        /// * `Result<PaymentId>` - The ID of the new record
         #[ink(message)]
-        pub fn get_payment_history(&self, user: AccountId) -> Vec<PaymentRecord> {
-            self.payment_history.get(&user).unwrap_or_default()
+        #[allow(clippy::too_many_arguments)]
+        pub fn record_payment(
+            &mut self,
+            recipient: AccountId,
+            amount: Balance,
+            voice_command: String,
+            currency: String,
+            network: String,
+            confidence: u8,
+            locale: Option<String>,
+        ) -> Result<PaymentId> {
+            let input = PaymentInput {
+                recipient,
+                amount,
+                voice_command,
+                currency,
+                network,
+                confidence,
+                locale,
+            };
+            Self::validate_input(&input)?;
+
+            let sender = self.env().caller();
+            self.authorize_payment(sender, &input)?;
+            let record = self.new_record(input);
+            Ok(self.store_payment(sender, record))
         }
 
-        /// Retrieves the caller's own payment history
-        /// 
+        /// Records a payment on behalf of a user (authorized delegates only)
+        ///
+        /// The record is attributed to `user` and notes the delegate that
+        /// submitted it. The payment must be in the authorized currency, within
+        /// the per-payment cap and within what remains of the total cap, and it
+        /// is subject to the user's own spending limits and freezes. A payment
+        /// recorded below the user's confidence policy is charged to the total
+        /// cap only when the user confirms it.
+        ///
+        /// # Arguments
+        /// * `user` - The user the payment is recorded for
+        /// * `recipient` - The recipient's AccountId
+        /// * `amount` - The payment amount in smallest unit
+        /// * `voice_command` - The original voice command
+        /// * `currency` - Currency type (e.g., "DOT", "WND")
+        /// * `network` - Network name (e.g., "polkadot", "rococo")
+        /// * `confidence` - Voice recognition confidence (0-100)
+        /// * `locale` - BCP 47 tag of the language the command was spoken in, if known
+        ///
         /// # Returns
-        /// * `Vec<PaymentRecord>` - List of payment records for the caller
+        /// * `Result<PaymentId>` - The ID of the new record
         #[ink(message)]
-        pub fn get_my_payment_history(&self) -> Vec<PaymentRecord> {
-            let caller = self.env().caller();
-            self.get_payment_history(caller)
+        #[allow(clippy::too_many_arguments)]
+        pub fn record_payment_for(
+            &mut self,
+            user: AccountId,
+            recipient: AccountId,
+            amount: Balance,
+            voice_command: String,
+            currency: String,
+            network: String,
+            confidence: u8,
+            locale: Option<String>,
+        ) -> Result<PaymentId> {
+            let input = PaymentInput {
+                recipient,
+                amount,
+                voice_command,
+                currency,
+                network,
+                confidence,
+                locale,
+            };
+            Self::validate_input(&input)?;
+
+            let delegate = self.env().caller();
+            let key = (user, delegate);
+            let mut authorization = self.delegates.get(key).ok_or(Error::DelegateNotAuthorized)?;
+            if authorization.currency != input.currency {
+                return Err(Error::DelegateNotAuthorized);
+            }
+
+            if self.env().block_timestamp() > authorization.expires_at {
+                return Err(Error::DelegateExpired);
+            }
+
+            authorization.spent = authorization
+                .spent
+                .checked_add(amount)
+                .filter(|spent| amount <= authorization.max_per_payment && *spent <= authorization.total_cap)
+                .ok_or(Error::DelegateLimitExceeded)?;
+
+            self.authorize_payment(user, &input)?;
+            let pending = self.needs_confirmation(user, input.amount, input.confidence);
+            if !pending {
+                self.delegates.insert(key, &authorization);
+            }
+
+            let mut record = self.new_record(input);
+            record.submitted_by = Some(delegate);
+            let payment_id = self.store_payment(user, record);
+            if pending {
+                self.pending_delegate_charges.insert(payment_id, &delegate);
+            }
+            Ok(payment_id)
         }
 
-        /// Gets the total number of payments recorded
-        /// 
+        /// Records a payment intent signed off-chain by the sender's wallet
+        ///
+        /// Only accounts holding the relayer role may submit intents. The
+        /// signature is checked against the intent's signer, the nonce must be
+        /// the signer's next nonce, the intent must not have expired, and
+        /// `voice_command` must hash to the intent's command hash. The payment
+        /// is recorded as the signer's and notes the relayer that submitted it.
+        ///
+        /// # Arguments
+        /// * `intent` - The signed payment intent
+        /// * `signature` - The signer's sr25519 or ECDSA signature
+        /// * `voice_command` - The original voice command
+        ///
         /// # Returns
-        /// * `u64` - Total number of payments
+        /// * `Result<PaymentId>` - The ID of the new record
         #[ink(message)]
-        pub fn get_total_payments(&self) -> u64 {
-            self.total_payments
+        pub fn record_signed_intent(
+            &mut self,
+            intent: PaymentIntent,
+            signature: IntentSignature,
+            voice_command: String,
+        ) -> Result<PaymentId> {
+            self.ensure_role(Role::Relayer)?;
+            if self.env().block_timestamp() > intent.expires_at {
+                return Err(Error::IntentExpired);
+            }
+
+            let signer = intent.signer;
+            let nonce = self.intent_nonces.get(signer).unwrap_or(0);
+            if intent.nonce != nonce {
+                return Err(Error::InvalidNonce);
+            }
+
+            let command_hash = self.env().hash_bytes::<Blake2x256>(voice_command.as_bytes());
+            if Hash::from(command_hash) != intent.command_hash {
+                return Err(Error::CommandHashMismatch);
+            }
+
+            self.verify_intent_signature(&intent, &signature)?;
+
+            let input = PaymentInput {
+                recipient: intent.recipient,
+                amount: intent.amount,
+                voice_command,
+                currency: intent.currency,
+                network: intent.network,
+                confidence: intent.confidence,
+                locale: intent.locale,
+            };
+            Self::validate_input(&input)?;
+            self.authorize_payment(signer, &input)?;
+            self.intent_nonces.insert(signer, &(nonce + 1));
+
+            let relayer = self.env().caller();
+            let mut record = self.new_record(input);
+            if relayer != signer {
+                record.submitted_by = Some(relayer);
+            }
+            let payment_id = self.store_payment(signer, record);
+
+            self.env().emit_event(SignedIntentRecorded {
+                signer,
+                relayer,
+                nonce,
+                payment_id,
+            });
+
+            Ok(payment_id)
+        }
+
+        /// Gets the nonce the signer's next payment intent must use
+        ///
+        /// # Arguments
+        /// * `signer` - The signer's AccountId
+        ///
+        /// # Returns
+        /// * `u64` - The next intent nonce
+        #[ink(message)]
+        pub fn get_intent_nonce(&self, signer: AccountId) -> u64 {
+            self.intent_nonces.get(signer).unwrap_or(0)
+        }
+
+        /// Records several payments from the caller in one call
+        ///
+        /// In all-or-nothing mode (`atomic = true`) every item is validated and
+        /// authorized, in order and against the spending limits left after
+        /// the items before it, before any is stored. The call fails with
+        /// `BatchItemFailed` if any item is rejected, and the whole message
+        /// reverts so nothing is recorded or charged. In best-effort mode
+        /// each item is recorded independently and its outcome reported in
+        /// order.
+        ///
+        /// # Arguments
+        /// * `payments` - The payments to record, at most `max_batch_size`
+        /// * `atomic` - Whether a single invalid item rejects the whole batch
+        ///
+        /// # Returns
+        /// * `Result<Vec<Result<PaymentId>>>` - Per-item results, in input order
+        #[ink(message)]
+        pub fn record_payments_batch(
+            &mut self,
+            payments: Vec<PaymentInput>,
+            atomic: bool,
+        ) -> Result<Vec<Result<PaymentId>>> {
+            if payments.len() > self.config.max_batch_size as usize {
+                return Err(Error::BatchTooLarge);
+            }
+
+            let sender = self.env().caller();
+            if atomic {
+                let mut records = Vec::with_capacity(payments.len());
+                for (index, input) in payments.into_iter().enumerate() {
+                    let failed = |_| Error::BatchItemFailed { index: index as u32 };
+                    Self::validate_input(&input).map_err(failed)?;
+                    self.authorize_payment(sender, &input).map_err(failed)?;
+                    records.push(self.new_record(input));
+                }
+
+                return Ok(records
+                    .into_iter()
+                    .map(|record| Ok(self.store_payment(sender, record)))
+                    .collect());
+            }
+
+            let results = payments
+                .into_iter()
+                .map(|input| {
+                    Self::validate_input(&input)?;
+                    self.authorize_payment(sender, &input)?;
+                    let record = self.new_record(input);
+                    Ok(self.store_payment(sender, record))
+                })
+                .collect();
+
+            Ok(results)
+        }
+
+        /// Pays and records a payment in a registered PSP22 token
+        ///
+        /// The recorder calls `PSP22::transfer_from` on the token contract to
+        /// move `amount` from the caller to the recipient, so the caller must
+        /// first approve the recorder as a spender. The record's currency is
+        /// the symbol the token was registered with.
+        ///
+        /// # Arguments
+        /// * `token` - The registered PSP22 token contract
+        /// * `recipient` - The recipient's AccountId
+        /// * `amount` - The payment amount in the token's smallest unit
+        /// * `voice_command` - The original voice command
+        /// * `network` - Network name (e.g., "astar", "moonbeam")
+        /// * `confidence` - Voice recognition confidence (0-100)
+        /// * `locale` - BCP 47 tag of the language the command was spoken in, if known
+        ///
+        /// # Returns
+        /// * `Result<PaymentId>` - The ID of the new record
+        #[ink(message)]
+        #[allow(clippy::too_many_arguments)]
+        pub fn record_token_payment(
+            &mut self,
+            token: AccountId,
+            recipient: AccountId,
+            amount: Balance,
+            voice_command: String,
+            network: String,
+            confidence: u8,
+            locale: Option<String>,
+        ) -> Result<PaymentId> {
+            let token_info = self
+                .registered_tokens
+                .get(token)
+                .ok_or(Error::TokenNotRegistered)?;
+            let input = PaymentInput {
+                recipient,
+                amount,
+                voice_command,
+                currency: token_info.symbol,
+                network,
+                confidence,
+                locale,
+            };
+            Self::validate_input(&input)?;
+
+            let sender = self.env().caller();
+            self.ensure_confident(sender, amount, input.confidence)?;
+            self.authorize_payment(sender, &input)?;
+            self.psp22_transfer_from(token, sender, recipient, amount)?;
+
+            let mut record = self.new_record(input);
+            record.asset = PaymentAsset::Psp22(token);
+            Ok(self.store_payment(sender, record))
+        }
+
+        /// Pays and records a payment in a registered `pallet-assets` asset
+        ///
+        /// Uses the `pallet-assets` chain extension to check the caller's
+        /// balance, move `amount` from the caller to the recipient out of the
+        /// transfer the caller approved for this contract, and verify that the
+        /// recipient was credited. The record's currency is the symbol the
+        /// asset was registered with. Fails with `AssetsUnsupported` unless
+        /// the contract was built with the `assets-extension` feature.
+        ///
+        /// # Arguments
+        /// * `asset_id` - The registered asset (e.g. 1984 for USDT on Asset Hub)
+        /// * `recipient` - The recipient's AccountId
+        /// * `amount` - The payment amount in the asset's smallest unit
+        /// * `voice_command` - The original voice command
+        /// * `network` - Network name (e.g., "asset-hub-polkadot")
+        /// * `confidence` - Voice recognition confidence (0-100)
+        /// * `locale` - BCP 47 tag of the language the command was spoken in, if known
+        ///
+        /// # Returns
+        /// * `Result<PaymentId>` - The ID of the new record
+        #[ink(message)]
+        #[allow(clippy::too_many_arguments)]
+        pub fn record_asset_payment(
+            &mut self,
+            asset_id: AssetId,
+            recipient: AccountId,
+            amount: Balance,
+            voice_command: String,
+            network: String,
+            confidence: u8,
+            locale: Option<String>,
+        ) -> Result<PaymentId> {
+            let asset_info = self
+                .registered_assets
+                .get(asset_id)
+                .ok_or(Error::AssetNotRegistered)?;
+            let input = PaymentInput {
+                recipient,
+                amount,
+                voice_command,
+                currency: asset_info.symbol,
+                network,
+                confidence,
+                locale,
+            };
+            Self::validate_input(&input)?;
+
+            let sender = self.env().caller();
+            self.ensure_confident(sender, amount, input.confidence)?;
+            self.authorize_payment(sender, &input)?;
+            self.assets_transfer(asset_id, sender, recipient, amount)?;
+
+            let mut record = self.new_record(input);
+            record.asset = PaymentAsset::PalletAsset(asset_id);
+            Ok(self.store_payment(sender, record))
+        }
+
+        /// Records one voice command that pays several recipients
+        ///
+        /// Every share is validated before anything is stored, so either all
+        /// records are written or none are. Each share is appended to the
+        /// caller's history under a common split ID. If value is sent with the
+        /// call it must equal the sum of the shares, and each share is then
+        /// transferred to its recipient.
+        ///
+        /// # Arguments
+        /// * `shares` - Recipients with the amount each one receives
+        /// * `voice_command` - The original voice command
+        /// * `currency` - Currency type (e.g., "DOT", "WND")
+        /// * `network` - Network name (e.g., "polkadot", "rococo")
+        /// * `confidence` - Voice recognition confidence (0-100)
+        /// * `locale` - BCP 47 tag of the language the command was spoken in, if known
+        ///
+        /// # Returns
+        /// * `Result<SplitId>` - The split ID grouping the new records
+        #[ink(message, payable)]
+        pub fn record_split_payment(
+            &mut self,
+            shares: Vec<(AccountId, Balance)>,
+            voice_command: String,
+            currency: String,
+            network: String,
+            confidence: u8,
+            locale: Option<String>,
+        ) -> Result<SplitId> {
+            if shares.is_empty() || shares.len() > MAX_SPLIT_RECIPIENTS {
+                return Err(Error::InvalidSplit);
+            }
+            Self::validate_command(&voice_command, &currency, confidence)?;
+            if let Some(locale) = &locale {
+                Self::validate_locale(locale)?;
+            }
+
+            let mut total_amount: Balance = 0;
+            for (index, (recipient, amount)) in shares.iter().enumerate() {
+                if *amount == 0 || shares[..index].iter().any(|(other, _)| other == recipient) {
+                    return Err(Error::InvalidSplit);
+                }
+                total_amount = total_amount
+                    .checked_add(*amount)
+                    .ok_or(Error::AmountOverflow)?;
+            }
+
+            let sender = self.env().caller();
+            let inputs: Vec<PaymentInput> = shares
+                .iter()
+                .map(|(recipient, amount)| PaymentInput {
+                    recipient: *recipient,
+                    amount: *amount,
+                    voice_command: voice_command.clone(),
+                    currency: currency.clone(),
+                    network: network.clone(),
+                    confidence,
+                    locale: locale.clone(),
+                })
+                .collect();
+
+            let transferred_value = self.env().transferred_value();
+            let transferred = transferred_value > 0;
+            if transferred {
+                if transferred_value != total_amount {
+                    return Err(Error::TransferredValueMismatch);
+                }
+                self.ensure_confident(sender, total_amount, confidence)?;
+            }
+
+            for input in inputs.iter() {
+                self.authorize_payment(sender, input)?;
+            }
+
+            if transferred {
+                for (recipient, amount) in shares.iter() {
+                    self.env()
+                        .transfer(*recipient, *amount)
+                        .map_err(|_| Error::TransferFailed)?;
+                }
+            }
+
+            let timestamp = self.env().block_timestamp();
+            let split_id = self.next_split_id;
+            self.next_split_id += 1;
+
+            let mut payment_ids = Vec::with_capacity(shares.len());
+            for input in inputs {
+                let mut record = self.new_record(input);
+                record.split_id = Some(split_id);
+                payment_ids.push(self.store_payment(sender, record));
+            }
+
+            let recipients = shares.len() as u32;
+            self.split_payments.insert(
+                split_id,
+                &SplitPayment {
+                    payer: sender,
+                    shares,
+                    payment_ids,
+                    total_amount,
+                    voice_command,
+                    currency,
+                    network,
+                    timestamp,
+                    transferred,
+                },
+            );
+
+            self.env().emit_event(SplitPaymentRecorded {
+                split_id,
+                sender,
+                total_amount,
+                recipients,
+                transferred,
+            });
+
+            Ok(split_id)
+        }
+
+        /// Gets a split payment by its identifier
+        ///
+        /// # Arguments
+        /// * `split_id` - The split ID returned by `record_split_payment`
+        ///
+        /// # Returns
+        /// * `Option<SplitPayment>` - The split payment, if it exists
+        #[ink(message)]
+        pub fn get_split_payment(&self, split_id: SplitId) -> Option<SplitPayment> {
+            self.split_payments.get(split_id)
+        }
+
+        /// Gets a single payment record by its ID
+        ///
+        /// # Arguments
+        /// * `payment_id` - The ID returned when the payment was recorded
+        ///
+        /// # Returns
+        /// * `Option<PaymentRecord>` - The payment record, if it exists
+        #[ink(message)]
+        pub fn get_payment(&self, payment_id: PaymentId) -> Option<PaymentRecord> {
+            let (sender, index) = self.payment_locations.get(payment_id)?;
+            self.payment_history
+                .get(sender)
+                .and_then(|history| history.get(index as usize).cloned())
+        }
+
+        /// Issues an invoice that customers can pay by voice
+        ///
+        /// # Arguments
+        /// * `amount` - Amount due in smallest unit
+        /// * `currency` - Currency type (e.g., "DOT", "WND")
+        /// * `memo` - Description shown to the customer
+        /// * `expires_at` - Timestamp after which the invoice can no longer be paid
+        ///
+        /// # Returns
+        /// * `Result<InvoiceId>` - The new invoice's ID
+        #[ink(message)]
+        pub fn create_invoice(
+            &mut self,
+            amount: Balance,
+            currency: String,
+            memo: String,
+            expires_at: Timestamp,
+        ) -> Result<InvoiceId> {
+            if amount == 0 {
+                return Err(Error::InvalidAmount);
+            }
+
+            if currency.is_empty() || currency.len() > MAX_CURRENCY_LEN {
+                return Err(Error::InvalidCurrency);
+            }
+
+            if memo.len() > MAX_MEMO_LEN {
+                return Err(Error::InvalidMemo);
+            }
+
+            let now = self.env().block_timestamp();
+            if expires_at <= now {
+                return Err(Error::InvalidExpiry);
+            }
+
+            let merchant = self.env().caller();
+            let invoice_id = self.next_invoice_id;
+            self.next_invoice_id += 1;
+
+            self.env().emit_event(InvoiceCreated {
+                invoice_id,
+                merchant,
+                amount,
+                currency: currency.clone(),
+                expires_at,
+            });
+
+            self.invoices.insert(
+                invoice_id,
+                &Invoice {
+                    id: invoice_id,
+                    merchant,
+                    amount,
+                    currency,
+                    memo,
+                    created_at: now,
+                    expires_at,
+                    status: InvoiceStatus::Open,
+                    payer: None,
+                    payment_id: None,
+                },
+            );
+            let mut issued = self.merchant_invoices.get(merchant).unwrap_or_default();
+            issued.push(invoice_id);
+            self.merchant_invoices.insert(merchant, &issued);
+
+            Ok(invoice_id)
+        }
+
+        /// Pays an open invoice and records the payment to its merchant
+        ///
+        /// If value is sent with the call it must equal the invoice amount and
+        /// is transferred to the merchant. A payment recorded below the
+        /// payer's confidence policy leaves the invoice `AwaitingConfirmation`
+        /// until it is confirmed, and reopens it if it expires.
+        ///
+        /// # Arguments
+        /// * `invoice_id` - The invoice to pay
+        /// * `voice_command` - The original voice command (e.g. "pay invoice 42")
+        /// * `network` - Network name (e.g., "polkadot", "rococo")
+        /// * `confidence` - Voice recognition confidence (0-100)
+        /// * `locale` - BCP 47 tag of the language the command was spoken in, if known
+        ///
+        /// # Returns
+        /// * `Result<PaymentId>` - The ID of the new record
+        #[ink(message, payable)]
+        pub fn pay_invoice(
+            &mut self,
+            invoice_id: InvoiceId,
+            voice_command: String,
+            network: String,
+            confidence: u8,
+            locale: Option<String>,
+        ) -> Result<PaymentId> {
+            let mut invoice = self.invoices.get(invoice_id).ok_or(Error::InvoiceNotFound)?;
+            if invoice.status != InvoiceStatus::Open {
+                return Err(Error::InvoiceNotOpen);
+            }
+
+            if self.env().block_timestamp() > invoice.expires_at {
+                return Err(Error::InvoiceExpired);
+            }
+
+            let input = PaymentInput {
+                recipient: invoice.merchant,
+                amount: invoice.amount,
+                voice_command,
+                currency: invoice.currency.clone(),
+                network,
+                confidence,
+                locale,
+            };
+            Self::validate_input(&input)?;
+
+            let payer = self.env().caller();
+            let transferred_value = self.env().transferred_value();
+            let transferred = transferred_value > 0;
+            if transferred {
+                if transferred_value != invoice.amount {
+                    return Err(Error::TransferredValueMismatch);
+                }
+                self.ensure_confident(payer, input.amount, input.confidence)?;
+            }
+
+            self.authorize_payment(payer, &input)?;
+            if transferred {
+                self.env()
+                    .transfer(invoice.merchant, invoice.amount)
+                    .map_err(|_| Error::TransferFailed)?;
+            }
+
+            let mut record = self.new_record(input);
+            record.invoice_id = Some(invoice_id);
+            let payment_id = self.store_payment(payer, record);
+
+            let pending = self.pending_confirmations.contains(payment_id);
+            invoice.status = if pending {
+                InvoiceStatus::AwaitingConfirmation
+            } else {
+                InvoiceStatus::Paid
+            };
+            invoice.payer = Some(payer);
+            invoice.payment_id = Some(payment_id);
+            self.invoices.insert(invoice_id, &invoice);
+
+            if !pending {
+                self.env().emit_event(InvoicePaid {
+                    invoice_id,
+                    payer,
+                    payment_id,
+                    transferred,
+                });
+            }
+
+            Ok(payment_id)
+        }
+
+        /// Cancels an open invoice (merchant only)
+        ///
+        /// # Arguments
+        /// * `invoice_id` - The invoice to cancel
+        ///
+        /// # Returns
+        /// * `Result<()>` - Success or error
+        #[ink(message)]
+        pub fn cancel_invoice(&mut self, invoice_id: InvoiceId) -> Result<()> {
+            let mut invoice = self.invoices.get(invoice_id).ok_or(Error::InvoiceNotFound)?;
+            if self.env().caller() != invoice.merchant {
+                return Err(Error::Unauthorized);
+            }
+
+            if invoice.status != InvoiceStatus::Open {
+                return Err(Error::InvoiceNotOpen);
+            }
+
+            invoice.status = InvoiceStatus::Cancelled;
+            self.invoices.insert(invoice_id, &invoice);
+
+            self.env().emit_event(InvoiceCancelled { invoice_id });
+
+            Ok(())
+        }
+
+        /// Gets an invoice, reporting open invoices past their expiry as `Expired`
+        ///
+        /// # Arguments
+        /// * `invoice_id` - The invoice's ID
+        ///
+        /// # Returns
+        /// * `Option<Invoice>` - The invoice, if it exists
+        #[ink(message)]
+        pub fn get_invoice(&self, invoice_id: InvoiceId) -> Option<Invoice> {
+            self.invoices
+                .get(invoice_id)
+                .map(|invoice| self.with_current_status(invoice))
+        }
+
+        /// Gets a merchant's invoices that can still be paid, oldest first
+        ///
+        /// # Arguments
+        /// * `merchant` - The merchant's AccountId
+        /// * `offset` - Number of open invoices to skip
+        /// * `limit` - Maximum number of invoices to return
+        ///
+        /// # Returns
+        /// * `Vec<Invoice>` - Open invoices
+        #[ink(message)]
+        pub fn get_open_invoices(&self, merchant: AccountId, offset: u32, limit: u32) -> Vec<Invoice> {
+            self.merchant_invoices
+                .get(merchant)
+                .unwrap_or_default()
+                .into_iter()
+                .filter_map(|invoice_id| self.get_invoice(invoice_id))
+                .filter(|invoice| invoice.status == InvoiceStatus::Open)
+                .skip(offset as usize)
+                .take(limit as usize)
+                .collect()
+        }
+
+        /// Refunds part or all of a payment (original recipient only)
+        ///
+        /// The refund is tracked against the original record, whose status
+        /// becomes `PartiallyRefunded` or `Refunded`. If value is sent with the
+        /// call it must equal `amount` and is transferred back to the original
+        /// sender.
+        ///
+        /// # Arguments
+        /// * `original_sender` - The account that made the payment
+        /// * `payment_index` - Position of the payment in the sender's history
+        /// * `amount` - Amount to refund in smallest unit
+        /// * `reason` - Why the payment is refunded
+        ///
+        /// # Returns
+        /// * `Result<()>` - Success or error
+        #[ink(message, payable)]
+        pub fn record_refund(
+            &mut self,
+            original_sender: AccountId,
+            payment_index: u32,
+            amount: Balance,
+            reason: String,
+        ) -> Result<()> {
+            let mut history = self.payment_history.get(original_sender).unwrap_or_default();
+            let record = history
+                .get_mut(payment_index as usize)
+                .ok_or(Error::PaymentNotFound)?;
+
+            let refunder = self.env().caller();
+            if refunder != record.recipient {
+                return Err(Error::Unauthorized);
+            }
+
+            if matches!(record.status, PaymentStatus::PendingConfirmation | PaymentStatus::Expired) {
+                return Err(Error::PaymentNotConfirmed);
+            }
+
+            if amount == 0 {
+                return Err(Error::InvalidAmount);
+            }
+
+            if reason.len() > MAX_MEMO_LEN {
+                return Err(Error::InvalidReason);
+            }
+
+            if record.refunded_amount.saturating_add(amount) > record.amount {
+                return Err(Error::RefundExceedsPayment);
+            }
+
+            let transferred_value = self.env().transferred_value();
+            let transferred = transferred_value > 0;
+            if transferred {
+                if transferred_value != amount {
+                    return Err(Error::TransferredValueMismatch);
+                }
+                self.env()
+                    .transfer(original_sender, amount)
+                    .map_err(|_| Error::TransferFailed)?;
+            }
+
+            self.apply_refund(original_sender, payment_index, refunder, amount, reason, transferred);
+            Ok(())
+        }
+
+        /// Gets the refunds recorded against a payment, oldest first
+        ///
+        /// # Arguments
+        /// * `original_sender` - The account that made the payment
+        /// * `payment_index` - Position of the payment in the sender's history
+        ///
+        /// # Returns
+        /// * `Vec<RefundRecord>` - Refunds of the payment
+        #[ink(message)]
+        pub fn get_refunds(&self, original_sender: AccountId, payment_index: u32) -> Vec<RefundRecord> {
+            self.payment_history
+                .get(original_sender)
+                .and_then(|history| history.get(payment_index as usize).map(|record| record.id))
+                .and_then(|payment_id| self.refunds.get(payment_id))
+                .unwrap_or_default()
+        }
+
+        /// Grants or revokes the arbiter role (admin role)
+        ///
+        /// # Arguments
+        /// * `account` - The account to update
+        /// * `enabled` - Whether the account may resolve disputes
+        ///
+        /// # Returns
+        /// * `Result<()>` - Success or error
+        #[ink(message)]
+        pub fn set_arbiter(&mut self, account: AccountId, enabled: bool) -> Result<()> {
+            self.ensure_role(Role::Admin)?;
+            self.apply_admin_action(AdminAction::SetArbiter(account, enabled))
+        }
+
+        /// Checks whether an account may resolve disputes
+        ///
+        /// # Arguments
+        /// * `account` - The account to check
+        ///
+        /// # Returns
+        /// * `bool` - Whether the account is an arbiter
+        #[ink(message)]
+        pub fn is_arbiter(&self, account: AccountId) -> bool {
+            self.arbiters.contains(account)
+        }
+
+        /// Disputes one of the caller's payments (payer only)
+        ///
+        /// Must be called within `dispute_window` of the payment. The payment's
+        /// status becomes `Disputed` until the dispute is closed.
+        ///
+        /// # Arguments
+        /// * `payment_id` - The disputed payment
+        /// * `evidence_hash` - Hash of the evidence kept off-chain
+        ///
+        /// # Returns
+        /// * `Result<DisputeId>` - The new dispute's ID
+        #[ink(message)]
+        pub fn open_dispute(&mut self, payment_id: PaymentId, evidence_hash: Hash) -> Result<DisputeId> {
+            let (sender, index) = self
+                .payment_locations
+                .get(payment_id)
+                .ok_or(Error::PaymentNotFound)?;
+            let payer = self.env().caller();
+            if payer != sender {
+                return Err(Error::Unauthorized);
+            }
+
+            let mut history = self.payment_history.get(sender).unwrap_or_default();
+            let record = &mut history[index as usize];
+            let now = self.env().block_timestamp();
+            let disputable = matches!(
+                record.status,
+                PaymentStatus::Completed | PaymentStatus::PartiallyRefunded
+            );
+            if !disputable || now > record.timestamp.saturating_add(self.config.dispute_window) {
+                return Err(Error::PaymentNotDisputable);
+            }
+
+            let dispute_id = self.next_dispute_id;
+            self.next_dispute_id += 1;
+            let dispute = Dispute {
+                id: dispute_id,
+                payment_id,
+                payer,
+                recipient: record.recipient,
+                disputed_amount: record.amount - record.refunded_amount,
+                evidence_hash,
+                opened_at: now,
+                deadline: now.saturating_add(self.config.dispute_resolution_period),
+                escrowed: 0,
+                status: DisputeStatus::Open,
+            };
+
+            record.status = PaymentStatus::Disputed;
+            self.payment_history.insert(sender, &history);
+
+            self.env().emit_event(DisputeOpened {
+                dispute_id,
+                payer,
+                recipient: dispute.recipient,
+                payment_id,
+                evidence_hash,
+                deadline: dispute.deadline,
+            });
+            self.disputes.insert(dispute_id, &dispute);
+
+            Ok(dispute_id)
+        }
+
+        /// Places funds in escrow for an open dispute (recipient only)
+        ///
+        /// The value sent with the call is held by the contract until the
+        /// dispute is closed. The total escrow cannot exceed the disputed amount.
+        ///
+        /// # Arguments
+        /// * `dispute_id` - The dispute to fund
+        ///
+        /// # Returns
+        /// * `Result<()>` - Success or error
+        #[ink(message, payable)]
+        pub fn escrow_dispute_funds(&mut self, dispute_id: DisputeId) -> Result<()> {
+            let mut dispute = self.open_dispute_by_id(dispute_id)?;
+            if self.env().caller() != dispute.recipient {
+                return Err(Error::Unauthorized);
+            }
+
+            let amount = self.env().transferred_value();
+            if amount == 0 {
+                return Err(Error::InvalidAmount);
+            }
+
+            dispute.escrowed = dispute
+                .escrowed
+                .checked_add(amount)
+                .filter(|escrowed| *escrowed <= dispute.disputed_amount)
+                .ok_or(Error::EscrowExceedsDispute)?;
+            self.disputes.insert(dispute_id, &dispute);
+
+            self.env().emit_event(DisputeEscrowFunded {
+                dispute_id,
+                amount,
+                escrowed: dispute.escrowed,
+            });
+
+            Ok(())
+        }
+
+        /// Resolves an open dispute before its deadline (arbiter only)
+        ///
+        /// With `RefundPayer` the escrowed funds are transferred to the payer
+        /// and recorded as a refund of the payment. With `RejectClaim` they are
+        /// returned to the recipient.
+        ///
+        /// # Arguments
+        /// * `dispute_id` - The dispute to resolve
+        /// * `outcome` - The arbiter's decision
+        ///
+        /// # Returns
+        /// * `Result<()>` - Success or error
+        #[ink(message)]
+        pub fn resolve_dispute(&mut self, dispute_id: DisputeId, outcome: DisputeOutcome) -> Result<()> {
+            if !self.arbiters.contains(self.env().caller()) {
+                return Err(Error::Unauthorized);
+            }
+
+            let dispute = self.open_dispute_by_id(dispute_id)?;
+            if self.env().block_timestamp() > dispute.deadline {
+                return Err(Error::DisputeDeadlinePassed);
+            }
+
+            self.close_dispute(dispute, DisputeStatus::Resolved(outcome))
+        }
+
+        /// Closes a dispute nobody resolved before its deadline
+        ///
+        /// Anyone may call this. Escrowed funds go back to the recipient and
+        /// the payment's status is restored.
+        ///
+        /// # Arguments
+        /// * `dispute_id` - The lapsed dispute
+        ///
+        /// # Returns
+        /// * `Result<()>` - Success or error
+        #[ink(message)]
+        pub fn close_lapsed_dispute(&mut self, dispute_id: DisputeId) -> Result<()> {
+            let dispute = self.open_dispute_by_id(dispute_id)?;
+            if self.env().block_timestamp() <= dispute.deadline {
+                return Err(Error::DisputeDeadlineNotReached);
+            }
+
+            self.close_dispute(dispute, DisputeStatus::Lapsed)
+        }
+
+        /// Gets a dispute by its identifier
+        ///
+        /// # Arguments
+        /// * `dispute_id` - The dispute's ID
+        ///
+        /// # Returns
+        /// * `Option<Dispute>` - The dispute, if it exists
+        #[ink(message)]
+        pub fn get_dispute(&self, dispute_id: DisputeId) -> Option<Dispute> {
+            self.disputes.get(dispute_id)
+        }
+
+        /// Sets the caller's limit on voice payments in a currency per period
+        ///
+        /// Lowering a limit, or adding one where none existed, applies
+        /// immediately. Raising or removing (`None`) a limit only applies after
+        /// `limit_increase_delay`, so a stolen device cannot lift the cap.
+        ///
+        /// # Arguments
+        /// * `currency` - Currency the limit applies to (e.g., "DOT")
+        /// * `period` - Whether the limit is per day or per week
+        /// * `amount` - The new limit in smallest unit, or `None` to remove it
+        ///
+        /// # Returns
+        /// * `Result<()>` - Success or error
+        #[ink(message)]
+        pub fn set_spending_limit(
+            &mut self,
+            currency: String,
+            period: LimitPeriod,
+            amount: Option<Balance>,
+        ) -> Result<()> {
+            if currency.is_empty() || currency.len() > MAX_CURRENCY_LEN {
+                return Err(Error::InvalidCurrency);
+            }
+
+            let user = self.env().caller();
+            let now = self.env().block_timestamp();
+            let key = (user, currency, period);
+            let current = self
+                .spending_limits
+                .get(&key)
+                .map(|limit| Self::effective_limit(limit, now));
+
+            let effective_at = match (current, amount) {
+                (None, None) => return Ok(()),
+                (Some(current), Some(new)) if new <= current.amount => {
+                    self.spending_limits.insert(&key, &SpendingLimit { amount: new, pending: None });
+                    now
+                }
+                (None, Some(new)) => {
+                    self.spending_limits.insert(&key, &SpendingLimit { amount: new, pending: None });
+                    now
+                }
+                (Some(mut current), _) => {
+                    let effective_at = now.saturating_add(self.config.limit_increase_delay);
+                    current.pending = Some(PendingLimit { amount, effective_at });
+                    self.spending_limits.insert(&key, &current);
+                    effective_at
+                }
+            };
+
+            let (user, currency, period) = key;
+            self.env().emit_event(SpendingLimitChanged {
+                user,
+                currency,
+                period,
+                amount,
+                effective_at,
+            });
+
+            Ok(())
+        }
+
+        /// Gets a user's spending limit, with any pending change that is now due applied
+        ///
+        /// # Arguments
+        /// * `user` - The user's AccountId
+        /// * `currency` - Currency the limit applies to
+        /// * `period` - Whether the limit is per day or per week
+        ///
+        /// # Returns
+        /// * `Option<SpendingLimit>` - The limit, if one is set
+        #[ink(message)]
+        pub fn get_spending_limit(
+            &self,
+            user: AccountId,
+            currency: String,
+            period: LimitPeriod,
+        ) -> Option<SpendingLimit> {
+            let now = self.env().block_timestamp();
+            self.spending_limits
+                .get((user, currency, period))
+                .map(|limit| Self::effective_limit(limit, now))
+                .filter(|limit| limit.amount != Balance::MAX || limit.pending.is_some())
+        }
+
+        /// Gets how much more a user can spend in the current period
+        ///
+        /// # Arguments
+        /// * `user` - The user's AccountId
+        /// * `currency` - Currency the limit applies to
+        /// * `period` - Whether the limit is per day or per week
+        ///
+        /// # Returns
+        /// * `Option<Balance>` - Remaining allowance, or `None` if no limit is set
+        #[ink(message)]
+        pub fn get_remaining_allowance(
+            &self,
+            user: AccountId,
+            currency: String,
+            period: LimitPeriod,
+        ) -> Option<Balance> {
+            let limit = self.get_spending_limit(user, currency.clone(), period)?;
+            let spent = self.spent_in_window(&(user, currency, period));
+            Some(limit.amount.saturating_sub(spent))
+        }
+
+        /// Sets or clears the caller's minimum confidence policy
+        ///
+        /// Payments recorded below the policy are stored as
+        /// `PendingConfirmation` and must be confirmed with `confirm_pending`.
+        /// Payments that move funds are rejected instead.
+        ///
+        /// # Arguments
+        /// * `policy` - The new policy, or `None` to accept any confidence
+        ///
+        /// # Returns
+        /// * `Result<()>` - Success or error
+        #[ink(message)]
+        pub fn set_confidence_policy(&mut self, policy: Option<ConfidencePolicy>) -> Result<()> {
+            let user = self.env().caller();
+            match &policy {
+                Some(policy) => {
+                    if policy.min_confidence > 100
+                        || policy.tiers.len() > MAX_CONFIDENCE_TIERS
+                        || policy.tiers.iter().any(|tier| tier.min_confidence > 100)
+                    {
+                        return Err(Error::InvalidConfidencePolicy);
+                    }
+                    self.confidence_policies.insert(user, policy);
+                }
+                None => self.confidence_policies.remove(user),
+            }
+
+            self.env().emit_event(ConfidencePolicyChanged { user, policy });
+            Ok(())
+        }
+
+        /// Gets a user's confidence policy
+        ///
+        /// # Arguments
+        /// * `user` - The user's AccountId
+        ///
+        /// # Returns
+        /// * `Option<ConfidencePolicy>` - The policy, if one is set
+        #[ink(message)]
+        pub fn get_confidence_policy(&self, user: AccountId) -> Option<ConfidencePolicy> {
+            self.confidence_policies.get(user)
+        }
+
+        /// Sets or clears the locale the caller speaks voice commands in
+        ///
+        /// Clients read it to pick the grammar they parse the caller's
+        /// commands with. Payments store the locale each command was spoken
+        /// in, which they are given with the command, not this setting.
+        ///
+        /// # Arguments
+        /// * `locale` - A BCP 47 tag such as "es" or "pt-BR", or `None` to clear it
+        ///
+        /// # Returns
+        /// * `Result<()>` - Success or error
+        #[ink(message)]
+        pub fn set_command_locale(&mut self, locale: Option<String>) -> Result<()> {
+            let user = self.env().caller();
+            match &locale {
+                Some(locale) => {
+                    Self::validate_locale(locale)?;
+                    self.command_locales.insert(user, locale);
+                }
+                None => self.command_locales.remove(user),
+            }
+
+            self.env().emit_event(CommandLocaleChanged { user, locale });
+            Ok(())
+        }
+
+        /// Gets the locale a user speaks voice commands in
+        ///
+        /// # Arguments
+        /// * `user` - The user's AccountId
+        ///
+        /// # Returns
+        /// * `Option<String>` - The locale, if one is set
+        #[ink(message)]
+        pub fn get_command_locale(&self, user: AccountId) -> Option<String> {
+            self.command_locales.get(user)
+        }
+
+        /// Confirms a payment recorded below the caller's confidence policy
+        ///
+        /// Must be called by the payment's sender within `confirmation_window`
+        /// of recording. The payment is checked against the restrictions that
+        /// apply when recording and only now counts against the sender's
+        /// spending limits and the cap of the delegate that recorded it, and an
+        /// invoice it pays becomes `Paid`. Unconfirmed
+        /// payments stay `PendingConfirmation` until `expire_pending` is called
+        /// for them.
+        ///
+        /// # Arguments
+        /// * `payment_id` - The pending payment's ID
+        ///
+        /// # Returns
+        /// * `Result<()>` - Success or error
+        #[ink(message)]
+        pub fn confirm_pending(&mut self, payment_id: PaymentId) -> Result<()> {
+            let (sender, index) = self
+                .payment_locations
+                .get(payment_id)
+                .ok_or(Error::PaymentNotFound)?;
+            if self.env().caller() != sender {
+                return Err(Error::Unauthorized);
+            }
+
+            let expires_at = self
+                .pending_confirmations
+                .get(payment_id)
+                .ok_or(Error::PaymentNotPending)?;
+            if self.env().block_timestamp() > expires_at {
+                return Err(Error::ConfirmationExpired);
+            }
+
+            let mut history = self.payment_history.get(sender).unwrap_or_default();
+            let record = &mut history[index as usize];
+            self.ensure_can_pay(sender, record.recipient)?;
+            if let Some(delegate) = self.pending_delegate_charges.get(payment_id) {
+                self.charge_delegate(sender, delegate, record.amount)?;
+            }
+            self.charge_spending_limits(sender, &record.currency, record.amount)?;
+            record.status = PaymentStatus::Completed;
+            let invoice_id = record.invoice_id;
+            self.payment_history.insert(sender, &history);
+            self.pending_confirmations.remove(payment_id);
+            self.pending_delegate_charges.remove(payment_id);
+
+            self.env().emit_event(PaymentConfirmed { payment_id, sender });
+            if let Some(invoice_id) = invoice_id {
+                let mut invoice = self.invoices.get(invoice_id).ok_or(Error::InvoiceNotFound)?;
+                invoice.status = InvoiceStatus::Paid;
+                self.invoices.insert(invoice_id, &invoice);
+                self.env().emit_event(InvoicePaid {
+                    invoice_id,
+                    payer: sender,
+                    payment_id,
+                    transferred: false,
+                });
+            }
+            Ok(())
+        }
+
+        /// Marks a payment that was not confirmed in time as `Expired` (anyone)
+        ///
+        /// Expired payments stay in the sender's history but are never
+        /// charged to spending limits or counted in statistics. An invoice the
+        /// payment was for is open to be paid again.
+        ///
+        /// # Arguments
+        /// * `payment_id` - The pending payment's ID
+        ///
+        /// # Returns
+        /// * `Result<()>` - Success or error
+        #[ink(message)]
+        pub fn expire_pending(&mut self, payment_id: PaymentId) -> Result<()> {
+            let expires_at = self
+                .pending_confirmations
+                .get(payment_id)
+                .ok_or(Error::PaymentNotPending)?;
+            if self.env().block_timestamp() <= expires_at {
+                return Err(Error::ConfirmationWindowOpen);
+            }
+
+            let (sender, index) = self
+                .payment_locations
+                .get(payment_id)
+                .ok_or(Error::PaymentNotFound)?;
+            let mut history = self.payment_history.get(sender).unwrap_or_default();
+            let record = &mut history[index as usize];
+            record.status = PaymentStatus::Expired;
+            let invoice_id = record.invoice_id;
+            self.payment_history.insert(sender, &history);
+            self.pending_confirmations.remove(payment_id);
+            self.pending_delegate_charges.remove(payment_id);
+
+            if let Some(mut invoice) = invoice_id.and_then(|invoice_id| self.invoices.get(invoice_id)) {
+                invoice.status = InvoiceStatus::Open;
+                invoice.payer = None;
+                invoice.payment_id = None;
+                self.invoices.insert(invoice.id, &invoice);
+            }
+
+            self.env().emit_event(PendingPaymentExpired { payment_id, sender });
+            Ok(())
+        }
+
+        /// Gets the confirmation deadline of a payment awaiting confirmation
+        ///
+        /// # Arguments
+        /// * `payment_id` - The payment's ID
+        ///
+        /// # Returns
+        /// * `Option<Timestamp>` - The deadline, if the payment is pending
+        #[ink(message)]
+        pub fn get_confirmation_deadline(&self, payment_id: PaymentId) -> Option<Timestamp> {
+            self.pending_confirmations.get(payment_id)
+        }
+
+        /// Adds a recipient to the caller's allowlist or blocklist
+        ///
+        /// # Arguments
+        /// * `list` - The list to add to
+        /// * `recipient` - The recipient's AccountId
+        ///
+        /// # Returns
+        /// * `Result<()>` - Success or error
+        #[ink(message)]
+        pub fn add_recipient(&mut self, list: RecipientList, recipient: AccountId) -> Result<()> {
+            let user = self.env().caller();
+            let mut recipients = self.recipient_lists.get((user, list)).unwrap_or_default();
+            if recipients.contains(&recipient) {
+                return Ok(());
+            }
+
+            if recipients.len() >= MAX_RECIPIENT_LIST_LEN {
+                return Err(Error::RecipientListFull);
+            }
+
+            recipients.push(recipient);
+            self.recipient_lists.insert((user, list), &recipients);
+            self.env().emit_event(RecipientListUpdated {
+                user,
+                list,
+                recipient,
+                added: true,
+            });
+            Ok(())
+        }
+
+        /// Removes a recipient from the caller's allowlist or blocklist
+        ///
+        /// # Arguments
+        /// * `list` - The list to remove from
+        /// * `recipient` - The recipient's AccountId
+        ///
+        /// # Returns
+        /// * `Result<()>` - Success or error
+        #[ink(message)]
+        pub fn remove_recipient(&mut self, list: RecipientList, recipient: AccountId) -> Result<()> {
+            let user = self.env().caller();
+            let mut recipients = self.recipient_lists.get((user, list)).unwrap_or_default();
+            let Some(position) = recipients.iter().position(|account| *account == recipient) else {
+                return Ok(());
+            };
+
+            recipients.remove(position);
+            self.recipient_lists.insert((user, list), &recipients);
+            self.env().emit_event(RecipientListUpdated {
+                user,
+                list,
+                recipient,
+                added: false,
+            });
+            Ok(())
+        }
+
+        /// Restricts the caller's payments to allowlisted recipients, or lifts the restriction
+        ///
+        /// # Arguments
+        /// * `enabled` - Whether only allowlisted recipients may be paid
+        ///
+        /// # Returns
+        /// * `Result<()>` - Success or error
+        #[ink(message)]
+        pub fn set_allowlist_only(&mut self, enabled: bool) -> Result<()> {
+            let user = self.env().caller();
+            if enabled {
+                self.allowlist_only.insert(user, &());
+            } else {
+                self.allowlist_only.remove(user);
+            }
+
+            self.env().emit_event(AllowlistOnlyChanged { user, enabled });
+            Ok(())
+        }
+
+        /// Checks whether a user only pays allowlisted recipients
+        ///
+        /// # Arguments
+        /// * `user` - The user's AccountId
+        ///
+        /// # Returns
+        /// * `bool` - Whether allowlist-only mode is on
+        #[ink(message)]
+        pub fn is_allowlist_only(&self, user: AccountId) -> bool {
+            self.allowlist_only.contains(user)
+        }
+
+        /// Gets a page of a user's allowlist or blocklist
+        ///
+        /// # Arguments
+        /// * `user` - The user's AccountId
+        /// * `list` - The list to read
+        /// * `offset` - Number of entries to skip
+        /// * `limit` - Maximum number of entries to return
+        ///
+        /// # Returns
+        /// * `Vec<AccountId>` - Recipients in the order they were added
+        #[ink(message)]
+        pub fn get_recipients(
+            &self,
+            user: AccountId,
+            list: RecipientList,
+            offset: u32,
+            limit: u32,
+        ) -> Vec<AccountId> {
+            self.recipient_lists
+                .get((user, list))
+                .unwrap_or_default()
+                .into_iter()
+                .skip(offset as usize)
+                .take(limit as usize)
+                .collect()
+        }
+
+        /// Adds an account to or removes it from the global deny list (config manager role)
+        ///
+        /// No user can pay a denied account.
+        ///
+        /// # Arguments
+        /// * `account` - The account to deny or clear
+        /// * `denied` - Whether the account is denied
+        ///
+        /// # Returns
+        /// * `Result<()>` - Success or error
+        #[ink(message)]
+        pub fn set_denied(&mut self, account: AccountId, denied: bool) -> Result<()> {
+            self.ensure_role(Role::ConfigManager)?;
+            self.apply_admin_action(AdminAction::SetDenied(account, denied))
+        }
+
+        /// Checks whether an account is on the global deny list
+        ///
+        /// # Arguments
+        /// * `account` - The account to check
+        ///
+        /// # Returns
+        /// * `bool` - Whether the account is denied
+        #[ink(message)]
+        pub fn is_denied(&self, account: AccountId) -> bool {
+            self.denied_accounts.contains(account)
+        }
+
+        /// Gets a page of the global deny list
+        ///
+        /// # Arguments
+        /// * `offset` - Number of entries to skip
+        /// * `limit` - Maximum number of entries to return
+        ///
+        /// # Returns
+        /// * `Vec<AccountId>` - Denied accounts
+        #[ink(message)]
+        pub fn get_deny_list(&self, offset: u32, limit: u32) -> Vec<AccountId> {
+            let end = offset.saturating_add(limit).min(self.deny_list_len);
+            (offset..end)
+                .filter_map(|position| self.deny_list.get(position))
+                .collect()
+        }
+
+        /// Authorizes a delegate to record payments on the caller's behalf
+        ///
+        /// Replaces any existing authorization of the same delegate and resets
+        /// the amount it has spent.
+        ///
+        /// # Arguments
+        /// * `delegate` - The relayer or session key to authorize
+        /// * `currency` - Currency the delegate may record payments in
+        /// * `expires_at` - Timestamp after which the authorization lapses
+        /// * `max_per_payment` - Maximum amount of a single payment
+        /// * `total_cap` - Maximum total amount the delegate may record
+        ///
+        /// # Returns
+        /// * `Result<()>` - Success or error
+        #[ink(message)]
+        pub fn authorize_delegate(
+            &mut self,
+            delegate: AccountId,
+            currency: String,
+            expires_at: Timestamp,
+            max_per_payment: Balance,
+            total_cap: Balance,
+        ) -> Result<()> {
+            let user = self.env().caller();
+            self.ensure_not_frozen(user)?;
+
+            if currency.is_empty() || currency.len() > MAX_CURRENCY_LEN {
+                return Err(Error::InvalidCurrency);
+            }
+
+            if expires_at <= self.env().block_timestamp() {
+                return Err(Error::InvalidExpiry);
+            }
+
+            if max_per_payment == 0 || total_cap == 0 {
+                return Err(Error::InvalidAmount);
+            }
+
+            self.delegates.insert(
+                (user, delegate),
+                &DelegateAuthorization {
+                    currency: currency.clone(),
+                    expires_at,
+                    max_per_payment,
+                    total_cap,
+                    spent: 0,
+                },
+            );
+
+            self.env().emit_event(DelegateAuthorized {
+                user,
+                delegate,
+                currency,
+                expires_at,
+                max_per_payment,
+                total_cap,
+            });
+            Ok(())
+        }
+
+        /// Revokes a delegate's authorization with immediate effect
+        ///
+        /// # Arguments
+        /// * `delegate` - The delegate to revoke
+        ///
+        /// # Returns
+        /// * `Result<()>` - Success or error
+        #[ink(message)]
+        pub fn revoke_delegate(&mut self, delegate: AccountId) -> Result<()> {
+            let user = self.env().caller();
+            if !self.delegates.contains((user, delegate)) {
+                return Err(Error::DelegateNotAuthorized);
+            }
+
+            self.delegates.remove((user, delegate));
+            self.env().emit_event(DelegateRevoked { user, delegate });
+            Ok(())
+        }
+
+        /// Gets a delegate's authorization from a user
+        ///
+        /// # Arguments
+        /// * `user` - The user who authorized the delegate
+        /// * `delegate` - The delegate
+        ///
+        /// # Returns
+        /// * `Option<DelegateAuthorization>` - The authorization, if any
+        #[ink(message)]
+        pub fn get_delegate(&self, user: AccountId, delegate: AccountId) -> Option<DelegateAuthorization> {
+            self.delegates.get((user, delegate))
+        }
+
+        /// Nominates a guardian who can freeze the caller's voice payments
+        ///
+        /// The first guardian sets the unfreeze threshold to 1. Guardians
+        /// cannot be changed while the caller is frozen.
+        ///
+        /// # Arguments
+        /// * `guardian` - The account to nominate
+        ///
+        /// # Returns
+        /// * `Result<()>` - Success or error
+        #[ink(message)]
+        pub fn add_guardian(&mut self, guardian: AccountId) -> Result<()> {
+            let user = self.env().caller();
+            self.ensure_not_frozen(user)?;
+
+            let mut set = self.guardians.get(user).unwrap_or_default();
+            if guardian == user || set.guardians.contains(&guardian) {
+                return Err(Error::InvalidGuardian);
+            }
+
+            if set.guardians.len() >= MAX_GUARDIANS {
+                return Err(Error::TooManyGuardians);
+            }
+
+            set.guardians.push(guardian);
+            set.threshold = set.threshold.max(1);
+            self.guardians.insert(user, &set);
+
+            self.env().emit_event(GuardianAdded { user, guardian });
+            Ok(())
+        }
+
+        /// Removes one of the caller's guardians
+        ///
+        /// The threshold is lowered if it would exceed the remaining guardians.
+        ///
+        /// # Arguments
+        /// * `guardian` - The guardian to remove
+        ///
+        /// # Returns
+        /// * `Result<()>` - Success or error
+        #[ink(message)]
+        pub fn remove_guardian(&mut self, guardian: AccountId) -> Result<()> {
+            let user = self.env().caller();
+            self.ensure_not_frozen(user)?;
+
+            let mut set = self.guardians.get(user).unwrap_or_default();
+            let position = set
+                .guardians
+                .iter()
+                .position(|account| *account == guardian)
+                .ok_or(Error::InvalidGuardian)?;
+
+            set.guardians.remove(position);
+            set.threshold = set.threshold.min(set.guardians.len() as u32);
+            if set.guardians.is_empty() {
+                self.guardians.remove(user);
+            } else {
+                self.guardians.insert(user, &set);
+            }
+
+            self.env().emit_event(GuardianRemoved { user, guardian });
+            Ok(())
+        }
+
+        /// Sets how many of the caller's guardians must approve an unfreeze
+        ///
+        /// # Arguments
+        /// * `threshold` - Required approvals, between 1 and the number of guardians
+        ///
+        /// # Returns
+        /// * `Result<()>` - Success or error
+        #[ink(message)]
+        pub fn set_guardian_threshold(&mut self, threshold: u32) -> Result<()> {
+            let user = self.env().caller();
+            self.ensure_not_frozen(user)?;
+
+            let mut set = self.guardians.get(user).unwrap_or_default();
+            if threshold == 0 || threshold as usize > set.guardians.len() {
+                return Err(Error::InvalidThreshold);
+            }
+
+            set.threshold = threshold;
+            self.guardians.insert(user, &set);
+            Ok(())
+        }
+
+        /// Freezes a user's voice payments (any single guardian of the user)
+        ///
+        /// Only recording by the user is blocked; the rest of the contract is
+        /// unaffected.
+        ///
+        /// # Arguments
+        /// * `user` - The user to freeze
+        /// * `reason` - Why the user is frozen
+        ///
+        /// # Returns
+        /// * `Result<()>` - Success or error
+        #[ink(message)]
+        pub fn freeze_account(&mut self, user: AccountId, reason: String) -> Result<()> {
+            let guardian = self.env().caller();
+            self.ensure_guardian(user, guardian)?;
+            self.ensure_not_frozen(user)?;
+
+            if reason.len() > MAX_MEMO_LEN {
+                return Err(Error::InvalidReason);
+            }
+
+            self.frozen_accounts.insert(
+                user,
+                &FreezeState {
+                    frozen_by: guardian,
+                    reason: reason.clone(),
+                    frozen_at: self.env().block_timestamp(),
+                    unfreeze_approvals: Vec::new(),
+                },
+            );
+
+            self.env().emit_event(AccountFrozen { user, guardian, reason });
+            Ok(())
+        }
+
+        /// Approves unfreezing a user (guardians of the user only)
+        ///
+        /// The user is unfrozen once the number of approvals reaches their
+        /// guardian threshold.
+        ///
+        /// # Arguments
+        /// * `user` - The frozen user
+        ///
+        /// # Returns
+        /// * `Result<()>` - Success or error
+        #[ink(message)]
+        pub fn approve_unfreeze(&mut self, user: AccountId) -> Result<()> {
+            let guardian = self.env().caller();
+            self.ensure_guardian(user, guardian)?;
+
+            let mut state = self.frozen_accounts.get(user).ok_or(Error::AccountNotFrozen)?;
+            if state.unfreeze_approvals.contains(&guardian) {
+                return Err(Error::AlreadyApproved);
+            }
+            state.unfreeze_approvals.push(guardian);
+
+            let approvals = state.unfreeze_approvals.len() as u32;
+            let threshold = self.guardians.get(user).unwrap_or_default().threshold;
+            self.env().emit_event(UnfreezeApproved {
+                user,
+                guardian,
+                approvals,
+                threshold,
+            });
+
+            if approvals >= threshold {
+                self.frozen_accounts.remove(user);
+                self.env().emit_event(AccountUnfrozen { user });
+            } else {
+                self.frozen_accounts.insert(user, &state);
+            }
+
+            Ok(())
+        }
+
+        /// Gets a user's guardians and unfreeze threshold
+        ///
+        /// # Arguments
+        /// * `user` - The user's AccountId
+        ///
+        /// # Returns
+        /// * `GuardianSet` - The guardians, empty if none are nominated
+        #[ink(message)]
+        pub fn get_guardians(&self, user: AccountId) -> GuardianSet {
+            self.guardians.get(user).unwrap_or_default()
+        }
+
+        /// Gets why and by whom a user is frozen
+        ///
+        /// # Arguments
+        /// * `user` - The user's AccountId
+        ///
+        /// # Returns
+        /// * `Option<FreezeState>` - The freeze, if the user is frozen
+        #[ink(message)]
+        pub fn get_freeze_state(&self, user: AccountId) -> Option<FreezeState> {
+            self.frozen_accounts.get(user)
+        }
+
+        /// Retrieves payment history for a specific user
+        /// 
+        /// # Arguments
+        /// * `user` - The user's AccountId
+        /// 
+        /// # Returns
+        /// * `Vec<PaymentRecord>` - List of payment records
+        #[ink(message)]
+        pub fn get_payment_history(&self, user: AccountId) -> Vec<PaymentRecord> {
+            self.payment_history.get(&user).unwrap_or_default()
+        }
+
+        /// Retrieves the caller's own payment history
+        /// 
+        /// # Returns
+        /// * `Vec<PaymentRecord>` - List of payment records for the caller
+        #[ink(message)]
+        pub fn get_my_payment_history(&self) -> Vec<PaymentRecord> {
+            let caller = self.env().caller();
+            self.get_payment_history(caller)
+        }
+
+        /// Gets the total number of payments recorded
+        /// 
+        /// # Returns
+        /// * `u64` - Total number of payments
+        #[ink(message)]
+        pub fn get_total_payments(&self) -> u64 {
+            self.total_payments
+        }
+
+        /// Gets the contract owner
+        /// 
+        /// # Returns
+        /// * `AccountId` - The owner's account ID
+        #[ink(message)]
+        pub fn get_owner(&self) -> AccountId {
+            self.owner
+        }
+
+        /// Schedules nominating a new owner of the contract (owner only)
+        ///
+        /// Once the timelock delay has passed and the operation is executed,
+        /// the nominee becomes owner by calling `accept_ownership`.
+        /// 
+        /// # Arguments
+        /// * `new_owner` - The new owner's AccountId
+        /// 
+        /// # Returns
+        /// * `Result<OperationId>` - The ID of the scheduled operation
+        #[ink(message)]
+        pub fn transfer_ownership(&mut self, new_owner: AccountId) -> Result<OperationId> {
+            self.ensure_owner()?;
+            self.schedule_operation(AdminAction::TransferOwnership(new_owner))
+        }
+
+        /// Completes an ownership transfer (nominated owner only)
+        ///
+        /// Any council is dissolved, every granted role revoked and every
+        /// scheduled operation cancelled, as the new owner administers the
+        /// contract alone.
+        ///
+        /// # Returns
+        /// * `Result<()>` - Success or error
+        #[ink(message)]
+        pub fn accept_ownership(&mut self) -> Result<()> {
+            let caller = self.env().caller();
+            if self.pending_owner != Some(caller) {
+                return Err(Error::Unauthorized);
+            }
+
+            let previous_owner = self.owner;
+            self.owner = caller;
+            self.pending_owner = None;
+            self.council = None;
+            self.revoke_all_roles();
+            self.cancel_pending_operations();
+
+            self.env().emit_event(OwnershipTransferred {
+                previous_owner,
+                new_owner: caller,
+            });
+            Ok(())
+        }
+
+        /// Gets the account nominated to take over ownership
+        ///
+        /// # Returns
+        /// * `Option<AccountId>` - The nominee, if a transfer is in progress
+        #[ink(message)]
+        pub fn get_pending_owner(&self) -> Option<AccountId> {
+            self.pending_owner
+        }
+
+        /// Executes a scheduled operation once its delay has passed
+        ///
+        /// Anyone may execute a ready operation; it was authorized when it was
+        /// scheduled.
+        ///
+        /// # Arguments
+        /// * `operation_id` - The operation to execute
+        ///
+        /// # Returns
+        /// * `Result<()>` - Success or error
+        #[ink(message)]
+        pub fn execute_operation(&mut self, operation_id: OperationId) -> Result<()> {
+            let mut operation = self.pending_operation(operation_id)?;
+            if self.env().block_timestamp() < operation.ready_at {
+                return Err(Error::OperationNotReady);
+            }
+
+            operation.status = OperationStatus::Executed;
+            self.operations.insert(operation_id, &operation);
+            self.remove_pending_operation(operation_id);
+            self.apply_admin_action(operation.action)?;
+
+            self.env().emit_event(OperationExecuted { operation_id });
+            Ok(())
+        }
+
+        /// Cancels a scheduled operation (admin role)
+        ///
+        /// # Arguments
+        /// * `operation_id` - The operation to cancel
+        ///
+        /// # Returns
+        /// * `Result<()>` - Success or error
+        #[ink(message)]
+        pub fn cancel_operation(&mut self, operation_id: OperationId) -> Result<()> {
+            self.ensure_role(Role::Admin)?;
+            self.apply_admin_action(AdminAction::CancelOperation(operation_id))
+        }
+
+        /// Gets a scheduled operation by its identifier
+        ///
+        /// # Arguments
+        /// * `operation_id` - The operation's ID
+        ///
+        /// # Returns
+        /// * `Option<ScheduledOperation>` - The operation, if it exists
+        #[ink(message)]
+        pub fn get_operation(&self, operation_id: OperationId) -> Option<ScheduledOperation> {
+            self.operations.get(operation_id)
+        }
+
+        /// Gets operations that are waiting in the timelock
+        ///
+        /// Executing or cancelling an operation moves the last pending one
+        /// into its position, so the order is not that of scheduling.
+        ///
+        /// # Arguments
+        /// * `offset` - Number of pending operations to skip
+        /// * `limit` - Maximum number of operations to return
+        ///
+        /// # Returns
+        /// * `Vec<ScheduledOperation>` - Pending operations
+        #[ink(message)]
+        pub fn get_pending_operations(&self, offset: u32, limit: u32) -> Vec<ScheduledOperation> {
+            let end = offset.saturating_add(limit).min(self.pending_operations_len);
+            (offset..end)
+                .filter_map(|position| self.pending_operations.get(position))
+                .filter_map(|operation_id| self.operations.get(operation_id))
+                .collect()
+        }
+
+        /// Pauses or resumes payment recording (pauser role)
+        ///
+        /// # Arguments
+        /// * `paused` - Whether recording should be paused
+        ///
+        /// # Returns
+        /// * `Result<()>` - Success or error
+        #[ink(message)]
+        pub fn set_paused(&mut self, paused: bool) -> Result<()> {
+            self.ensure_role(Role::Pauser)?;
+            self.apply_admin_action(AdminAction::SetPaused(paused))
+        }
+
+        /// Checks whether payment recording is paused
+        ///
+        /// # Returns
+        /// * `bool` - Whether recording is paused
+        #[ink(message)]
+        pub fn is_paused(&self) -> bool {
+            self.paused
+        }
+
+        /// Schedules an upgrade to already uploaded code (owner only)
+        ///
+        /// # Arguments
+        /// * `code_hash` - Hash of the new contract code
+        ///
+        /// # Returns
+        /// * `Result<OperationId>` - The ID of the scheduled operation
+        #[ink(message)]
+        pub fn set_code_hash(&mut self, code_hash: Hash) -> Result<OperationId> {
+            self.ensure_owner()?;
+            self.schedule_operation(AdminAction::SetCodeHash(code_hash))
+        }
+
+        /// Schedules handing administration to a council of members (owner only)
+        ///
+        /// Once the timelock delay has passed and the operation is executed,
+        /// the contract becomes its own owner, so every admin action must then
+        /// go through `propose_admin_action` and reach the council threshold.
+        /// Every granted role is revoked and every other scheduled operation
+        /// cancelled; the council grants roles again through proposals.
+        ///
+        /// # Arguments
+        /// * `council` - The members and approval threshold
+        ///
+        /// # Returns
+        /// * `Result<OperationId>` - The ID of the scheduled operation
+        #[ink(message)]
+        pub fn establish_council(&mut self, council: Council) -> Result<OperationId> {
+            self.ensure_owner()?;
+            Self::validate_council(&council)?;
+            self.schedule_operation(AdminAction::UpdateCouncil(council))
+        }
+
+        /// Gets the council, if one administers the contract
+        ///
+        /// # Returns
+        /// * `Option<Council>` - The council's members and threshold
+        #[ink(message)]
+        pub fn get_council(&self) -> Option<Council> {
+            self.council.clone()
+        }
+
+        /// Proposes an admin action for council approval (council members only)
+        ///
+        /// The proposer's approval is counted immediately.
+        ///
+        /// # Arguments
+        /// * `action` - The admin action to perform once approved
+        ///
+        /// # Returns
+        /// * `Result<ProposalId>` - The new proposal's ID
+        #[ink(message)]
+        pub fn propose_admin_action(&mut self, action: AdminAction) -> Result<ProposalId> {
+            let proposer = self.env().caller();
+            self.ensure_council_member(proposer)?;
+
+            let proposal_id = self.next_proposal_id;
+            self.next_proposal_id += 1;
+            let expires_at = self
+                .env()
+                .block_timestamp()
+                .saturating_add(self.config.proposal_lifetime);
+
+            self.env().emit_event(ProposalCreated {
+                proposal_id,
+                proposer,
+                action: action.clone(),
+                expires_at,
+            });
+            self.proposals.insert(
+                proposal_id,
+                &Proposal {
+                    id: proposal_id,
+                    proposer,
+                    action,
+                    approvals: ink::prelude::vec![proposer],
+                    expires_at,
+                    executed: false,
+                },
+            );
+            self.env().emit_event(ProposalApproved {
+                proposal_id,
+                member: proposer,
+                approvals: 1,
+            });
+
+            Ok(proposal_id)
+        }
+
+        /// Approves a pending proposal (council members only)
+        ///
+        /// # Arguments
+        /// * `proposal_id` - The proposal to approve
+        ///
+        /// # Returns
+        /// * `Result<()>` - Success or error
+        #[ink(message)]
+        pub fn approve_proposal(&mut self, proposal_id: ProposalId) -> Result<()> {
+            let member = self.env().caller();
+            self.ensure_council_member(member)?;
+
+            let mut proposal = self.pending_proposal(proposal_id)?;
+            if proposal.approvals.contains(&member) {
+                return Err(Error::AlreadyApproved);
+            }
+            proposal.approvals.push(member);
+            self.proposals.insert(proposal_id, &proposal);
+
+            self.env().emit_event(ProposalApproved {
+                proposal_id,
+                member,
+                approvals: proposal.approvals.len() as u32,
+            });
+            Ok(())
+        }
+
+        /// Performs a proposal's action once it has enough approvals (council members only)
+        ///
+        /// Only approvals from current council members count towards the
+        /// threshold. Timelocked actions are scheduled rather than performed.
+        ///
+        /// # Arguments
+        /// * `proposal_id` - The proposal to execute
+        ///
+        /// # Returns
+        /// * `Result<()>` - Success or error
+        #[ink(message)]
+        pub fn execute_proposal(&mut self, proposal_id: ProposalId) -> Result<()> {
+            let member = self.env().caller();
+            self.ensure_council_member(member)?;
+
+            let mut proposal = self.pending_proposal(proposal_id)?;
+            let council = self.council.clone().ok_or(Error::Unauthorized)?;
+            let approvals = proposal
+                .approvals
+                .iter()
+                .filter(|account| council.members.contains(account))
+                .count() as u32;
+            if approvals < council.threshold {
+                return Err(Error::ThresholdNotMet);
+            }
+
+            proposal.executed = true;
+            self.proposals.insert(proposal_id, &proposal);
+            if proposal.action.is_timelocked() {
+                self.schedule_operation(proposal.action)?;
+            } else {
+                self.apply_admin_action(proposal.action)?;
+            }
+
+            self.env().emit_event(ProposalExecuted { proposal_id });
+            Ok(())
+        }
+
+        /// Gets a council proposal by its identifier
+        ///
+        /// # Arguments
+        /// * `proposal_id` - The proposal's ID
+        ///
+        /// # Returns
+        /// * `Option<Proposal>` - The proposal, if it exists
+        #[ink(message)]
+        pub fn get_proposal(&self, proposal_id: ProposalId) -> Option<Proposal> {
+            self.proposals.get(proposal_id)
+        }
+
+        /// Grants a role to an account (holders of the role's admin role only)
+        ///
+        /// # Arguments
+        /// * `role` - The role to grant
+        /// * `account` - The account receiving the role
+        ///
+        /// # Returns
+        /// * `Result<()>` - Success or error
+        #[ink(message)]
+        pub fn grant_role(&mut self, role: Role, account: AccountId) -> Result<()> {
+            self.ensure_role(self.get_role_admin(role))?;
+            self.apply_admin_action(AdminAction::GrantRole(role, account))
+        }
+
+        /// Revokes a role from an account (holders of the role's admin role only)
+        ///
+        /// # Arguments
+        /// * `role` - The role to revoke
+        /// * `account` - The account losing the role
+        ///
+        /// # Returns
+        /// * `Result<()>` - Success or error
+        #[ink(message)]
+        pub fn revoke_role(&mut self, role: Role, account: AccountId) -> Result<()> {
+            self.ensure_role(self.get_role_admin(role))?;
+            self.apply_admin_action(AdminAction::RevokeRole(role, account))
+        }
+
+        /// Gives up a role held by the caller
+        ///
+        /// # Arguments
+        /// * `role` - The role to give up
+        ///
+        /// # Returns
+        /// * `Result<()>` - Success or error
+        #[ink(message)]
+        pub fn renounce_role(&mut self, role: Role) -> Result<()> {
+            let caller = self.env().caller();
+            self.apply_admin_action(AdminAction::RevokeRole(role, caller))
+        }
+
+        /// Changes which role may grant and revoke a role (owner only)
+        ///
+        /// # Arguments
+        /// * `role` - The role being managed
+        /// * `admin_role` - The role that will manage it
+        ///
+        /// # Returns
+        /// * `Result<()>` - Success or error
+        #[ink(message)]
+        pub fn set_role_admin(&mut self, role: Role, admin_role: Role) -> Result<()> {
+            self.ensure_owner()?;
+            self.apply_admin_action(AdminAction::SetRoleAdmin(role, admin_role))
+        }
+
+        /// Checks whether an account holds a role
+        ///
+        /// # Arguments
+        /// * `role` - The role to check
+        /// * `account` - The account to check
+        ///
+        /// # Returns
+        /// * `bool` - Whether the account holds the role, or is the owner
+        #[ink(message)]
+        pub fn has_role(&self, role: Role, account: AccountId) -> bool {
+            account == self.owner || self.roles.contains((role, account))
+        }
+
+        /// Gets the role allowed to grant and revoke a role
+        ///
+        /// # Arguments
+        /// * `role` - The role being managed
+        ///
+        /// # Returns
+        /// * `Role` - The managing role, `Admin` unless changed
+        #[ink(message)]
+        pub fn get_role_admin(&self, role: Role) -> Role {
+            self.role_admins.get(role).unwrap_or(Role::Admin)
+        }
+
+        /// Gets the administrative settings
+        ///
+        /// # Returns
+        /// * `ContractConfig` - The current configuration
+        #[ink(message)]
+        pub fn get_config(&self) -> ContractConfig {
+            self.config.clone()
+        }
+
+        /// Schedules new administrative settings (config manager role)
+        ///
+        /// The settings take effect when the operation is executed after the
+        /// timelock delay.
+        ///
+        /// # Arguments
+        /// * `config` - The new configuration
+        ///
+        /// # Returns
+        /// * `Result<OperationId>` - The ID of the scheduled operation
+        #[ink(message)]
+        pub fn update_config(&mut self, config: ContractConfig) -> Result<OperationId> {
+            self.ensure_role(Role::ConfigManager)?;
+            Self::validate_config(&config)?;
+            self.schedule_operation(AdminAction::UpdateConfig(config))
+        }
+
+        /// Registers a PSP22 token contract for token payments (config manager role)
+        ///
+        /// # Arguments
+        /// * `token` - The token contract's AccountId
+        /// * `symbol` - Currency symbol recorded with its payments
+        /// * `decimals` - Number of decimals of the token's smallest unit
+        ///
+        /// # Returns
+        /// * `Result<()>` - Success or error
+        #[ink(message)]
+        pub fn register_token(&mut self, token: AccountId, symbol: String, decimals: u8) -> Result<()> {
+            self.ensure_role(Role::ConfigManager)?;
+            self.apply_admin_action(AdminAction::RegisterToken(token, TokenInfo { symbol, decimals }))
+        }
+
+        /// Removes a PSP22 token from the registry (config manager role)
+        ///
+        /// # Arguments
+        /// * `token` - The token contract's AccountId
+        ///
+        /// # Returns
+        /// * `Result<()>` - Success or error
+        #[ink(message)]
+        pub fn unregister_token(&mut self, token: AccountId) -> Result<()> {
+            self.ensure_role(Role::ConfigManager)?;
+            self.apply_admin_action(AdminAction::UnregisterToken(token))
+        }
+
+        /// Gets the registration of a PSP22 token
+        ///
+        /// # Arguments
+        /// * `token` - The token contract's AccountId
+        ///
+        /// # Returns
+        /// * `Option<TokenInfo>` - The token's symbol and decimals, if registered
+        #[ink(message)]
+        pub fn get_token(&self, token: AccountId) -> Option<TokenInfo> {
+            self.registered_tokens.get(token)
+        }
+
+        /// Registers a `pallet-assets` asset for asset payments (config manager role)
+        ///
+        /// # Arguments
+        /// * `asset_id` - The asset's ID in `pallet-assets`
+        /// * `symbol` - Currency symbol recorded with its payments
+        /// * `decimals` - Number of decimals of the asset's smallest unit
+        ///
+        /// # Returns
+        /// * `Result<()>` - Success or error
+        #[ink(message)]
+        pub fn register_asset(&mut self, asset_id: AssetId, symbol: String, decimals: u8) -> Result<()> {
+            self.ensure_role(Role::ConfigManager)?;
+            self.apply_admin_action(AdminAction::RegisterAsset(asset_id, TokenInfo { symbol, decimals }))
+        }
+
+        /// Removes a `pallet-assets` asset from the registry (config manager role)
+        ///
+        /// # Arguments
+        /// * `asset_id` - The asset's ID in `pallet-assets`
+        ///
+        /// # Returns
+        /// * `Result<()>` - Success or error
+        #[ink(message)]
+        pub fn unregister_asset(&mut self, asset_id: AssetId) -> Result<()> {
+            self.ensure_role(Role::ConfigManager)?;
+            self.apply_admin_action(AdminAction::UnregisterAsset(asset_id))
+        }
+
+        /// Gets the registration of a `pallet-assets` asset
+        ///
+        /// # Arguments
+        /// * `asset_id` - The asset's ID in `pallet-assets`
+        ///
+        /// # Returns
+        /// * `Option<TokenInfo>` - The asset's symbol and decimals, if registered
+        #[ink(message)]
+        pub fn get_asset(&self, asset_id: AssetId) -> Option<TokenInfo> {
+            self.registered_assets.get(asset_id)
+        }
+
+        /// Gets payment statistics for a user
+        /// 
+        /// # Arguments
+        /// * `user` - The user's AccountId
+        /// 
+        /// # Returns
+        /// * `(u32, Balance)` - (number of payments, total amount sent), not
+        ///   counting payments that are awaiting confirmation or expired
+        #[ink(message)]
+        pub fn get_user_stats(&self, user: AccountId) -> (u32, Balance) {
+            let history = self.payment_history.get(&user).unwrap_or_default();
+            let confirmed: Vec<&PaymentRecord> = history
+                .iter()
+                .filter(|record| {
+                    !matches!(record.status, PaymentStatus::PendingConfirmation | PaymentStatus::Expired)
+                })
+                .collect();
+            let count = confirmed.len() as u32;
+            let total_amount = confirmed.iter().map(|record| record.amount).sum();
+            (count, total_amount)
+        }
+
+        /// Gets recent payments (last N payments for a user)
+        /// 
+        /// # Arguments
+        /// * `user` - The user's AccountId  
+        /// * `limit` - Maximum number of recent payments to return
+        /// 
+        /// # Returns
+        /// * `Vec<PaymentRecord>` - Recent payment records
+        #[ink(message)]
+        pub fn get_recent_payments(&self, user: AccountId, limit: u32) -> Vec<PaymentRecord> {
+            let history = self.payment_history.get(&user).unwrap_or_default();
+            let start_index = if history.len() > limit as usize {
+                history.len() - limit as usize
+            } else {
+                0
+            };
+            
+            history[start_index..].to_vec()
+        }
+
+        /// Lists payments of all users by ID (auditor role)
+        ///
+        /// # Arguments
+        /// * `offset` - ID of the first payment to return
+        /// * `limit` - Maximum number of payments to return
+        ///
+        /// # Returns
+        /// * `Result<Vec<PaymentRecord>>` - Payment records in ID order
+        #[ink(message)]
+        pub fn get_all_payments(&self, offset: PaymentId, limit: u32) -> Result<Vec<PaymentRecord>> {
+            self.ensure_role(Role::Auditor)?;
+
+            let end = offset.saturating_add(limit as u64).min(self.total_payments);
+            Ok((offset..end).filter_map(|payment_id| self.get_payment(payment_id)).collect())
+        }
+
+        /// Validates a single payment before it is recorded
+        fn validate_input(input: &PaymentInput) -> Result<()> {
+            if input.amount == 0 {
+                return Err(Error::InvalidAmount);
+            }
+            if let Some(locale) = &input.locale {
+                Self::validate_locale(locale)?;
+            }
+            Self::validate_command(&input.voice_command, &input.currency, input.confidence)
+        }
+
+        /// Builds the record for a validated payment, stamped with the current block time
+        fn new_record(&self, input: PaymentInput) -> PaymentRecord {
+            PaymentRecord {
+                id: self.total_payments,
+                recipient: input.recipient,
+                amount: input.amount,
+                voice_command: input.voice_command,
+                currency: input.currency,
+                network: input.network,
+                timestamp: self.env().block_timestamp(),
+                confidence: input.confidence,
+                asset: PaymentAsset::Native,
+                split_id: None,
+                invoice_id: None,
+                status: PaymentStatus::Completed,
+                refunded_amount: 0,
+                submitted_by: None,
+                locale: input.locale,
+            }
+        }
+
+        /// Moves `amount` of a PSP22 token from `from` to `to` using the recorder's allowance
+        fn psp22_transfer_from(
+            &self,
+            token: AccountId,
+            from: AccountId,
+            to: AccountId,
+            amount: Balance,
+        ) -> Result<()> {
+            let result = build_call::<Environment>()
+                .call(token)
+                .exec_input(
+                    ExecutionInput::new(Selector::new(ink::selector_bytes!("PSP22::transfer_from")))
+                        .push_arg(from)
+                        .push_arg(to)
+                        .push_arg(amount)
+                        .push_arg(Vec::<u8>::new()),
+                )
+                .returns::<core::result::Result<(), Psp22Error>>()
+                .try_invoke();
+
+            match result {
+                Ok(Ok(Ok(()))) => Ok(()),
+                _ => Err(Error::TokenTransferFailed),
+            }
+        }
+
+        /// Moves `amount` of a `pallet-assets` asset from `from` to `to` and checks `to` was credited
+        #[cfg(feature = "assets-extension")]
+        fn assets_transfer(
+            &mut self,
+            asset_id: AssetId,
+            from: AccountId,
+            to: AccountId,
+            amount: Balance,
+        ) -> Result<()> {
+            if self.env().extension().balance_of(asset_id, from) < amount {
+                return Err(Error::InsufficientAssetBalance);
+            }
+
+            let before = self.env().extension().balance_of(asset_id, to);
+            self.env()
+                .extension()
+                .transfer_approved(asset_id, from, to, amount)
+                .map_err(|_| Error::AssetTransferFailed)?;
+
+            let after = self.env().extension().balance_of(asset_id, to);
+            if after.checked_sub(before) != Some(amount) {
+                return Err(Error::AssetTransferFailed);
+            }
+
+            Ok(())
+        }
+
+        /// Without the chain extension there is no way to move `pallet-assets` balances
+        #[cfg(not(feature = "assets-extension"))]
+        fn assets_transfer(
+            &mut self,
+            _asset_id: AssetId,
+            _from: AccountId,
+            _to: AccountId,
+            _amount: Balance,
+        ) -> Result<()> {
+            Err(Error::AssetsUnsupported)
+        }
+
+        /// Checks per-sender restrictions on a payment and tracks it against spending limits
+        ///
+        /// Every recording path calls this after validating its input and
+        /// before storing a record or moving funds. Payments that will await
+        /// confirmation are only charged to the limits by `confirm_pending`.
+        fn authorize_payment(&mut self, sender: AccountId, input: &PaymentInput) -> Result<()> {
+            self.ensure_can_pay(sender, input.recipient)?;
+            if self.needs_confirmation(sender, input.amount, input.confidence) {
+                return Ok(());
+            }
+            self.charge_spending_limits(sender, &input.currency, input.amount)
+        }
+
+        /// Fails if the contract is paused, the sender frozen or the recipient denied
+        fn ensure_can_pay(&self, sender: AccountId, recipient: AccountId) -> Result<()> {
+            if self.paused {
+                return Err(Error::ContractPaused);
+            }
+            self.ensure_not_frozen(sender)?;
+            self.ensure_recipient_allowed(sender, recipient)
+        }
+
+        /// Adds a confirmed payment to what a delegate has spent for `user`,
+        /// failing if it exceeds the total cap; revoked delegates are not charged
+        fn charge_delegate(&mut self, user: AccountId, delegate: AccountId, amount: Balance) -> Result<()> {
+            let Some(mut authorization) = self.delegates.get((user, delegate)) else {
+                return Ok(());
+            };
+            authorization.spent = authorization
+                .spent
+                .checked_add(amount)
+                .filter(|spent| *spent <= authorization.total_cap)
+                .ok_or(Error::DelegateLimitExceeded)?;
+            self.delegates.insert((user, delegate), &authorization);
+            Ok(())
+        }
+
+        /// Adds a payment to the sender's spending windows, failing if it exceeds a limit
+        fn charge_spending_limits(&mut self, sender: AccountId, currency: &str, amount: Balance) -> Result<()> {
+            let now = self.env().block_timestamp();
+            let mut tracked = Vec::new();
+            for period in [LimitPeriod::Day, LimitPeriod::Week] {
+                let key = (sender, String::from(currency), period);
+                let Some(limit) = self.spending_limits.get(&key) else {
+                    continue;
+                };
+
+                let limit = Self::effective_limit(limit, now);
+                let spent = self.spent_in_window(&key);
+                let remaining = limit.amount.saturating_sub(spent);
+                if amount > remaining {
+                    return Err(Error::SpendingLimitExceeded { remaining });
+                }
+                tracked.push((key, spent + amount));
+            }
+
+            for (key, spent) in tracked {
+                let window_start = now - now % key.2.duration();
+                self.spending_windows.insert(&key, &(window_start, spent));
+            }
+
+            Ok(())
+        }
+
+        /// Whether a payment falls below the sender's confidence policy
+        fn needs_confirmation(&self, sender: AccountId, amount: Balance, confidence: u8) -> bool {
+            self.confidence_policies
+                .get(sender)
+                .is_some_and(|policy| confidence < policy.required_confidence(amount))
+        }
+
+        /// Fails with `ConfidenceTooLow` for payments that cannot wait for confirmation
+        fn ensure_confident(&self, sender: AccountId, amount: Balance, confidence: u8) -> Result<()> {
+            if self.needs_confirmation(sender, amount, confidence) {
+                return Err(Error::ConfidenceTooLow);
+            }
+            Ok(())
+        }
+
+        /// Fails if the recipient is denied globally or by the sender's lists
+        fn ensure_recipient_allowed(&self, sender: AccountId, recipient: AccountId) -> Result<()> {
+            let on_list = |list| {
+                self.recipient_lists
+                    .get((sender, list))
+                    .is_some_and(|recipients| recipients.contains(&recipient))
+            };
+
+            if self.denied_accounts.contains(recipient) || on_list(RecipientList::Blocklist) {
+                return Err(Error::RecipientBlocked);
+            }
+
+            if self.allowlist_only.contains(sender) && !on_list(RecipientList::Allowlist) {
+                return Err(Error::RecipientNotAllowed);
+            }
+
+            Ok(())
+        }
+
+        /// Checks that the intent's signer signed it
+        ///
+        /// sr25519 signatures are accepted over the raw payload and over the
+        /// `<Bytes>`-wrapped payload produced by browser wallet extensions.
+        fn verify_intent_signature(&self, intent: &PaymentIntent, signature: &IntentSignature) -> Result<()> {
+            let payload = scale::Encode::encode(&(INTENT_DOMAIN, self.env().account_id(), intent));
+            let signer: &[u8; 32] = intent.signer.as_ref();
+
+            let verified = match signature {
+                IntentSignature::Sr25519(signature) => {
+                    let mut wrapped = Vec::with_capacity(payload.len() + 15);
+                    wrapped.extend_from_slice(b"<Bytes>");
+                    wrapped.extend_from_slice(&payload);
+                    wrapped.extend_from_slice(b"</Bytes>");
+
+                    [payload.as_slice(), wrapped.as_slice()]
+                        .iter()
+                        .any(|message| self.env().sr25519_verify(signature, message, signer).is_ok())
+                }
+                IntentSignature::Ecdsa(signature) => {
+                    let message_hash = self.env().hash_bytes::<Blake2x256>(&payload);
+                    self.env()
+                        .ecdsa_recover(signature, &message_hash)
+                        .map(|public_key| self.env().hash_bytes::<Blake2x256>(&public_key) == *signer)
+                        .unwrap_or(false)
+                }
+            };
+
+            if !verified {
+                return Err(Error::InvalidSignature);
+            }
+            Ok(())
+        }
+
+        /// Fails with `Unauthorized` unless the caller is the owner
+        ///
+        /// Once a council is established the contract owns itself, so this
+        /// rejects every caller and admin actions must go through proposals.
+        fn ensure_owner(&self) -> Result<()> {
+            if self.env().caller() != self.owner {
+                return Err(Error::Unauthorized);
+            }
+            Ok(())
+        }
+
+        /// Revokes every granted role when administration changes hands
+        fn revoke_all_roles(&mut self) {
+            for position in 0..self.role_holders_len {
+                if let Some((role, account)) = self.role_holders.take(position) {
+                    self.roles.remove((role, account));
+                    self.env().emit_event(RoleRevoked {
+                        role,
+                        account,
+                        sender: self.env().caller(),
+                    });
+                }
+            }
+            self.role_holders_len = 0;
+        }
+
+        /// Cancels every scheduled operation when administration changes hands
+        fn cancel_pending_operations(&mut self) {
+            for position in 0..self.pending_operations_len {
+                let Some(operation_id) = self.pending_operations.take(position) else {
+                    continue;
+                };
+                self.pending_positions.remove(operation_id);
+                if let Some(mut operation) = self.operations.get(operation_id) {
+                    operation.status = OperationStatus::Cancelled;
+                    self.operations.insert(operation_id, &operation);
+                    self.env().emit_event(OperationCancelled { operation_id });
+                }
+            }
+            self.pending_operations_len = 0;
+        }
+
+        /// Fails with `Unauthorized` unless the caller holds `role`
+        fn ensure_role(&self, role: Role) -> Result<()> {
+            if !self.has_role(role, self.env().caller()) {
+                return Err(Error::Unauthorized);
+            }
+            Ok(())
+        }
+
+        /// Fails with `Unauthorized` unless `account` sits on the council
+        fn ensure_council_member(&self, account: AccountId) -> Result<()> {
+            match &self.council {
+                Some(council) if council.members.contains(&account) => Ok(()),
+                _ => Err(Error::Unauthorized),
+            }
+        }
+
+        /// Loads a proposal that can still be approved or executed
+        fn pending_proposal(&self, proposal_id: ProposalId) -> Result<Proposal> {
+            let proposal = self.proposals.get(proposal_id).ok_or(Error::ProposalNotFound)?;
+            if proposal.executed {
+                return Err(Error::ProposalAlreadyExecuted);
+            }
+
+            if self.env().block_timestamp() > proposal.expires_at {
+                return Err(Error::ProposalExpired);
+            }
+
+            Ok(proposal)
+        }
+
+        /// Loads an operation that is still waiting in the timelock
+        fn pending_operation(&self, operation_id: OperationId) -> Result<ScheduledOperation> {
+            let operation = self.operations.get(operation_id).ok_or(Error::OperationNotFound)?;
+            if operation.status != OperationStatus::Pending {
+                return Err(Error::OperationNotPending);
+            }
+            Ok(operation)
+        }
+
+        /// Queues an admin action to be executed after the timelock delay
+        fn schedule_operation(&mut self, action: AdminAction) -> Result<OperationId> {
+            let operation_id = self.next_operation_id;
+            self.next_operation_id += 1;
+            let scheduled_by = self.env().caller();
+            let ready_at = self
+                .env()
+                .block_timestamp()
+                .saturating_add(self.config.timelock_delay);
+
+            self.env().emit_event(OperationScheduled {
+                operation_id,
+                scheduled_by,
+                action: action.clone(),
+                ready_at,
+            });
+            self.operations.insert(
+                operation_id,
+                &ScheduledOperation {
+                    id: operation_id,
+                    action,
+                    scheduled_by,
+                    ready_at,
+                    status: OperationStatus::Pending,
+                },
+            );
+            let position = self.pending_operations_len;
+            self.pending_operations.insert(position, &operation_id);
+            self.pending_positions.insert(operation_id, &position);
+            self.pending_operations_len += 1;
+
+            Ok(operation_id)
+        }
+
+        /// Drops an operation from the pending list once it is executed or cancelled
+        fn remove_pending_operation(&mut self, operation_id: OperationId) {
+            if let Some(position) = self.pending_positions.take(operation_id) {
+                // Move the last entry into the freed position
+                self.pending_operations_len -= 1;
+                let last = self.pending_operations.take(self.pending_operations_len);
+                if let Some(last) = last.filter(|_| position != self.pending_operations_len) {
+                    self.pending_operations.insert(position, &last);
+                    self.pending_positions.insert(last, &position);
+                }
+            }
+        }
+
+        /// Checks that every duration and limit in the settings is non-zero
+        fn validate_config(config: &ContractConfig) -> Result<()> {
+            if config.max_batch_size == 0
+                || config.dispute_window == 0
+                || config.dispute_resolution_period == 0
+                || config.proposal_lifetime == 0
+                || config.timelock_delay == 0
+                || config.confirmation_window == 0
+            {
+                return Err(Error::InvalidConfig);
+            }
+            Ok(())
+        }
+
+        /// Checks that a council has distinct members and an attainable threshold
+        fn validate_council(council: &Council) -> Result<()> {
+            let unique = council
+                .members
+                .iter()
+                .enumerate()
+                .all(|(index, member)| !council.members[..index].contains(member));
+            if council.members.is_empty()
+                || council.members.len() > MAX_COUNCIL_MEMBERS
+                || !unique
+                || council.threshold == 0
+                || council.threshold as usize > council.members.len()
+            {
+                return Err(Error::InvalidCouncil);
+            }
+            Ok(())
+        }
+
+        /// Performs an admin action; callers are responsible for authorization
+        fn apply_admin_action(&mut self, action: AdminAction) -> Result<()> {
+            match action {
+                AdminAction::SetPaused(paused) => {
+                    self.paused = paused;
+                    self.env().emit_event(PausedChanged { paused });
+                }
+                AdminAction::UpdateConfig(config) => {
+                    Self::validate_config(&config)?;
+                    self.config = config;
+                }
+                AdminAction::SetCodeHash(code_hash) => {
+                    self.env()
+                        .set_code_hash(&code_hash)
+                        .map_err(|_| Error::UpgradeFailed)?;
+                }
+                AdminAction::TransferOwnership(new_owner) => {
+                    self.pending_owner = Some(new_owner);
+                    self.env().emit_event(OwnershipTransferStarted {
+                        previous_owner: self.owner,
+                        pending_owner: new_owner,
+                    });
+                }
+                AdminAction::SetArbiter(account, enabled) => {
+                    if enabled {
+                        self.arbiters.insert(account, &());
+                    } else {
+                        self.arbiters.remove(account);
+                    }
+                    self.env().emit_event(ArbiterUpdated { account, enabled });
+                }
+                AdminAction::RegisterToken(token, info) => {
+                    if info.symbol.is_empty() || info.symbol.len() > MAX_CURRENCY_LEN {
+                        return Err(Error::InvalidCurrency);
+                    }
+                    self.registered_tokens.insert(token, &info);
+                }
+                AdminAction::UnregisterToken(token) => {
+                    if !self.registered_tokens.contains(token) {
+                        return Err(Error::TokenNotRegistered);
+                    }
+                    self.registered_tokens.remove(token);
+                }
+                AdminAction::RegisterAsset(asset_id, info) => {
+                    if info.symbol.is_empty() || info.symbol.len() > MAX_CURRENCY_LEN {
+                        return Err(Error::InvalidCurrency);
+                    }
+                    self.registered_assets.insert(asset_id, &info);
+                }
+                AdminAction::UnregisterAsset(asset_id) => {
+                    if !self.registered_assets.contains(asset_id) {
+                        return Err(Error::AssetNotRegistered);
+                    }
+                    self.registered_assets.remove(asset_id);
+                }
+                AdminAction::UpdateCouncil(council) => {
+                    Self::validate_council(&council)?;
+                    self.owner = self.env().account_id();
+                    if self.council.is_none() {
+                        self.revoke_all_roles();
+                        self.cancel_pending_operations();
+                    }
+                    self.env().emit_event(CouncilUpdated {
+                        members: council.members.clone(),
+                        threshold: council.threshold,
+                    });
+                    self.council = Some(council);
+                }
+                AdminAction::GrantRole(role, account) => {
+                    if !self.roles.contains((role, account)) {
+                        let position = self.role_holders_len;
+                        self.role_holders.insert(position, &(role, account));
+                        self.roles.insert((role, account), &position);
+                        self.role_holders_len += 1;
+                        self.env().emit_event(RoleGranted {
+                            role,
+                            account,
+                            sender: self.env().caller(),
+                        });
+                    }
+                }
+                AdminAction::RevokeRole(role, account) => {
+                    if let Some(position) = self.roles.take((role, account)) {
+                        // Move the last entry into the freed position
+                        self.role_holders_len -= 1;
+                        let last = self.role_holders.take(self.role_holders_len);
+                        if let Some(last) = last.filter(|_| position != self.role_holders_len) {
+                            self.role_holders.insert(position, &last);
+                            self.roles.insert(last, &position);
+                        }
+                        self.env().emit_event(RoleRevoked {
+                            role,
+                            account,
+                            sender: self.env().caller(),
+                        });
+                    }
+                }
+                AdminAction::SetRoleAdmin(role, new_admin) => {
+                    let previous_admin = self.get_role_admin(role);
+                    self.role_admins.insert(role, &new_admin);
+                    self.env().emit_event(RoleAdminChanged {
+                        role,
+                        previous_admin,
+                        new_admin,
+                    });
+                }
+                AdminAction::SetDenied(account, true) => {
+                    if !self.denied_accounts.contains(account) {
+                        let position = self.deny_list_len;
+                        self.deny_list.insert(position, &account);
+                        self.denied_accounts.insert(account, &position);
+                        self.deny_list_len += 1;
+                        self.env().emit_event(DenyListUpdated { account, denied: true });
+                    }
+                }
+                AdminAction::SetDenied(account, false) => {
+                    if let Some(position) = self.denied_accounts.take(account) {
+                        // Move the last entry into the freed position
+                        self.deny_list_len -= 1;
+                        let last = self.deny_list.take(self.deny_list_len);
+                        if let Some(last) = last.filter(|_| position != self.deny_list_len) {
+                            self.deny_list.insert(position, &last);
+                            self.denied_accounts.insert(last, &position);
+                        }
+                        self.env().emit_event(DenyListUpdated { account, denied: false });
+                    }
+                }
+                AdminAction::CancelOperation(operation_id) => {
+                    let mut operation = self.pending_operation(operation_id)?;
+                    operation.status = OperationStatus::Cancelled;
+                    self.operations.insert(operation_id, &operation);
+                    self.remove_pending_operation(operation_id);
+                    self.env().emit_event(OperationCancelled { operation_id });
+                }
+            }
+            Ok(())
+        }
+
+        /// Fails with `AccountFrozen` if a guardian has frozen the user
+        fn ensure_not_frozen(&self, user: AccountId) -> Result<()> {
+            if self.frozen_accounts.contains(user) {
+                return Err(Error::AccountFrozen);
+            }
+            Ok(())
+        }
+
+        /// Fails with `Unauthorized` unless `guardian` is one of the user's guardians
+        fn ensure_guardian(&self, user: AccountId, guardian: AccountId) -> Result<()> {
+            let set = self.guardians.get(user).unwrap_or_default();
+            if !set.guardians.contains(&guardian) {
+                return Err(Error::Unauthorized);
+            }
+            Ok(())
+        }
+
+        /// Applies a pending limit change whose waiting period has passed
+        fn effective_limit(mut limit: SpendingLimit, now: Timestamp) -> SpendingLimit {
+            if let Some(pending) = limit.pending.clone() {
+                if now >= pending.effective_at {
+                    // A removed limit is kept as an unlimited cap until it is set again
+                    limit.amount = pending.amount.unwrap_or(Balance::MAX);
+                    limit.pending = None;
+                }
+            }
+            limit
+        }
+
+        /// Amount spent in the window containing the current block
+        fn spent_in_window(&self, key: &LimitKey) -> Balance {
+            let now = self.env().block_timestamp();
+            let window_start = now - now % key.2.duration();
+            match self.spending_windows.get(key) {
+                Some((start, spent)) if start == window_start => spent,
+                _ => 0,
+            }
+        }
+
+        /// Records a refund against a payment and updates its status and events
+        fn apply_refund(
+            &mut self,
+            original_sender: AccountId,
+            payment_index: u32,
+            refunder: AccountId,
+            amount: Balance,
+            reason: String,
+            transferred: bool,
+        ) {
+            let mut history = self.payment_history.get(original_sender).unwrap_or_default();
+            let record = &mut history[payment_index as usize];
+            record.refunded_amount += amount;
+            if record.status != PaymentStatus::Disputed {
+                record.status = Self::settled_status(record);
+            }
+            let payment_id = record.id;
+            let total_refunded = record.refunded_amount;
+            self.payment_history.insert(original_sender, &history);
+
+            let mut refunds = self.refunds.get(payment_id).unwrap_or_default();
+            refunds.push(RefundRecord {
+                refunder,
+                amount,
+                reason: reason.clone(),
+                timestamp: self.env().block_timestamp(),
+                transferred,
+            });
+            self.refunds.insert(payment_id, &refunds);
+
+            self.env().emit_event(PaymentRefunded {
+                original_sender,
+                refunder,
+                payment_id,
+                amount,
+                total_refunded,
+                reason,
+            });
+        }
+
+        /// Status of a payment that is not disputed, derived from its refunds
+        fn settled_status(record: &PaymentRecord) -> PaymentStatus {
+            if record.refunded_amount == 0 {
+                PaymentStatus::Completed
+            } else if record.refunded_amount >= record.amount {
+                PaymentStatus::Refunded
+            } else {
+                PaymentStatus::PartiallyRefunded
+            }
+        }
+
+        /// Loads a dispute that has not been closed yet
+        fn open_dispute_by_id(&self, dispute_id: DisputeId) -> Result<Dispute> {
+            let dispute = self.disputes.get(dispute_id).ok_or(Error::DisputeNotFound)?;
+            if dispute.status != DisputeStatus::Open {
+                return Err(Error::DisputeNotOpen);
+            }
+            Ok(dispute)
+        }
+
+        /// Settles a dispute's escrow, restores the payment's status and stores the outcome
+        fn close_dispute(&mut self, mut dispute: Dispute, status: DisputeStatus) -> Result<()> {
+            let (sender, index) = self
+                .payment_locations
+                .get(dispute.payment_id)
+                .ok_or(Error::PaymentNotFound)?;
+
+            let mut history = self.payment_history.get(sender).unwrap_or_default();
+            let record = &mut history[index as usize];
+            record.status = Self::settled_status(record);
+            // Voluntary refunds made during the dispute reduce what the escrow still owes
+            let refundable = record.amount - record.refunded_amount;
+            self.payment_history.insert(sender, &history);
+
+            let refunded = if status == DisputeStatus::Resolved(DisputeOutcome::RefundPayer) {
+                dispute.escrowed.min(refundable)
+            } else {
+                0
+            };
+            if refunded > 0 {
+                self.env()
+                    .transfer(dispute.payer, refunded)
+                    .map_err(|_| Error::TransferFailed)?;
+                self.apply_refund(
+                    sender,
+                    index,
+                    dispute.recipient,
+                    refunded,
+                    String::from("Dispute resolved in favour of the payer"),
+                    true,
+                );
+            }
+
+            let returned = dispute.escrowed - refunded;
+            if returned > 0 {
+                self.env()
+                    .transfer(dispute.recipient, returned)
+                    .map_err(|_| Error::TransferFailed)?;
+            }
+
+            dispute.status = status;
+            self.disputes.insert(dispute.id, &dispute);
+
+            self.env().emit_event(DisputeClosed {
+                dispute_id: dispute.id,
+                status,
+                refunded,
+            });
+
+            Ok(())
+        }
+
+        /// Reports an open invoice whose expiry has passed as `Expired`
+        fn with_current_status(&self, mut invoice: Invoice) -> Invoice {
+            if invoice.status == InvoiceStatus::Open
+                && self.env().block_timestamp() > invoice.expires_at
+            {
+                invoice.status = InvoiceStatus::Expired;
+            }
+            invoice
+        }
+
+        /// Validates the voice metadata shared by every recording message
+        fn validate_command(voice_command: &str, currency: &str, confidence: u8) -> Result<()> {
+            if voice_command.is_empty() || voice_command.len() > MAX_VOICE_COMMAND_LEN {
+                return Err(Error::InvalidVoiceCommand);
+            }
+
+            if currency.is_empty() || currency.len() > MAX_CURRENCY_LEN {
+                return Err(Error::InvalidCurrency);
+            }
+
+            if confidence > 100 {
+                return Err(Error::InvalidConfidence);
+            }
+
+            Ok(())
+        }
+
+        /// Checks that a locale is a BCP 47 tag: subtags of 1-8 ASCII letters or digits joined by "-"
+        fn validate_locale(locale: &str) -> Result<()> {
+            let well_formed = !locale.is_empty()
+                && locale.len() <= MAX_LOCALE_LEN
+                && locale.split('-').all(|subtag| {
+                    (1..=8).contains(&subtag.len())
+                        && subtag.bytes().all(|byte| byte.is_ascii_alphanumeric())
+                });
+            if !well_formed {
+                return Err(Error::InvalidLocale);
+            }
+            Ok(())
+        }
+
+        /// Appends a validated record to the sender's history and emits `PaymentRecorded`
+        fn store_payment(&mut self, sender: AccountId, mut record: PaymentRecord) -> PaymentId {
+            let payment_id = self.total_payments;
+            record.id = payment_id;
+            let pending = self.needs_confirmation(sender, record.amount, record.confidence);
+            if pending {
+                record.status = PaymentStatus::PendingConfirmation;
+            }
+            let confidence = record.confidence;
+            let recipient = record.recipient;
+            let amount = record.amount;
+            let timestamp = record.timestamp;
+            let currency = record.currency.clone();
+            let voice_command = record.voice_command.clone();
+            let locale = record.locale.clone();
+
+            // Get or create payment history for sender
+            let mut history = self.payment_history.get(&sender).unwrap_or_default();
+            history.push(record);
+
+            // Update storage
+            self.payment_locations
+                .insert(payment_id, &(sender, history.len() as u32 - 1));
+            self.payment_history.insert(&sender, &history);
+            self.total_payments += 1;
+
+            // Emit event
+            self.env().emit_event(PaymentRecorded {
+                payment_id,
+                sender,
+                recipient,
+                amount,
+                currency,
+                voice_command,
+                locale,
+                timestamp,
+            });
+
+            if pending {
+                let expires_at = timestamp.saturating_add(self.config.confirmation_window);
+                self.pending_confirmations.insert(payment_id, &expires_at);
+                self.env().emit_event(PaymentPendingConfirmation {
+                    payment_id,
+                    sender,
+                    confidence,
+                    expires_at,
+                });
+            }
+
+            payment_id
+        }
+    }
+
+    /// Unit tests for the contract
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        /// Helper function to create a test payment record
+        fn create_test_record() -> (AccountId, Balance, String, String, String, u8) {
+            let recipient = AccountId::from([0x01; 32]);
+            let amount = 1000000000000; // 1 DOT in Planck
+            let voice_command = String::from("Send 1 DOT to Alice");
+            let currency = String::from("DOT");
+            let network = String::from("polkadot");
+            let confidence = 95;
+            
+            (recipient, amount, voice_command, currency, network, confidence)
+        }
+
+        #[ink::test]
+        fn test_new_contract() {
+            let contract = PaymentRecorder::new();
+            assert_eq!(contract.get_total_payments(), 0);
+        }
+
+        #[ink::test]
+        fn test_record_payment_success() {
+            let mut contract = PaymentRecorder::new();
+            let (recipient, amount, voice_command, currency, network, confidence) = create_test_record();
+            
+            let result = contract.record_payment(
+                recipient,
+                amount,
+                voice_command.clone(),
+                currency,
+                network,
+                confidence,
+                None,
+            );
+            
+            assert!(result.is_ok());
+            assert_eq!(contract.get_total_payments(), 1);
+            
+            let history = contract.get_my_payment_history();
+            assert_eq!(history.len(), 1);
+            assert_eq!(history[0].recipient, recipient);
+            assert_eq!(history[0].amount, amount);
+            assert_eq!(history[0].voice_command, voice_command);
+        }
+
+        #[ink::test]
+        fn test_record_payment_invalid_amount() {
+            let mut contract = PaymentRecorder::new();
+            let (recipient, _, voice_command, currency, network, confidence) = create_test_record();
+            
+            let result = contract.record_payment(
+                recipient,
+                0, // Invalid amount
+                voice_command,
+                currency,
+                network,
+                confidence,
+                None,
+            );
+            
+            assert_eq!(result, Err(Error::InvalidAmount));
+        }
+
+        #[ink::test]
+        fn test_record_payment_invalid_voice_command() {
+            let mut contract = PaymentRecorder::new();
+            let (recipient, amount, _, currency, network, confidence) = create_test_record();
+            
+            let result = contract.record_payment(
+                recipient,
+                amount,
+                String::new(), // Empty voice command
+                currency,
+                network,
+                confidence,
+                None,
+            );
+            
+            assert_eq!(result, Err(Error::InvalidVoiceCommand));
+        }
+
+        #[ink::test]
+        fn test_record_payment_invalid_confidence() {
+            let mut contract = PaymentRecorder::new();
+            let (recipient, amount, voice_command, currency, network, _) = create_test_record();
+            
+            let result = contract.record_payment(
+                recipient,
+                amount,
+                voice_command,
+                currency,
+                network,
+                101, // Invalid confidence > 100
+                None,
+            );
+            
+            assert_eq!(result, Err(Error::InvalidConfidence));
+        }
+
+        #[ink::test]
+        fn test_user_stats() {
+            let mut contract = PaymentRecorder::new();
+            let (recipient, amount, voice_command, currency, network, confidence) = create_test_record();
+            
+            // Record multiple payments
+            for i in 0..3 {
+                let mut cmd = voice_command.clone();
+                cmd.push_str(&i.to_string());
+                contract.record_payment(
+                    recipient,
+                    amount,
+                    cmd,
+                    currency.clone(),
+                    network.clone(),
+                    confidence,
+                    None,
+                ).unwrap();
+            }
+            
+            let caller = contract.env().caller();
+            let (count, total_amount) = contract.get_user_stats(caller);
+            
+            assert_eq!(count, 3);
+            assert_eq!(total_amount, amount * 3);
+        }
+
+        #[ink::test]
+        fn test_recent_payments() {
+            let mut contract = PaymentRecorder::new();
+            let (recipient, amount, voice_command, currency, network, confidence) = create_test_record();
+            
+            // Record 5 payments
+            for i in 0..5 {
+                let mut cmd = voice_command.clone();
+                cmd.push_str(&i.to_string());
+                contract.record_payment(
+                    recipient,
+                    amount + i as u128,
+                    cmd,
+                    currency.clone(),
+                    network.clone(),
+                    confidence,
+                    None,
+                ).unwrap();
+            }
+            
+            let caller = contract.env().caller();
+            let recent = contract.get_recent_payments(caller, 3);
+            
+            assert_eq!(recent.len(), 3);
+            // Should return the last 3 payments
+            assert_eq!(recent[0].amount, amount + 2);
+            assert_eq!(recent[1].amount, amount + 3);
+            assert_eq!(recent[2].amount, amount + 4);
+        }
+
+        #[ink::test]
+        fn test_transfer_ownership() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let mut contract = PaymentRecorder::new();
+            let new_owner = accounts.bob;
+            
+            let operation_id = contract.transfer_ownership(new_owner).unwrap();
+            assert_eq!(contract.execute_operation(operation_id), Err(Error::OperationNotReady));
+            let operation = contract.get_operation(operation_id).unwrap();
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(operation.ready_at);
+            contract.execute_operation(operation_id).unwrap();
+            assert_eq!(contract.get_pending_owner(), Some(new_owner));
+            assert_eq!(contract.get_owner(), accounts.alice);
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.charlie);
+            assert_eq!(contract.accept_ownership(), Err(Error::Unauthorized));
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(new_owner);
+            contract.accept_ownership().unwrap();
+            assert_eq!(contract.get_owner(), new_owner);
+            assert_eq!(contract.get_pending_owner(), None);
+        }
+
+        #[ink::test]
+        fn test_operations_cancelled_when_administration_changes_hands() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            ink::env::test::set_callee::<ink::env::DefaultEnvironment>(accounts.frank);
+            let mut contract = PaymentRecorder::new();
+            let upgrade = contract.set_code_hash(Hash::from([0x01; 32])).unwrap();
+            let transfer = contract.transfer_ownership(accounts.eve).unwrap();
+            let ready_at = contract.get_operation(transfer).unwrap().ready_at;
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(ready_at);
+            contract.execute_operation(transfer).unwrap();
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.eve);
+            contract.accept_ownership().unwrap();
+
+            // The old owner's upgrade can no longer be executed
+            assert_eq!(contract.get_operation(upgrade).unwrap().status, OperationStatus::Cancelled);
+            assert!(contract.get_pending_operations(0, 10).is_empty());
+            assert_eq!(contract.execute_operation(upgrade), Err(Error::OperationNotPending));
+
+            // Nor can the new owner's once a council takes over
+            let config = contract.update_config(ContractConfig::default()).unwrap();
+            apply_council(&mut contract, Council {
+                members: vec![accounts.bob],
+                threshold: 1,
+            });
+            assert_eq!(contract.get_operation(config).unwrap().status, OperationStatus::Cancelled);
+            assert!(contract.get_pending_operations(0, 10).is_empty());
+        }
+
+        #[ink::test]
+        fn test_roles_revoked_when_administration_changes_hands() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            ink::env::test::set_callee::<ink::env::DefaultEnvironment>(accounts.frank);
+            let mut contract = PaymentRecorder::new();
+            contract.grant_role(Role::Admin, accounts.bob).unwrap();
+            contract.grant_role(Role::ConfigManager, accounts.charlie).unwrap();
+            contract.grant_role(Role::Pauser, accounts.charlie).unwrap();
+            contract.grant_role(Role::Pauser, accounts.django).unwrap();
+            contract.revoke_role(Role::Pauser, accounts.charlie).unwrap();
+            assert!(contract.has_role(Role::Pauser, accounts.django));
+
+            let operation_id = contract.transfer_ownership(accounts.eve).unwrap();
+            let operation = contract.get_operation(operation_id).unwrap();
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(operation.ready_at);
+            contract.execute_operation(operation_id).unwrap();
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.eve);
+            contract.accept_ownership().unwrap();
+            assert!(!contract.has_role(Role::Admin, accounts.bob));
+            assert!(!contract.has_role(Role::ConfigManager, accounts.charlie));
+            assert!(!contract.has_role(Role::Pauser, accounts.django));
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.charlie);
+            assert_eq!(contract.update_config(ContractConfig::default()), Err(Error::Unauthorized));
+
+            // Roles granted by the new owner do not outlive a council either
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.eve);
+            contract.grant_role(Role::ConfigManager, accounts.charlie).unwrap();
+            contract.grant_role(Role::Admin, accounts.bob).unwrap();
+            apply_council(&mut contract, Council {
+                members: vec![accounts.bob, accounts.django],
+                threshold: 2,
+            });
+            assert!(!contract.has_role(Role::ConfigManager, accounts.charlie));
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.charlie);
+            assert_eq!(contract.update_config(ContractConfig::default()), Err(Error::Unauthorized));
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.grant_role(Role::ConfigManager, accounts.bob), Err(Error::Unauthorized));
+
+            // The council grants roles through proposals, and later membership
+            // changes keep them
+            let proposal_id = contract
+                .propose_admin_action(AdminAction::GrantRole(Role::Pauser, accounts.charlie))
+                .unwrap();
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.django);
+            contract.approve_proposal(proposal_id).unwrap();
+            contract.execute_proposal(proposal_id).unwrap();
+            let proposal_id = contract
+                .propose_admin_action(AdminAction::UpdateCouncil(Council {
+                    members: vec![accounts.bob, accounts.django, accounts.eve],
+                    threshold: 2,
+                }))
+                .unwrap();
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            contract.approve_proposal(proposal_id).unwrap();
+            contract.execute_proposal(proposal_id).unwrap();
+            let operation = contract.get_pending_operations(0, 1).pop().unwrap();
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(operation.ready_at);
+            contract.execute_operation(operation.id).unwrap();
+            assert_eq!(contract.get_council().unwrap().members.len(), 3);
+            assert!(contract.has_role(Role::Pauser, accounts.charlie));
+        }
+
+        #[ink::test]
+        fn test_record_split_payment() {
+            let mut contract = PaymentRecorder::new();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let shares = vec![(accounts.bob, 10), (accounts.charlie, 10), (accounts.django, 10)];
+
+            let split_id = contract.record_split_payment(
+                shares.clone(),
+                String::from("Split 30 DOT between Bob, Charlie and Django"),
+                String::from("DOT"),
+                String::from("polkadot"),
+                95,
+                None,
+            ).unwrap();
+
+            assert_eq!(contract.get_total_payments(), 3);
+            let history = contract.get_payment_history(accounts.alice);
+            assert_eq!(history.len(), 3);
+            assert!(history.iter().all(|record| record.split_id == Some(split_id)));
+
+            let split = contract.get_split_payment(split_id).unwrap();
+            assert_eq!(split.payer, accounts.alice);
+            assert_eq!(split.shares, shares);
+            assert_eq!(split.total_amount, 30);
+            assert!(!split.transferred);
+        }
+
+        #[ink::test]
+        fn test_record_split_payment_invalid_shares() {
+            let mut contract = PaymentRecorder::new();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let mut split = |shares: Vec<(AccountId, Balance)>| {
+                contract.record_split_payment(
+                    shares,
+                    String::from("Split between friends"),
+                    String::from("DOT"),
+                    String::from("polkadot"),
+                    95,
+                    None,
+                )
+            };
+
+            assert_eq!(split(vec![]), Err(Error::InvalidSplit));
+            assert_eq!(split(vec![(accounts.bob, 10), (accounts.bob, 5)]), Err(Error::InvalidSplit));
+            assert_eq!(split(vec![(accounts.bob, 10), (accounts.charlie, 0)]), Err(Error::InvalidSplit));
+            assert_eq!(
+                split(vec![(accounts.bob, Balance::MAX), (accounts.charlie, 1)]),
+                Err(Error::AmountOverflow)
+            );
+            assert_eq!(contract.get_total_payments(), 0);
+        }
+
+        #[ink::test]
+        fn test_record_split_payment_with_transfer() {
+            let mut contract = PaymentRecorder::new();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let bob_before = ink::env::test::get_account_balance::<ink::env::DefaultEnvironment>(accounts.bob).unwrap();
+
+            let result = ink::env::pay_with_call!(
+                contract.record_split_payment(
+                    vec![(accounts.bob, 20), (accounts.charlie, 10)],
+                    String::from("Split 30 between Bob and Charlie"),
+                    String::from("DOT"),
+                    String::from("polkadot"),
+                    95,
+                    None
+                ),
+                30
+            );
+
+            let split = contract.get_split_payment(result.unwrap()).unwrap();
+            assert!(split.transferred);
+            let bob_after = ink::env::test::get_account_balance::<ink::env::DefaultEnvironment>(accounts.bob).unwrap();
+            assert_eq!(bob_after, bob_before + 20);
+        }
+
+        #[ink::test]
+        fn test_record_split_payment_value_mismatch() {
+            let mut contract = PaymentRecorder::new();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+
+            let result = ink::env::pay_with_call!(
+                contract.record_split_payment(
+                    vec![(accounts.bob, 20), (accounts.charlie, 10)],
+                    String::from("Split 30 between Bob and Charlie"),
+                    String::from("DOT"),
+                    String::from("polkadot"),
+                    95,
+                    None
+                ),
+                25
+            );
+
+            assert_eq!(result, Err(Error::TransferredValueMismatch));
+        }
+
+        #[ink::test]
+        fn test_register_token() {
+            let mut contract = PaymentRecorder::new();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let usdc = AccountId::from([0x10; 32]);
+
+            contract.register_token(usdc, String::from("USDC"), 6).unwrap();
+            assert_eq!(
+                contract.get_token(usdc),
+                Some(TokenInfo { symbol: String::from("USDC"), decimals: 6 })
+            );
+
+            contract.unregister_token(usdc).unwrap();
+            assert_eq!(contract.get_token(usdc), None);
+            assert_eq!(contract.unregister_token(usdc), Err(Error::TokenNotRegistered));
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(
+                contract.register_token(usdc, String::from("USDC"), 6),
+                Err(Error::Unauthorized)
+            );
+        }
+
+        #[ink::test]
+        fn test_record_token_payment_unregistered_token() {
+            let mut contract = PaymentRecorder::new();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+
+            let result = contract.record_token_payment(
+                AccountId::from([0x10; 32]),
+                accounts.bob,
+                1_000_000,
+                String::from("Send 1 USDC to Bob"),
+                String::from("astar"),
+                95,
+                None,
+            );
+
+            assert_eq!(result, Err(Error::TokenNotRegistered));
+            assert_eq!(contract.get_total_payments(), 0);
+        }
+
+        #[ink::test]
+        fn test_record_asset_payment_unregistered_asset() {
+            let mut contract = PaymentRecorder::new();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+
+            let result = contract.record_asset_payment(
+                1984,
+                accounts.bob,
+                20_000_000,
+                String::from("Send 20 USDT to Bob"),
+                String::from("asset-hub-polkadot"),
+                95,
+                None,
+            );
+
+            assert_eq!(result, Err(Error::AssetNotRegistered));
+        }
+
+        #[cfg(not(feature = "assets-extension"))]
+        #[ink::test]
+        fn test_record_asset_payment_without_extension() {
+            let mut contract = PaymentRecorder::new();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            contract.register_asset(1984, String::from("USDT"), 6).unwrap();
+
+            let result = contract.record_asset_payment(
+                1984,
+                accounts.bob,
+                20_000_000,
+                String::from("Send 20 USDT to Bob"),
+                String::from("asset-hub-polkadot"),
+                95,
+                None,
+            );
+
+            assert_eq!(result, Err(Error::AssetsUnsupported));
+        }
+
+        /// Off-chain stand-in for the runtime's `pallet-assets` chain extension
+        #[cfg(feature = "assets-extension")]
+        struct MockedAssets {
+            balances: std::collections::HashMap<(AssetId, AccountId), Balance>,
+        }
+
+        #[cfg(feature = "assets-extension")]
+        impl ink::env::test::ChainExtension for MockedAssets {
+            fn ext_id(&self) -> u16 {
+                crate::assets_extension::ASSETS_EXTENSION_ID
+            }
+
+            fn call(&mut self, func_id: u16, mut input: &[u8], output: &mut Vec<u8>) -> u32 {
+                use crate::assets_extension::{functions, AssetsError};
+                use ink::scale::{Decode, Encode};
+
+                match func_id {
+                    functions::BALANCE_OF => {
+                        let key = <(AssetId, AccountId)>::decode(&mut input).unwrap();
+                        self.balances.get(&key).copied().unwrap_or(0).encode_to(output);
+                        0
+                    }
+                    functions::TRANSFER_APPROVED => {
+                        let (asset_id, owner, dest, amount) =
+                            <(AssetId, AccountId, AccountId, Balance)>::decode(&mut input).unwrap();
+                        let owner_balance = self.balances.entry((asset_id, owner)).or_insert(0);
+                        if *owner_balance < amount {
+                            return AssetsError::InsufficientBalance.status_code();
+                        }
+                        *owner_balance -= amount;
+                        *self.balances.entry((asset_id, dest)).or_insert(0) += amount;
+                        0
+                    }
+                    _ => AssetsError::Failed.status_code(),
+                }
+            }
+        }
+
+        #[cfg(feature = "assets-extension")]
+        #[ink::test]
+        fn test_record_asset_payment_with_extension() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let mut balances = std::collections::HashMap::new();
+            balances.insert((1984, accounts.alice), 50_000_000);
+            ink::env::test::register_chain_extension(MockedAssets { balances });
+
+            let mut contract = PaymentRecorder::new();
+            contract.register_asset(1984, String::from("USDT"), 6).unwrap();
+
+            let payment_id = contract.record_asset_payment(
+                1984,
+                accounts.bob,
+                20_000_000,
+                String::from("Send 20 USDT to Bob"),
+                String::from("asset-hub-polkadot"),
+                95,
+                None,
+            ).unwrap();
+            let record = contract.get_payment(payment_id).unwrap();
+            assert_eq!(record.asset, PaymentAsset::PalletAsset(1984));
+            assert_eq!(record.currency, "USDT");
+
+            let result = contract.record_asset_payment(
+                1984,
+                accounts.bob,
+                40_000_000,
+                String::from("Send 40 USDT to Bob"),
+                String::from("asset-hub-polkadot"),
+                95,
+                None,
+            );
+            assert_eq!(result, Err(Error::InsufficientAssetBalance));
+        }
+
+        #[ink::test]
+        fn test_invoice_lifecycle() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let mut contract = PaymentRecorder::new();
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            let invoice_id = contract.create_invoice(
+                5_000,
+                String::from("DOT"),
+                String::from("Coffee beans"),
+                1_000,
+            ).unwrap();
+            assert_eq!(contract.get_open_invoices(accounts.bob, 0, 10).len(), 1);
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            let payment_id = contract.pay_invoice(
+                invoice_id,
+                String::from("Pay invoice 0"),
+                String::from("polkadot"),
+                95,
+                None,
+            ).unwrap();
+
+            let invoice = contract.get_invoice(invoice_id).unwrap();
+            assert_eq!(invoice.status, InvoiceStatus::Paid);
+            assert_eq!(invoice.payer, Some(accounts.alice));
+            assert_eq!(invoice.payment_id, Some(payment_id));
+
+            let record = contract.get_payment(payment_id).unwrap();
+            assert_eq!(record.recipient, accounts.bob);
+            assert_eq!(record.amount, 5_000);
+            assert_eq!(record.invoice_id, Some(invoice_id));
+
+            assert_eq!(
+                contract.pay_invoice(invoice_id, String::from("Pay invoice 0"), String::from("polkadot"), 95, None),
+                Err(Error::InvoiceNotOpen)
+            );
+            assert!(contract.get_open_invoices(accounts.bob, 0, 10).is_empty());
+        }
+
+        #[ink::test]
+        fn test_invoice_expiry_and_cancellation() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let mut contract = PaymentRecorder::new();
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            let expiring = contract.create_invoice(5_000, String::from("DOT"), String::new(), 1).unwrap();
+            let cancelled = contract.create_invoice(5_000, String::from("DOT"), String::new(), 1_000_000).unwrap();
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert_eq!(contract.cancel_invoice(cancelled), Err(Error::Unauthorized));
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            contract.cancel_invoice(cancelled).unwrap();
+            assert_eq!(contract.get_invoice(cancelled).unwrap().status, InvoiceStatus::Cancelled);
+
+            ink::env::test::advance_block::<ink::env::DefaultEnvironment>();
+            assert_eq!(contract.get_invoice(expiring).unwrap().status, InvoiceStatus::Expired);
+            assert!(contract.get_open_invoices(accounts.bob, 0, 10).is_empty());
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert_eq!(
+                contract.pay_invoice(expiring, String::from("Pay invoice 0"), String::from("polkadot"), 95, None),
+                Err(Error::InvoiceExpired)
+            );
+        }
+
+        #[ink::test]
+        fn test_record_refund_partial_and_full() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let mut contract = PaymentRecorder::new();
+            contract.record_payment(
+                accounts.bob,
+                100,
+                String::from("Send 100 to Bob"),
+                String::from("DOT"),
+                String::from("polkadot"),
+                95,
+                None,
+            ).unwrap();
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            contract.record_refund(accounts.alice, 0, 40, String::from("Overcharged")).unwrap();
+            let record = contract.get_payment_history(accounts.alice)[0].clone();
+            assert_eq!(record.status, PaymentStatus::PartiallyRefunded);
+            assert_eq!(record.refunded_amount, 40);
+
+            assert_eq!(
+                contract.record_refund(accounts.alice, 0, 61, String::from("Too much")),
+                Err(Error::RefundExceedsPayment)
+            );
+
+            contract.record_refund(accounts.alice, 0, 60, String::from("Order cancelled")).unwrap();
+            let record = contract.get_payment_history(accounts.alice)[0].clone();
+            assert_eq!(record.status, PaymentStatus::Refunded);
+
+            let refunds = contract.get_refunds(accounts.alice, 0);
+            assert_eq!(refunds.len(), 2);
+            assert_eq!(refunds[1].refunder, accounts.bob);
+            assert_eq!(refunds[1].reason, "Order cancelled");
+        }
+
+        #[ink::test]
+        fn test_record_refund_only_by_recipient() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let mut contract = PaymentRecorder::new();
+            contract.record_payment(
+                accounts.bob,
+                100,
+                String::from("Send 100 to Bob"),
+                String::from("DOT"),
+                String::from("polkadot"),
+                95,
+                None,
+            ).unwrap();
+
+            assert_eq!(
+                contract.record_refund(accounts.alice, 0, 10, String::new()),
+                Err(Error::Unauthorized)
+            );
+            assert_eq!(
+                contract.record_refund(accounts.alice, 1, 10, String::new()),
+                Err(Error::PaymentNotFound)
+            );
+        }
+
+        fn setup_dispute() -> (PaymentRecorder, DisputeId) {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let mut contract = PaymentRecorder::new();
+            contract.set_arbiter(accounts.eve, true).unwrap();
+            let payment_id = contract.record_payment(
+                accounts.bob,
+                100,
+                String::from("Send 100 to Bob"),
+                String::from("DOT"),
+                String::from("polkadot"),
+                95,
+                None,
+            ).unwrap();
+            let dispute_id = contract.open_dispute(payment_id, Hash::from([0x01; 32])).unwrap();
+            (contract, dispute_id)
+        }
+
+        #[ink::test]
+        fn test_open_dispute() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let (mut contract, dispute_id) = setup_dispute();
+
+            let history = contract.get_payment_history(accounts.alice);
+            assert_eq!(history[0].status, PaymentStatus::Disputed);
+            let dispute = contract.get_dispute(dispute_id).unwrap();
+            assert_eq!(dispute.recipient, accounts.bob);
+            assert_eq!(dispute.disputed_amount, 100);
+            assert_eq!(dispute.status, DisputeStatus::Open);
+
+            assert_eq!(
+                contract.open_dispute(0, Hash::from([0x02; 32])),
+                Err(Error::PaymentNotDisputable)
+            );
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.open_dispute(0, Hash::from([0x02; 32])), Err(Error::Unauthorized));
+        }
+
+        #[ink::test]
+        fn test_resolve_dispute_refunds_escrow() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let (mut contract, dispute_id) = setup_dispute();
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            ink::env::pay_with_call!(contract.escrow_dispute_funds(dispute_id), 100).unwrap();
+            assert_eq!(
+                contract.resolve_dispute(dispute_id, DisputeOutcome::RefundPayer),
+                Err(Error::Unauthorized)
+            );
+
+            let alice_before = ink::env::test::get_account_balance::<ink::env::DefaultEnvironment>(accounts.alice).unwrap();
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.eve);
+            contract.resolve_dispute(dispute_id, DisputeOutcome::RefundPayer).unwrap();
+
+            let alice_after = ink::env::test::get_account_balance::<ink::env::DefaultEnvironment>(accounts.alice).unwrap();
+            assert_eq!(alice_after, alice_before + 100);
+            let record = contract.get_payment_history(accounts.alice)[0].clone();
+            assert_eq!(record.status, PaymentStatus::Refunded);
+            assert_eq!(contract.get_refunds(accounts.alice, 0).len(), 1);
+            assert_eq!(
+                contract.get_dispute(dispute_id).unwrap().status,
+                DisputeStatus::Resolved(DisputeOutcome::RefundPayer)
+            );
+        }
+
+        #[ink::test]
+        fn test_reject_dispute_restores_payment() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let (mut contract, dispute_id) = setup_dispute();
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.eve);
+            contract.resolve_dispute(dispute_id, DisputeOutcome::RejectClaim).unwrap();
+
+            let record = contract.get_payment_history(accounts.alice)[0].clone();
+            assert_eq!(record.status, PaymentStatus::Completed);
+            assert_eq!(
+                contract.resolve_dispute(dispute_id, DisputeOutcome::RefundPayer),
+                Err(Error::DisputeNotOpen)
+            );
+        }
+
+        #[ink::test]
+        fn test_dispute_deadline() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let mut contract = PaymentRecorder::new();
+            apply_config(&mut contract, ContractConfig {
+                dispute_resolution_period: 1,
+                ..Default::default()
+            });
+            contract.set_arbiter(accounts.eve, true).unwrap();
+            let payment_id = contract.record_payment(
+                accounts.bob,
+                100,
+                String::from("Send 100 to Bob"),
+                String::from("DOT"),
+                String::from("polkadot"),
+                95,
+                None,
+            ).unwrap();
+            let dispute_id = contract.open_dispute(payment_id, Hash::from([0x01; 32])).unwrap();
+            assert_eq!(contract.close_lapsed_dispute(dispute_id), Err(Error::DisputeDeadlineNotReached));
+
+            ink::env::test::advance_block::<ink::env::DefaultEnvironment>();
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.eve);
+            assert_eq!(
+                contract.resolve_dispute(dispute_id, DisputeOutcome::RefundPayer),
+                Err(Error::DisputeDeadlinePassed)
+            );
+            contract.close_lapsed_dispute(dispute_id).unwrap();
+            assert_eq!(contract.get_dispute(dispute_id).unwrap().status, DisputeStatus::Lapsed);
+        }
+
+        #[ink::test]
+        fn test_spending_limit_enforced() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let mut contract = PaymentRecorder::new();
+            contract.set_spending_limit(String::from("DOT"), LimitPeriod::Day, Some(100)).unwrap();
+
+            let mut pay = |amount: Balance| {
+                contract.record_payment(
+                    accounts.bob,
+                    amount,
+                    String::from("Send DOT to Bob"),
+                    String::from("DOT"),
+                    String::from("polkadot"),
+                    95,
+                    None,
+                )
+            };
+            assert!(pay(60).is_ok());
+            assert_eq!(pay(50), Err(Error::SpendingLimitExceeded { remaining: 40 }));
+            assert!(pay(40).is_ok());
+
+            // Other currencies are not affected
+            assert!(contract.record_payment(
+                accounts.bob,
+                500,
+                String::from("Send USDC to Bob"),
+                String::from("USDC"),
+                String::from("polkadot"),
+                95,
+                None,
+            ).is_ok());
+            assert_eq!(
+                contract.get_remaining_allowance(accounts.alice, String::from("DOT"), LimitPeriod::Day),
+                Some(0)
+            );
+        }
+
+        #[ink::test]
+        fn test_spending_limit_raise_waits_for_delay() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let mut contract = PaymentRecorder::new();
+            apply_config(&mut contract, ContractConfig {
+                limit_increase_delay: 1,
+                ..Default::default()
+            });
+            contract.set_spending_limit(String::from("DOT"), LimitPeriod::Week, Some(100)).unwrap();
+
+            contract.set_spending_limit(String::from("DOT"), LimitPeriod::Week, Some(1_000)).unwrap();
+            let limit = contract
+                .get_spending_limit(accounts.alice, String::from("DOT"), LimitPeriod::Week)
+                .unwrap();
+            assert_eq!(limit.amount, 100);
+            assert_eq!(limit.pending.unwrap().amount, Some(1_000));
+
+            // Lowering applies immediately and drops the pending raise
+            contract.set_spending_limit(String::from("DOT"), LimitPeriod::Week, Some(50)).unwrap();
+            let limit = contract
+                .get_spending_limit(accounts.alice, String::from("DOT"), LimitPeriod::Week)
+                .unwrap();
+            assert_eq!(limit, SpendingLimit { amount: 50, pending: None });
+
+            contract.set_spending_limit(String::from("DOT"), LimitPeriod::Week, None).unwrap();
+            assert!(contract
+                .get_spending_limit(accounts.alice, String::from("DOT"), LimitPeriod::Week)
+                .is_some());
+            ink::env::test::advance_block::<ink::env::DefaultEnvironment>();
+            assert_eq!(
+                contract.get_spending_limit(accounts.alice, String::from("DOT"), LimitPeriod::Week),
+                None
+            );
+        }
+
+        #[ink::test]
+        fn test_guardian_freeze_blocks_payments() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let mut contract = PaymentRecorder::new();
+            contract.add_guardian(accounts.charlie).unwrap();
+            assert_eq!(contract.add_guardian(accounts.charlie), Err(Error::InvalidGuardian));
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(
+                contract.freeze_account(accounts.alice, String::from("Lost phone")),
+                Err(Error::Unauthorized)
+            );
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.charlie);
+            contract.freeze_account(accounts.alice, String::from("Lost phone")).unwrap();
+            assert_eq!(contract.get_freeze_state(accounts.alice).unwrap().reason, "Lost phone");
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            let (recipient, amount, voice_command, currency, network, confidence) = create_test_record();
+            assert_eq!(
+                contract.record_payment(recipient, amount, voice_command, currency, network, confidence, None),
+                Err(Error::AccountFrozen)
+            );
+            assert_eq!(contract.remove_guardian(accounts.charlie), Err(Error::AccountFrozen));
+
+            // Other users can still record payments
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            let (recipient, amount, voice_command, currency, network, confidence) = create_test_record();
+            assert!(contract.record_payment(recipient, amount, voice_command, currency, network, confidence, None).is_ok());
+        }
+
+        #[ink::test]
+        fn test_unfreeze_requires_threshold() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let mut contract = PaymentRecorder::new();
+            contract.add_guardian(accounts.bob).unwrap();
+            contract.add_guardian(accounts.charlie).unwrap();
+            contract.add_guardian(accounts.django).unwrap();
+            assert_eq!(contract.set_guardian_threshold(4), Err(Error::InvalidThreshold));
+            contract.set_guardian_threshold(2).unwrap();
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            contract.freeze_account(accounts.alice, String::from("Suspicious activity")).unwrap();
+            contract.approve_unfreeze(accounts.alice).unwrap();
+            assert_eq!(contract.approve_unfreeze(accounts.alice), Err(Error::AlreadyApproved));
+            assert!(contract.get_freeze_state(accounts.alice).is_some());
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.django);
+            contract.approve_unfreeze(accounts.alice).unwrap();
+            assert!(contract.get_freeze_state(accounts.alice).is_none());
+            assert_eq!(contract.approve_unfreeze(accounts.alice), Err(Error::AccountNotFrozen));
+        }
+
+        #[ink::test]
+        fn test_record_payment_for_delegate() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let mut contract = PaymentRecorder::new();
+            contract.authorize_delegate(accounts.eve, String::from("DOT"), 1_000, 50, 80).unwrap();
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.eve);
+            let mut pay_for = |amount: Balance, currency: &str| {
+                contract.record_payment_for(
+                    accounts.alice,
+                    accounts.bob,
+                    amount,
+                    String::from("Send DOT to Bob"),
+                    String::from(currency),
+                    String::from("polkadot"),
+                    95,
+                    None,
+                )
+            };
+            let payment_id = pay_for(50, "DOT").unwrap();
+            assert_eq!(pay_for(60, "DOT"), Err(Error::DelegateLimitExceeded));
+            assert_eq!(pay_for(40, "DOT"), Err(Error::DelegateLimitExceeded));
+            assert_eq!(pay_for(10, "USDC"), Err(Error::DelegateNotAuthorized));
+
+            let record = contract.get_payment(payment_id).unwrap();
+            assert_eq!(record.submitted_by, Some(accounts.eve));
+            assert_eq!(contract.get_payment_history(accounts.alice).len(), 1);
+            assert!(contract.get_payment_history(accounts.eve).is_empty());
+            assert_eq!(contract.get_delegate(accounts.alice, accounts.eve).unwrap().spent, 50);
+        }
+
+        #[ink::test]
+        fn test_delegate_cap_charged_on_confirmation() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let mut contract = PaymentRecorder::new();
+            contract.authorize_delegate(accounts.eve, String::from("DOT"), u64::MAX, 50, 80).unwrap();
+            contract.set_confidence_policy(Some(ConfidencePolicy { min_confidence: 90, tiers: vec![] })).unwrap();
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.eve);
+            let pay_for = |contract: &mut PaymentRecorder, amount: Balance| {
+                contract.record_payment_for(
+                    accounts.alice,
+                    accounts.bob,
+                    amount,
+                    String::from("Send DOT to Bob"),
+                    String::from("DOT"),
+                    String::from("polkadot"),
+                    50,
+                    None,
+                )
+            };
+            let spent = |contract: &PaymentRecorder| contract.get_delegate(accounts.alice, accounts.eve).unwrap().spent;
+
+            // An expired payment never uses up the cap
+            let expiring = pay_for(&mut contract, 50).unwrap();
+            assert_eq!(spent(&contract), 0);
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(
+                contract.get_confirmation_deadline(expiring).unwrap() + 1,
+            );
+            contract.expire_pending(expiring).unwrap();
+            assert_eq!(spent(&contract), 0);
+
+            // A confirmed one is charged, and cannot push the total past the cap
+            let confirmed = pay_for(&mut contract, 50).unwrap();
+            let over_cap = pay_for(&mut contract, 40).unwrap();
+            assert_eq!(spent(&contract), 0);
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            contract.confirm_pending(confirmed).unwrap();
+            assert_eq!(spent(&contract), 50);
+            assert_eq!(contract.confirm_pending(over_cap), Err(Error::DelegateLimitExceeded));
+            assert_eq!(spent(&contract), 50);
+        }
+
+        #[ink::test]
+        fn test_revoke_delegate() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let mut contract = PaymentRecorder::new();
+            contract.authorize_delegate(accounts.eve, String::from("DOT"), 1, 50, 80).unwrap();
+
+            ink::env::test::advance_block::<ink::env::DefaultEnvironment>();
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.eve);
+            assert_eq!(
+                contract.record_payment_for(
+                    accounts.alice,
+                    accounts.bob,
+                    10,
+                    String::from("Send DOT to Bob"),
+                    String::from("DOT"),
+                    String::from("polkadot"),
+                    95,
+                    None,
+                ),
+                Err(Error::DelegateExpired)
+            );
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            contract.revoke_delegate(accounts.eve).unwrap();
+            assert_eq!(contract.get_delegate(accounts.alice, accounts.eve), None);
+            assert_eq!(contract.revoke_delegate(accounts.eve), Err(Error::DelegateNotAuthorized));
+        }
+
+        fn signed_intent(signer: AccountId, nonce: u64, expires_at: Timestamp) -> (PaymentIntent, String) {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let voice_command = String::from("Send 5 DOT to Bob");
+            let mut command_hash = <Blake2x256 as ink::env::hash::HashOutput>::Type::default();
+            ink::env::hash_bytes::<Blake2x256>(voice_command.as_bytes(), &mut command_hash);
+            let intent = PaymentIntent {
+                signer,
+                recipient: accounts.bob,
+                amount: 5,
+                currency: String::from("DOT"),
+                network: String::from("polkadot"),
+                confidence: 95,
+                command_hash: Hash::from(command_hash),
+                nonce,
+                expires_at,
+                locale: Some(String::from("en")),
+            };
+            (intent, voice_command)
+        }
+
+        fn intent_payload(intent: &PaymentIntent) -> Vec<u8> {
+            let contract = ink::env::account_id::<ink::env::DefaultEnvironment>();
+            scale::Encode::encode(&(INTENT_DOMAIN, contract, intent))
+        }
+
+        fn blake2_256(data: &[u8]) -> [u8; 32] {
+            let mut output = <Blake2x256 as ink::env::hash::HashOutput>::Type::default();
+            ink::env::hash_bytes::<Blake2x256>(data, &mut output);
+            output
+        }
+
+        fn sr25519_signer() -> (AccountId, impl Fn(&PaymentIntent) -> IntentSignature) {
+            let keypair = schnorrkel::MiniSecretKey::from_bytes(&[0x42; 32])
+                .unwrap()
+                .expand_to_keypair(schnorrkel::ExpansionMode::Ed25519);
+            let signer = AccountId::from(keypair.public.to_bytes());
+            let sign = move |intent: &PaymentIntent| {
+                let context = schnorrkel::signing_context(b"substrate");
+                IntentSignature::Sr25519(keypair.sign(context.bytes(&intent_payload(intent))).to_bytes())
+            };
+            (signer, sign)
+        }
+
+        fn ecdsa_signer() -> (AccountId, impl Fn(&PaymentIntent) -> IntentSignature) {
+            let secp = secp256k1::Secp256k1::new();
+            let secret = secp256k1::SecretKey::from_slice(&[0x42; 32]).unwrap();
+            let public_key = secp256k1::PublicKey::from_secret_key(&secp, &secret).serialize();
+            let signer = AccountId::from(blake2_256(&public_key));
+            let sign = move |intent: &PaymentIntent| {
+                let message = secp256k1::Message::from_slice(&blake2_256(&intent_payload(intent))).unwrap();
+                let (recovery_id, compact) = secp.sign_ecdsa_recoverable(&message, &secret).serialize_compact();
+                let mut signature = [0; 65];
+                signature[..64].copy_from_slice(&compact);
+                signature[64] = recovery_id.to_i32() as u8;
+                IntentSignature::Ecdsa(signature)
+            };
+            (signer, sign)
+        }
+
+        fn assert_signed_intent_recorded(signer: AccountId, sign: impl Fn(&PaymentIntent) -> IntentSignature) {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let mut contract = PaymentRecorder::new();
+            contract.grant_role(Role::Relayer, accounts.eve).unwrap();
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.eve);
+
+            let (intent, voice_command) = signed_intent(signer, 0, 1_000);
+            let signature = sign(&intent);
+            let payment_id = contract
+                .record_signed_intent(intent.clone(), signature.clone(), voice_command.clone())
+                .unwrap();
+
+            let record = contract.get_payment(payment_id).unwrap();
+            assert_eq!(record.recipient, accounts.bob);
+            assert_eq!(record.amount, 5);
+            assert_eq!(record.voice_command, voice_command);
+            assert_eq!(record.locale, Some(String::from("en")));
+            assert_eq!(record.submitted_by, Some(accounts.eve));
+            assert_eq!(contract.get_payment_history(signer), vec![record]);
+            assert_eq!(contract.get_intent_nonce(signer), 1);
+
+            assert_eq!(
+                contract.record_signed_intent(intent, signature, voice_command),
+                Err(Error::InvalidNonce)
+            );
+            assert_eq!(contract.get_intent_nonce(signer), 1);
+            assert_eq!(contract.get_total_payments(), 1);
+        }
+
+        #[ink::test]
+        fn test_record_signed_intent_sr25519() {
+            let (signer, sign) = sr25519_signer();
+            assert_signed_intent_recorded(signer, sign);
+        }
+
+        #[ink::test]
+        fn test_record_signed_intent_ecdsa() {
+            let (signer, sign) = ecdsa_signer();
+            assert_signed_intent_recorded(signer, sign);
+        }
+
+        #[ink::test]
+        fn test_record_signed_intent_rejects_invalid_intents() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let mut contract = PaymentRecorder::new();
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.eve);
+            let (intent, voice_command) = signed_intent(accounts.alice, 0, 1_000);
+            assert_eq!(
+                contract.record_signed_intent(intent, IntentSignature::Sr25519([0; 64]), voice_command),
+                Err(Error::Unauthorized)
+            );
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            contract.grant_role(Role::Relayer, accounts.eve).unwrap();
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.eve);
+
+            let (intent, voice_command) = signed_intent(accounts.alice, 1, 1_000);
+            assert_eq!(
+                contract.record_signed_intent(intent, IntentSignature::Sr25519([0; 64]), voice_command),
+                Err(Error::InvalidNonce)
+            );
+
+            let (intent, _) = signed_intent(accounts.alice, 0, 1_000);
+            assert_eq!(
+                contract.record_signed_intent(intent, IntentSignature::Sr25519([0; 64]), String::from("Send 50 DOT to Bob")),
+                Err(Error::CommandHashMismatch)
+            );
+
+            let (intent, voice_command) = signed_intent(accounts.alice, 0, 1_000);
+            assert_eq!(
+                contract.record_signed_intent(intent, IntentSignature::Ecdsa([0; 65]), voice_command),
+                Err(Error::InvalidSignature)
+            );
+
+            ink::env::test::advance_block::<ink::env::DefaultEnvironment>();
+            let (intent, voice_command) = signed_intent(accounts.alice, 0, 0);
+            assert_eq!(
+                contract.record_signed_intent(intent, IntentSignature::Sr25519([0; 64]), voice_command),
+                Err(Error::IntentExpired)
+            );
+
+            assert_eq!(contract.get_intent_nonce(ink::env::test::default_accounts::<ink::env::DefaultEnvironment>().alice), 0);
+            assert_eq!(contract.get_total_payments(), 0);
+        }
+
+        #[ink::test]
+        fn test_pause_blocks_recording() {
+            let mut contract = PaymentRecorder::new();
+            contract.set_paused(true).unwrap();
+            assert!(contract.is_paused());
+
+            let (recipient, amount, voice_command, currency, network, confidence) = create_test_record();
+            assert_eq!(
+                contract.record_payment(recipient, amount, voice_command, currency, network, confidence, None),
+                Err(Error::ContractPaused)
+            );
+        }
+
+        #[ink::test]
+        fn test_council_replaces_owner() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            ink::env::test::set_callee::<ink::env::DefaultEnvironment>(accounts.frank);
+            let mut contract = PaymentRecorder::new();
+            assert_eq!(
+                contract.establish_council(Council { members: vec![accounts.bob], threshold: 2 }),
+                Err(Error::InvalidCouncil)
+            );
+            apply_council(&mut contract, Council {
+                members: vec![accounts.bob, accounts.charlie, accounts.django],
+                threshold: 2,
+            });
+
+            // The former owner has no direct admin rights any more
+            assert_eq!(contract.set_paused(true), Err(Error::Unauthorized));
+            assert_eq!(contract.propose_admin_action(AdminAction::SetPaused(true)), Err(Error::Unauthorized));
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            let proposal_id = contract.propose_admin_action(AdminAction::SetPaused(true)).unwrap();
+            assert_eq!(contract.execute_proposal(proposal_id), Err(Error::ThresholdNotMet));
+            assert_eq!(contract.approve_proposal(proposal_id), Err(Error::AlreadyApproved));
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.charlie);
+            contract.approve_proposal(proposal_id).unwrap();
+            contract.execute_proposal(proposal_id).unwrap();
+            assert!(contract.is_paused());
+            assert_eq!(contract.execute_proposal(proposal_id), Err(Error::ProposalAlreadyExecuted));
+        }
+
+        #[ink::test]
+        fn test_council_takeover_waits_for_timelock() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            ink::env::test::set_callee::<ink::env::DefaultEnvironment>(accounts.frank);
+            let mut contract = PaymentRecorder::new();
+            let operation_id = contract.establish_council(Council {
+                members: vec![accounts.bob, accounts.charlie],
+                threshold: 1,
+            }).unwrap();
+
+            // The owner stays in charge, and can still cancel, until the delay passes
+            assert_eq!(contract.execute_operation(operation_id), Err(Error::OperationNotReady));
+            assert_eq!(contract.get_council(), None);
+            assert_eq!(contract.get_owner(), accounts.alice);
+            contract.set_paused(true).unwrap();
+
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(DEFAULT_TIMELOCK_DELAY);
+            contract.execute_operation(operation_id).unwrap();
+            assert_eq!(contract.get_owner(), accounts.frank);
+
+            // A council cannot swap itself out at once either
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            let proposal_id = contract
+                .propose_admin_action(AdminAction::UpdateCouncil(Council {
+                    members: vec![accounts.eve],
+                    threshold: 1,
+                }))
+                .unwrap();
+            contract.execute_proposal(proposal_id).unwrap();
+            assert_eq!(contract.get_council().unwrap().members, vec![accounts.bob, accounts.charlie]);
+            let operation = contract.get_pending_operations(0, 1).pop().unwrap();
+            assert_eq!(contract.execute_operation(operation.id), Err(Error::OperationNotReady));
+
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(operation.ready_at);
+            contract.execute_operation(operation.id).unwrap();
+            assert_eq!(contract.get_council().unwrap().members, vec![accounts.eve]);
         }
 
-        /// Gets the contract owner
-        /// 
-        /// # Returns
-        /// * `AccountId` - The owner's account ID
-        #[ink(message)]
-        pub fn get_owner(&self) -> AccountId {
-            self.owner
+        #[ink::test]
+        fn test_council_proposal_expiry() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let mut contract = PaymentRecorder::new();
+            apply_config(&mut contract, ContractConfig {
+                proposal_lifetime: 1,
+                ..Default::default()
+            });
+            apply_council(&mut contract, Council {
+                members: vec![accounts.bob, accounts.charlie],
+                threshold: 2,
+            });
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            let proposal_id = contract
+                .propose_admin_action(AdminAction::TransferOwnership(accounts.eve))
+                .unwrap();
+
+            ink::env::test::advance_block::<ink::env::DefaultEnvironment>();
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.charlie);
+            assert_eq!(contract.approve_proposal(proposal_id), Err(Error::ProposalExpired));
+            assert_eq!(contract.get_owner(), ink::env::account_id::<ink::env::DefaultEnvironment>());
+        }
+
+        #[ink::test]
+        fn test_roles_gate_privileged_messages() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let mut contract = PaymentRecorder::new();
+            contract.grant_role(Role::Admin, accounts.bob).unwrap();
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.set_paused(true), Err(Error::Unauthorized));
+            contract.grant_role(Role::Pauser, accounts.charlie).unwrap();
+            contract.set_arbiter(accounts.django, true).unwrap();
+            assert_eq!(contract.set_role_admin(Role::Pauser, Role::ConfigManager), Err(Error::Unauthorized));
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.charlie);
+            assert!(contract.has_role(Role::Pauser, accounts.charlie));
+            assert_eq!(contract.update_config(ContractConfig::default()), Err(Error::Unauthorized));
+            assert_eq!(contract.get_all_payments(0, 10), Err(Error::Unauthorized));
+            assert_eq!(contract.grant_role(Role::Pauser, accounts.eve), Err(Error::Unauthorized));
+            contract.set_paused(true).unwrap();
+            contract.renounce_role(Role::Pauser).unwrap();
+            assert_eq!(contract.set_paused(false), Err(Error::Unauthorized));
+        }
+
+        #[ink::test]
+        fn test_role_admin_hierarchy() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let mut contract = PaymentRecorder::new();
+            assert_eq!(contract.get_role_admin(Role::Relayer), Role::Admin);
+            contract.set_role_admin(Role::Relayer, Role::ConfigManager).unwrap();
+            contract.grant_role(Role::ConfigManager, accounts.bob).unwrap();
+            contract.grant_role(Role::Admin, accounts.charlie).unwrap();
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.charlie);
+            assert_eq!(contract.grant_role(Role::Relayer, accounts.eve), Err(Error::Unauthorized));
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            contract.grant_role(Role::Relayer, accounts.eve).unwrap();
+            assert!(contract.has_role(Role::Relayer, accounts.eve));
+            contract.revoke_role(Role::Relayer, accounts.eve).unwrap();
+            assert!(!contract.has_role(Role::Relayer, accounts.eve));
+        }
+
+        #[ink::test]
+        fn test_timelocked_config_update() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let mut contract = PaymentRecorder::new();
+            let config = ContractConfig { max_batch_size: 5, ..Default::default() };
+
+            let operation_id = contract.update_config(config.clone()).unwrap();
+            assert_eq!(contract.get_config(), ContractConfig::default());
+            assert_eq!(contract.get_pending_operations(0, 10).len(), 1);
+            assert_eq!(contract.execute_operation(operation_id), Err(Error::OperationNotReady));
+
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(DEFAULT_TIMELOCK_DELAY);
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            contract.execute_operation(operation_id).unwrap();
+            assert_eq!(contract.get_config(), config);
+            assert!(contract.get_pending_operations(0, 10).is_empty());
+            assert_eq!(contract.execute_operation(operation_id), Err(Error::OperationNotPending));
+        }
+
+        #[ink::test]
+        fn test_cancel_scheduled_operation() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let mut contract = PaymentRecorder::new();
+            let operation_id = contract.set_code_hash(Hash::from([0x01; 32])).unwrap();
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.cancel_operation(operation_id), Err(Error::Unauthorized));
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            contract.cancel_operation(operation_id).unwrap();
+            assert_eq!(
+                contract.get_operation(operation_id).unwrap().status,
+                OperationStatus::Cancelled
+            );
+
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(DEFAULT_TIMELOCK_DELAY);
+            assert_eq!(contract.execute_operation(operation_id), Err(Error::OperationNotPending));
+
+            // The last pending operation takes the place of a cancelled one
+            let first = contract.set_code_hash(Hash::from([0x02; 32])).unwrap();
+            let second = contract.set_code_hash(Hash::from([0x03; 32])).unwrap();
+            let third = contract.set_code_hash(Hash::from([0x04; 32])).unwrap();
+            contract.cancel_operation(first).unwrap();
+            let ids = |operations: Vec<ScheduledOperation>| -> Vec<OperationId> {
+                operations.iter().map(|operation| operation.id).collect()
+            };
+            assert_eq!(ids(contract.get_pending_operations(0, 10)), vec![third, second]);
+            assert_eq!(ids(contract.get_pending_operations(1, 10)), vec![second]);
         }
 
-        /// Transfers ownership of the contract (owner only)
-        /// 
-        /// # Arguments
-        /// * `new_owner` - The new owner's AccountId
-        /// 
-        /// # Returns
-        /// * `Result<()>` - Success or error
-        #[ink(message)]
-        pub fn transfer_ownership(&mut self, new_owner: AccountId) -> Result<()> {
-            if self.env().caller() != self.owner {
-                return Err(Error::Unauthorized);
-            }
-            
-            self.owner = new_owner;
-            Ok(())
+        /// Schedules a council takeover and executes it once the timelock has passed
+        fn apply_council(contract: &mut PaymentRecorder, council: Council) {
+            let operation_id = contract.establish_council(council).unwrap();
+            let operation = contract.get_operation(operation_id).unwrap();
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(operation.ready_at);
+            contract.execute_operation(operation_id).unwrap();
         }
 
-        /// Gets payment statistics for a user
-        /// 
-        /// # Arguments
-        /// * `user` - The user's AccountId
-        /// 
-        /// # Returns
-        /// * `(u32, Balance)` - (number of payments, total amount sent)
-        #[ink(message)]
-        pub fn get_user_stats(&self, user: AccountId) -> (u32, Balance) {
-            let history = self.payment_history.get(&user).unwrap_or_default();
-            let count = history.len() as u32;
-            let total_amount = history.iter().map(|record| record.amount).sum();
-            (count, total_amount)
+        /// Schedules new settings and executes them once the timelock has passed
+        fn apply_config(contract: &mut PaymentRecorder, config: ContractConfig) {
+            let operation_id = contract.update_config(config).unwrap();
+            let operation = contract.get_operation(operation_id).unwrap();
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(operation.ready_at);
+            contract.execute_operation(operation_id).unwrap();
         }
 
-        /// Gets recent payments (last N payments for a user)
-        /// 
-        /// # Arguments
-        /// * `user` - The user's AccountId  
-        /// * `limit` - Maximum number of recent payments to return
-        /// 
-        /// # Returns
-        /// * `Vec<PaymentRecord>` - Recent payment records
-        #[ink(message)]
-        pub fn get_recent_payments(&self, user: AccountId, limit: u32) -> Vec<PaymentRecord> {
-            let history = self.payment_history.get(&user).unwrap_or_default();
-            let start_index = if history.len() > limit as usize {
-                history.len() - limit as usize
-            } else {
-                0
+        #[ink::test]
+        fn test_recipient_allowlist_and_blocklist() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let mut contract = PaymentRecorder::new();
+            let pay = |contract: &mut PaymentRecorder, recipient| {
+                contract.record_payment(
+                    recipient,
+                    10,
+                    String::from("Send 10 DOT"),
+                    String::from("DOT"),
+                    String::from("polkadot"),
+                    90,
+                    None,
+                )
             };
-            
-            history[start_index..].to_vec()
-        }
-    }
 
-    /// Unit tests for the contract
-    #[cfg(test)]
-    mod tests {
-        use super::*;
+            contract.add_recipient(RecipientList::Blocklist, accounts.charlie).unwrap();
+            assert_eq!(pay(&mut contract, accounts.charlie), Err(Error::RecipientBlocked));
+            assert!(pay(&mut contract, accounts.bob).is_ok());
 
-        /// Helper function to create a test payment record
-        fn create_test_record() -> (AccountId, Balance, String, String, String, u8) {
-            let recipient = AccountId::from([0x01; 32]);
-            let amount = 1000000000000; // 1 DOT in Planck
-            let voice_command = String::from("Send 1 DOT to Alice");
-            let currency = String::from("DOT");
-            let network = String::from("polkadot");
-            let confidence = 95;
-            
-            (recipient, amount, voice_command, currency, network, confidence)
+            contract.set_allowlist_only(true).unwrap();
+            assert_eq!(pay(&mut contract, accounts.bob), Err(Error::RecipientNotAllowed));
+            contract.add_recipient(RecipientList::Allowlist, accounts.bob).unwrap();
+            assert!(pay(&mut contract, accounts.bob).is_ok());
+            assert_eq!(
+                contract.get_recipients(accounts.alice, RecipientList::Allowlist, 0, 10),
+                vec![accounts.bob]
+            );
+
+            contract.remove_recipient(RecipientList::Allowlist, accounts.bob).unwrap();
+            assert_eq!(pay(&mut contract, accounts.bob), Err(Error::RecipientNotAllowed));
         }
 
         #[ink::test]
-        fn test_new_contract() {
-            let contract = PaymentRecorder::new();
-            assert_eq!(contract.get_total_payments(), 0);
+        fn test_global_deny_list() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let mut contract = PaymentRecorder::new();
+            contract.set_denied(accounts.bob, true).unwrap();
+            contract.set_denied(accounts.charlie, true).unwrap();
+            contract.set_denied(accounts.django, true).unwrap();
+
+            let result = contract.record_split_payment(
+                vec![(accounts.eve, 10), (accounts.charlie, 10)],
+                String::from("Split 20 DOT"),
+                String::from("DOT"),
+                String::from("polkadot"),
+                90,
+                None,
+            );
+            assert_eq!(result, Err(Error::RecipientBlocked));
+
+            contract.set_denied(accounts.bob, false).unwrap();
+            assert!(!contract.is_denied(accounts.bob));
+            assert_eq!(contract.get_deny_list(0, 10), vec![accounts.django, accounts.charlie]);
+            assert_eq!(contract.get_deny_list(1, 10), vec![accounts.charlie]);
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.set_denied(accounts.eve, true), Err(Error::Unauthorized));
         }
 
         #[ink::test]
-        fn test_record_payment_success() {
+        fn test_low_confidence_payment_awaits_confirmation() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
             let mut contract = PaymentRecorder::new();
-            let (recipient, amount, voice_command, currency, network, confidence) = create_test_record();
-            
-            let result = contract.record_payment(
-                recipient,
-                amount,
-                voice_command.clone(),
-                currency,
-                network,
-                confidence,
+            contract.set_confidence_policy(Some(ConfidencePolicy {
+                min_confidence: 80,
+                tiers: vec![ConfidenceTier { min_amount: 1_000, min_confidence: 95 }],
+            })).unwrap();
+            let pay = |contract: &mut PaymentRecorder, amount, confidence| {
+                contract.record_payment(
+                    accounts.bob,
+                    amount,
+                    String::from("Send DOT to Bob"),
+                    String::from("DOT"),
+                    String::from("polkadot"),
+                    confidence,
+                    None,
+                ).unwrap()
+            };
+
+            let confident = pay(&mut contract, 100, 85);
+            assert_eq!(contract.get_payment(confident).unwrap().status, PaymentStatus::Completed);
+
+            let large = pay(&mut contract, 1_000, 90);
+            assert_eq!(contract.get_payment(large).unwrap().status, PaymentStatus::PendingConfirmation);
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(contract.confirm_pending(large), Err(Error::Unauthorized));
+            assert_eq!(
+                contract.record_refund(accounts.alice, 1, 10, String::new()),
+                Err(Error::PaymentNotConfirmed)
             );
-            
-            assert!(result.is_ok());
-            assert_eq!(contract.get_total_payments(), 1);
-            
-            let history = contract.get_my_payment_history();
-            assert_eq!(history.len(), 1);
-            assert_eq!(history[0].recipient, recipient);
-            assert_eq!(history[0].amount, amount);
-            assert_eq!(history[0].voice_command, voice_command);
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            contract.confirm_pending(large).unwrap();
+            assert_eq!(contract.get_payment(large).unwrap().status, PaymentStatus::Completed);
+            assert_eq!(contract.confirm_pending(large), Err(Error::PaymentNotPending));
+
+            let unconfirmed = pay(&mut contract, 100, 50);
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(
+                contract.get_confirmation_deadline(unconfirmed).unwrap() + 1,
+            );
+            assert_eq!(contract.confirm_pending(unconfirmed), Err(Error::ConfirmationExpired));
         }
 
         #[ink::test]
-        fn test_record_payment_invalid_amount() {
+        fn test_low_confidence_rejected_when_funds_move() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
             let mut contract = PaymentRecorder::new();
-            let (recipient, _, voice_command, currency, network, confidence) = create_test_record();
-            
-            let result = contract.record_payment(
-                recipient,
-                0, // Invalid amount
-                voice_command,
-                currency,
-                network,
-                confidence,
+            assert_eq!(
+                contract.set_confidence_policy(Some(ConfidencePolicy { min_confidence: 101, tiers: vec![] })),
+                Err(Error::InvalidConfidencePolicy)
             );
-            
-            assert_eq!(result, Err(Error::InvalidAmount));
+            contract.set_confidence_policy(Some(ConfidencePolicy { min_confidence: 90, tiers: vec![] })).unwrap();
+
+            let result = ink::env::pay_with_call!(
+                contract.record_split_payment(
+                    vec![(accounts.bob, 10), (accounts.charlie, 10)],
+                    String::from("Split 20 DOT"),
+                    String::from("DOT"),
+                    String::from("polkadot"),
+                    70,
+                    None
+                ),
+                20
+            );
+            assert_eq!(result, Err(Error::ConfidenceTooLow));
+
+            // Without value the same split and an invoice are only recorded
+            ink::env::test::set_value_transferred::<ink::env::DefaultEnvironment>(0);
+            let split_id = contract.record_split_payment(
+                vec![(accounts.bob, 10), (accounts.charlie, 10)],
+                String::from("Split 20 DOT"),
+                String::from("DOT"),
+                String::from("polkadot"),
+                70,
+                None,
+            ).unwrap();
+            for payment_id in contract.get_split_payment(split_id).unwrap().payment_ids {
+                assert_eq!(contract.get_payment(payment_id).unwrap().status, PaymentStatus::PendingConfirmation);
+            }
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            let expires_at = 2 * DEFAULT_CONFIRMATION_WINDOW;
+            let invoice_id = contract.create_invoice(
+                5_000,
+                String::from("DOT"),
+                String::from("Coffee beans"),
+                expires_at,
+            ).unwrap();
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            let pay = |contract: &mut PaymentRecorder| {
+                contract.pay_invoice(
+                    invoice_id,
+                    String::from("Pay invoice 0"),
+                    String::from("polkadot"),
+                    70,
+                    None,
+                )
+            };
+            let payment_id = pay(&mut contract).unwrap();
+            assert_eq!(contract.get_payment(payment_id).unwrap().status, PaymentStatus::PendingConfirmation);
+            let invoice = contract.get_invoice(invoice_id).unwrap();
+            assert_eq!(invoice.status, InvoiceStatus::AwaitingConfirmation);
+            assert_eq!(invoice.payment_id, Some(payment_id));
+            assert_eq!(pay(&mut contract), Err(Error::InvoiceNotOpen));
+
+            // An expired payment reopens the invoice, and a confirmed one pays it
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(
+                contract.get_confirmation_deadline(payment_id).unwrap() + 1,
+            );
+            contract.expire_pending(payment_id).unwrap();
+            let invoice = contract.get_invoice(invoice_id).unwrap();
+            assert_eq!(invoice.status, InvoiceStatus::Open);
+            assert_eq!((invoice.payer, invoice.payment_id), (None, None));
+
+            let payment_id = pay(&mut contract).unwrap();
+            contract.confirm_pending(payment_id).unwrap();
+            let invoice = contract.get_invoice(invoice_id).unwrap();
+            assert_eq!(invoice.status, InvoiceStatus::Paid);
+            assert_eq!(invoice.payment_id, Some(payment_id));
         }
 
         #[ink::test]
-        fn test_record_payment_invalid_voice_command() {
+        fn test_pending_payment_charged_on_confirmation() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
             let mut contract = PaymentRecorder::new();
-            let (recipient, amount, _, currency, network, confidence) = create_test_record();
-            
-            let result = contract.record_payment(
-                recipient,
-                amount,
-                String::new(), // Empty voice command
-                currency,
-                network,
-                confidence,
+            contract.set_spending_limit(String::from("DOT"), LimitPeriod::Day, Some(100)).unwrap();
+            contract.set_confidence_policy(Some(ConfidencePolicy { min_confidence: 90, tiers: vec![] })).unwrap();
+            let pay = |contract: &mut PaymentRecorder, amount, confidence| {
+                contract.record_payment(
+                    accounts.bob,
+                    amount,
+                    String::from("Send DOT to Bob"),
+                    String::from("DOT"),
+                    String::from("polkadot"),
+                    confidence,
+                    None,
+                ).unwrap()
+            };
+            let remaining = |contract: &PaymentRecorder| {
+                contract.get_remaining_allowance(accounts.alice, String::from("DOT"), LimitPeriod::Day)
+            };
+
+            let pending = pay(&mut contract, 80, 50);
+            assert_eq!(remaining(&contract), Some(100));
+            assert_eq!(contract.get_user_stats(accounts.alice), (0, 0));
+
+            pay(&mut contract, 60, 95);
+            assert_eq!(remaining(&contract), Some(40));
+            assert_eq!(
+                contract.confirm_pending(pending),
+                Err(Error::SpendingLimitExceeded { remaining: 40 })
             );
-            
-            assert_eq!(result, Err(Error::InvalidVoiceCommand));
+            assert_eq!(contract.get_payment(pending).unwrap().status, PaymentStatus::PendingConfirmation);
+
+            let small = pay(&mut contract, 30, 50);
+            contract.confirm_pending(small).unwrap();
+            assert_eq!(remaining(&contract), Some(10));
+            assert_eq!(contract.get_user_stats(accounts.alice), (2, 90));
         }
 
         #[ink::test]
-        fn test_record_payment_invalid_confidence() {
+        fn test_expire_pending() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
             let mut contract = PaymentRecorder::new();
-            let (recipient, amount, voice_command, currency, network, _) = create_test_record();
-            
-            let result = contract.record_payment(
-                recipient,
-                amount,
-                voice_command,
-                currency,
-                network,
-                101, // Invalid confidence > 100
+            contract.set_confidence_policy(Some(ConfidencePolicy { min_confidence: 90, tiers: vec![] })).unwrap();
+            let payment_id = contract.record_payment(
+                accounts.bob,
+                100,
+                String::from("Send DOT to Bob"),
+                String::from("DOT"),
+                String::from("polkadot"),
+                50,
+                None,
+            ).unwrap();
+
+            assert_eq!(contract.expire_pending(payment_id), Err(Error::ConfirmationWindowOpen));
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(
+                contract.get_confirmation_deadline(payment_id).unwrap() + 1,
+            );
+
+            // Anyone may expire it once the window has passed
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.charlie);
+            contract.expire_pending(payment_id).unwrap();
+            assert_eq!(contract.get_payment(payment_id).unwrap().status, PaymentStatus::Expired);
+            assert_eq!(contract.get_confirmation_deadline(payment_id), None);
+            assert_eq!(contract.expire_pending(payment_id), Err(Error::PaymentNotPending));
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.alice);
+            assert_eq!(contract.confirm_pending(payment_id), Err(Error::PaymentNotPending));
+            assert_eq!(contract.get_user_stats(accounts.alice), (0, 0));
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(
+                contract.record_refund(accounts.alice, 0, 10, String::new()),
+                Err(Error::PaymentNotConfirmed)
             );
-            
-            assert_eq!(result, Err(Error::InvalidConfidence));
         }
 
         #[ink::test]
-        fn test_user_stats() {
+        fn test_command_locale_stored_with_payments() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
             let mut contract = PaymentRecorder::new();
-            let (recipient, amount, voice_command, currency, network, confidence) = create_test_record();
-            
-            // Record multiple payments
-            for i in 0..3 {
-                let mut cmd = voice_command.clone();
-                cmd.push_str(&i.to_string());
+            let pay = |contract: &mut PaymentRecorder, voice_command: &str, locale: Option<&str>| {
                 contract.record_payment(
-                    recipient,
-                    amount,
-                    cmd,
-                    currency.clone(),
-                    network.clone(),
-                    confidence,
-                ).unwrap();
+                    accounts.bob,
+                    100,
+                    String::from(voice_command),
+                    String::from("DOT"),
+                    String::from("polkadot"),
+                    90,
+                    locale.map(String::from),
+                )
+            };
+
+            let unknown = pay(&mut contract, "Send 1 DOT to Bob", None).unwrap();
+            assert_eq!(contract.get_payment(unknown).unwrap().locale, None);
+
+            for invalid in ["", "pt_BR", "es-", "zh-Hans-CN-toolongsubtag"] {
+                assert_eq!(
+                    contract.set_command_locale(Some(String::from(invalid))),
+                    Err(Error::InvalidLocale)
+                );
+                assert_eq!(pay(&mut contract, "Send 1 DOT to Bob", Some(invalid)), Err(Error::InvalidLocale));
+            }
+
+            // The user's preferred locale does not override the command's own
+            contract.set_command_locale(Some(String::from("es-ES"))).unwrap();
+            assert_eq!(contract.get_command_locale(accounts.alice), Some(String::from("es-ES")));
+            assert_eq!(contract.get_command_locale(accounts.bob), None);
+            let english = pay(&mut contract, "Send 1 DOT to Bob", Some("en")).unwrap();
+            let spanish = pay(&mut contract, "Envía 1 DOT a Bob", Some("es-ES")).unwrap();
+            assert_eq!(contract.get_payment(english).unwrap().locale, Some(String::from("en")));
+            assert_eq!(contract.get_payment(spanish).unwrap().locale, Some(String::from("es-ES")));
+
+            contract.set_command_locale(None).unwrap();
+            assert_eq!(contract.get_payment(spanish).unwrap().locale, Some(String::from("es-ES")));
+
+            let split_id = contract.record_split_payment(
+                vec![(accounts.bob, 1), (accounts.charlie, 1)],
+                String::from("Divide 2 DOT entre Bob y Charlie"),
+                String::from("DOT"),
+                String::from("polkadot"),
+                90,
+                Some(String::from("es")),
+            ).unwrap();
+            for payment_id in contract.get_split_payment(split_id).unwrap().payment_ids {
+                assert_eq!(contract.get_payment(payment_id).unwrap().locale, Some(String::from("es")));
+            }
+        }
+
+        fn batch_input(recipient: AccountId, amount: Balance) -> PaymentInput {
+            PaymentInput {
+                recipient,
+                amount,
+                voice_command: String::from("Send DOT to Bob"),
+                currency: String::from("DOT"),
+                network: String::from("polkadot"),
+                confidence: 95,
+                locale: None,
             }
-            
-            let caller = contract.env().caller();
-            let (count, total_amount) = contract.get_user_stats(caller);
-            
-            assert_eq!(count, 3);
-            assert_eq!(total_amount, amount * 3);
         }
 
         #[ink::test]
-        fn test_recent_payments() {
+        fn test_record_payments_batch_best_effort() {
             let mut contract = PaymentRecorder::new();
-            let (recipient, amount, voice_command, currency, network, confidence) = create_test_record();
-            
-            // Record 5 payments
-            for i in 0..5 {
-                let mut cmd = voice_command.clone();
-                cmd.push_str(&i.to_string());
-                contract.record_payment(
-                    recipient,
-                    amount + i as u128,
-                    cmd,
-                    currency.clone(),
-                    network.clone(),
-                    confidence,
-                ).unwrap();
-            }
-            
-            let caller = contract.env().caller();
-            let recent = contract.get_recent_payments(caller, 3);
-            
-            assert_eq!(recent.len(), 3);
-            // Should return the last 3 payments
-            assert_eq!(recent[0].amount, amount + 2);
-            assert_eq!(recent[1].amount, amount + 3);
-            assert_eq!(recent[2].amount, amount + 4);
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+
+            let results = contract.record_payments_batch(
+                vec![
+                    batch_input(accounts.bob, 10),
+                    batch_input(accounts.bob, 0),
+                    batch_input(accounts.charlie, 30),
+                ],
+                false,
+            ).unwrap();
+
+            assert_eq!(results, vec![Ok(0), Err(Error::InvalidAmount), Ok(1)]);
+            assert_eq!(contract.get_total_payments(), 2);
+            assert_eq!(contract.get_payment(1).unwrap().recipient, accounts.charlie);
         }
 
         #[ink::test]
-        fn test_transfer_ownership() {
+        fn test_record_payments_batch_atomic() {
             let mut contract = PaymentRecorder::new();
-            let new_owner = AccountId::from([0x02; 32]);
-            
-            let result = contract.transfer_ownership(new_owner);
-            assert!(result.is_ok());
-            assert_eq!(contract.get_owner(), new_owner);
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+
+            let result = contract.record_payments_batch(
+                vec![batch_input(accounts.bob, 10), batch_input(accounts.bob, 0)],
+                true,
+            );
+            assert_eq!(result, Err(Error::BatchItemFailed { index: 1 }));
+            assert_eq!(contract.get_total_payments(), 0);
+
+            let results = contract.record_payments_batch(
+                vec![batch_input(accounts.bob, 10), batch_input(accounts.charlie, 20)],
+                true,
+            ).unwrap();
+            assert_eq!(results, vec![Ok(0), Ok(1)]);
+        }
+
+        #[ink::test]
+        fn test_record_payments_batch_atomic_spending_limit() {
+            let mut contract = PaymentRecorder::new();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            contract.set_spending_limit(String::from("DOT"), LimitPeriod::Day, Some(100)).unwrap();
+
+            // Each item fits the limit on its own; the last one crosses it
+            let result = contract.record_payments_batch(
+                vec![
+                    batch_input(accounts.bob, 40),
+                    batch_input(accounts.charlie, 40),
+                    batch_input(accounts.bob, 40),
+                ],
+                true,
+            );
+            assert_eq!(result, Err(Error::BatchItemFailed { index: 2 }));
+            assert_eq!(contract.get_total_payments(), 0);
+            assert!(contract.get_my_payment_history().is_empty());
+
+            contract.set_paused(true).unwrap();
+            let result = contract.record_payments_batch(vec![batch_input(accounts.bob, 10)], true);
+            assert_eq!(result, Err(Error::BatchItemFailed { index: 0 }));
+            assert_eq!(contract.get_total_payments(), 0);
+        }
+
+        #[ink::test]
+        fn test_record_payments_batch_size_limit() {
+            let mut contract = PaymentRecorder::new();
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+
+            assert_eq!(
+                contract.update_config(ContractConfig { max_batch_size: 0, ..Default::default() }),
+                Err(Error::InvalidConfig)
+            );
+            apply_config(&mut contract, ContractConfig { max_batch_size: 2, ..Default::default() });
+
+            let result = contract.record_payments_batch(vec![batch_input(accounts.bob, 10); 3], false);
+            assert_eq!(result, Err(Error::BatchTooLarge));
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(
+                contract.update_config(ContractConfig { max_batch_size: 50, ..Default::default() }),
+                Err(Error::Unauthorized)
+            );
         }
     }
 }
+
+#[cfg(feature = "assets-extension")]
+mod assets_extension;
+
+use ink::env::{DefaultEnvironment, Environment};
+
+/// Identifier of an asset in `pallet-assets` (e.g. 1984 for USDT on Asset Hub)
+pub type AssetId = u32;
+
+/// Contract environment: the default Substrate types, plus the `pallet-assets`
+/// chain extension when built with the `assets-extension` feature
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[ink::scale_derive(TypeInfo)]
+pub enum EchoPayEnvironment {}
+
+impl Environment for EchoPayEnvironment {
+    const MAX_EVENT_TOPICS: usize = <DefaultEnvironment as Environment>::MAX_EVENT_TOPICS;
+
+    type AccountId = <DefaultEnvironment as Environment>::AccountId;
+    type Balance = <DefaultEnvironment as Environment>::Balance;
+    type Hash = <DefaultEnvironment as Environment>::Hash;
+    type Timestamp = <DefaultEnvironment as Environment>::Timestamp;
+    type BlockNumber = <DefaultEnvironment as Environment>::BlockNumber;
+
+    #[cfg(feature = "assets-extension")]
+    type ChainExtension = assets_extension::PalletAssets;
+    #[cfg(not(feature = "assets-extension"))]
+    type ChainExtension = ink::env::NoChainExtension;
+}
@@ -0,0 +1,445 @@
+//! Talking to a deployed `payment_recorder` through `pallet-contracts`
+//!
+//! Calls are built and their results decoded with `echopay-client`, so no
+//! contract metadata is needed at run time. Every call is dry-run first
+//! through the `ContractsApi` runtime API; that yields the return value, the
+//! contract's own errors and the gas to submit the real extrinsic with. What
+//! a submitted call did is read from the events of its finalized block, as
+//! the state may have changed since the dry run.
+
+use anyhow::{anyhow, bail, Context, Result};
+use echopay_client::{constructors, types::ContractError, Constructor, Event, Message};
+use scale::{Decode, Encode};
+use subxt::{
+    dynamic::Value,
+    ext::scale_value::Composite,
+    utils::{AccountId32, H256},
+    OnlineClient, PolkadotConfig,
+};
+use subxt_signer::sr25519::Keypair;
+
+/// Flag set in `ExecReturnValue::flags` when the contract reverted
+const REVERT_FLAG: u32 = 1;
+
+/// Two-dimensional weight used for gas limits
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Encode, Decode)]
+pub struct Weight {
+    #[codec(compact)]
+    pub ref_time: u64,
+    #[codec(compact)]
+    pub proof_size: u64,
+}
+
+impl Weight {
+    fn to_value(self) -> Value {
+        Value::named_composite([
+            ("ref_time", Value::u128(self.ref_time.into())),
+            ("proof_size", Value::u128(self.proof_size.into())),
+        ])
+    }
+}
+
+/// Storage deposit charged or refunded by a dry run
+#[derive(Debug, Clone, Copy, Decode)]
+enum StorageDeposit {
+    Refund(#[allow(dead_code)] u128),
+    Charge(#[allow(dead_code)] u128),
+}
+
+/// Runtime error of a dry run, before the contract returned
+#[derive(Debug, Clone, Copy)]
+enum DispatchError {
+    /// An error of a specific pallet, resolved through the metadata
+    Module { pallet: u8, error: u8 },
+    /// Any other variant of `sp_runtime::DispatchError`
+    Other(u8),
+}
+
+impl Decode for DispatchError {
+    fn decode<I: scale::Input>(input: &mut I) -> Result<Self, scale::Error> {
+        match input.read_byte()? {
+            3 => {
+                let pallet = input.read_byte()?;
+                let error = <[u8; 4]>::decode(input)?;
+                Ok(Self::Module {
+                    pallet,
+                    error: error[0],
+                })
+            }
+            variant => Ok(Self::Other(variant)),
+        }
+    }
+}
+
+/// Output of a contract call or instantiation
+#[derive(Debug, Clone, Decode)]
+struct ExecReturnValue {
+    flags: u32,
+    data: Vec<u8>,
+}
+
+/// Output of a successful instantiation dry run
+#[derive(Debug, Clone, Decode)]
+struct InstantiateReturnValue {
+    result: ExecReturnValue,
+    account_id: AccountId32,
+}
+
+/// Leading fields of `pallet_contracts_primitives::ContractResult`
+///
+/// Fields after `result` vary between node versions and are not needed.
+#[derive(Debug, Clone, Decode)]
+struct ContractResult<R> {
+    #[allow(dead_code)]
+    gas_consumed: Weight,
+    gas_required: Weight,
+    #[allow(dead_code)]
+    storage_deposit: StorageDeposit,
+    debug_message: Vec<u8>,
+    result: Result<R, DispatchError>,
+}
+
+/// Code to instantiate a contract from
+#[derive(Encode)]
+enum Code {
+    Upload(Vec<u8>),
+}
+
+/// A signer connected to a node running `pallet-contracts`
+pub struct RecorderClient {
+    api: OnlineClient<PolkadotConfig>,
+    signer: Keypair,
+}
+
+impl RecorderClient {
+    /// Connects to the node at `url`
+    pub async fn connect(url: &str, signer: Keypair) -> Result<Self> {
+        let api = OnlineClient::<PolkadotConfig>::from_url(url)
+            .await
+            .with_context(|| format!("failed to connect to {url}"))?;
+        Ok(Self { api, signer })
+    }
+
+    /// Account of the signer
+    pub fn account(&self) -> AccountId32 {
+        self.signer.public_key().to_account_id()
+    }
+
+    /// Uploads and instantiates the contract with its `new` constructor
+    ///
+    /// # Returns
+    /// * `Result<AccountId32>` - Address of the new contract
+    pub async fn deploy(&self, code: Vec<u8>, salt: Vec<u8>) -> Result<AccountId32> {
+        let data = constructors::new().call_data();
+        let args = (
+            self.account(),
+            0u128,
+            None::<Weight>,
+            None::<u128>,
+            Code::Upload(code.clone()),
+            data.clone(),
+            salt.clone(),
+        );
+        let dry_run: ContractResult<InstantiateReturnValue> = self
+            .dry_run("ContractsApi_instantiate", args.encode())
+            .await?;
+        let outcome = self.check(dry_run)?;
+        if outcome.value.result.flags & REVERT_FLAG != 0 {
+            bail!("the constructor reverted");
+        }
+
+        let tx = subxt::dynamic::tx(
+            "Contracts",
+            "instantiate_with_code",
+            Composite::named([
+                ("value", Value::u128(0)),
+                ("gas_limit", outcome.gas_required.to_value()),
+                ("storage_deposit_limit", Value::unnamed_variant("None", [])),
+                ("code", Value::from_bytes(code)),
+                ("data", Value::from_bytes(data)),
+                ("salt", Value::from_bytes(salt)),
+            ]),
+        );
+        let events = self
+            .api
+            .tx()
+            .sign_and_submit_then_watch_default(&tx, &self.signer)
+            .await?
+            .wait_for_finalized_success()
+            .await?;
+
+        for event in events.iter() {
+            let event = event?;
+            if event.pallet_name() == "Contracts" && event.variant_name() == "Instantiated" {
+                let (_deployer, contract) =
+                    <(AccountId32, AccountId32)>::decode(&mut event.field_bytes())?;
+                return Ok(contract);
+            }
+        }
+        Ok(outcome.value.account_id)
+    }
+
+    /// Reads a message's return value without submitting a transaction
+    pub async fn query<M: Message>(
+        &self,
+        contract: &AccountId32,
+        message: &M,
+    ) -> Result<M::Output> {
+        let output = self.dry_run_call(contract, message.call_data()).await?;
+        Ok(M::decode_output(&output.value.data)?)
+    }
+
+    /// Submits a message that returns `Result<T, Error>` and waits for finalization
+    ///
+    /// The call is dry-run first, so contract errors are reported without
+    /// spending fees. A call that reverts on chain is still a successful
+    /// extrinsic, so it fails here unless `pallet-contracts` reports the
+    /// contract as called.
+    ///
+    /// # Returns
+    /// * `Result<Emitted>` - The events the call emitted
+    pub async fn call<T, M>(&self, contract: &AccountId32, message: &M) -> Result<Emitted>
+    where
+        M: Message<Output = Result<T, ContractError>>,
+    {
+        let data = message.call_data();
+        let output = self.dry_run_call(contract, data.clone()).await?;
+        M::decode_output(&output.value.data)??;
+
+        let tx = subxt::dynamic::tx(
+            "Contracts",
+            "call",
+            Composite::named([
+                (
+                    "dest",
+                    Value::unnamed_variant("Id", [Value::from_bytes(contract.0)]),
+                ),
+                ("value", Value::u128(0)),
+                ("gas_limit", output.gas_required.to_value()),
+                ("storage_deposit_limit", Value::unnamed_variant("None", [])),
+                ("data", Value::from_bytes(data)),
+            ]),
+        );
+        let events = self
+            .api
+            .tx()
+            .sign_and_submit_then_watch_default(&tx, &self.signer)
+            .await?
+            .wait_for_finalized_success()
+            .await?;
+
+        let mut called = false;
+        let mut emitted = Vec::new();
+        for event in events.iter() {
+            let event = event?;
+            if event.pallet_name() != "Contracts" {
+                continue;
+            }
+            match event.variant_name() {
+                // `contract` is the last field, whatever type the caller has
+                "Called" => called |= event.field_bytes().ends_with(&contract.0),
+                "ContractEmitted" => {
+                    let (emitter, data) =
+                        <(AccountId32, Vec<u8>)>::decode(&mut event.field_bytes())
+                            .context("malformed Contracts::ContractEmitted event")?;
+                    if emitter == *contract {
+                        emitted.push((event.topics().to_vec(), data));
+                    }
+                }
+                _ => {}
+            }
+        }
+        if !called {
+            bail!("{} reverted on chain; nothing was changed", M::LABEL);
+        }
+
+        Ok(Emitted(emitted))
+    }
+
+    /// Dry-runs a message as the signer
+    async fn dry_run_call(
+        &self,
+        contract: &AccountId32,
+        data: Vec<u8>,
+    ) -> Result<DryRun<ExecReturnValue>> {
+        let args = (
+            self.account(),
+            contract.clone(),
+            0u128,
+            None::<Weight>,
+            None::<u128>,
+            data,
+        );
+        let dry_run: ContractResult<ExecReturnValue> =
+            self.dry_run("ContractsApi_call", args.encode()).await?;
+        self.check(dry_run)
+    }
+
+    /// Calls a `ContractsApi` runtime API function
+    async fn dry_run<R: Decode>(&self, function: &str, args: Vec<u8>) -> Result<ContractResult<R>> {
+        let bytes = self
+            .api
+            .rpc()
+            .state_call_raw(function, Some(&args), None)
+            .await?;
+        ContractResult::decode(&mut &bytes[..])
+            .with_context(|| format!("malformed {function} result"))
+    }
+
+    /// Turns a runtime error of a dry run into a readable error
+    fn check<R>(&self, dry_run: ContractResult<R>) -> Result<DryRun<R>> {
+        match dry_run.result {
+            Ok(value) => Ok(DryRun {
+                value,
+                gas_required: dry_run.gas_required,
+            }),
+            Err(error) => {
+                let debug_message = String::from_utf8_lossy(&dry_run.debug_message);
+                Err(anyhow!(
+                    "{}{}",
+                    self.describe(error),
+                    if debug_message.is_empty() {
+                        String::new()
+                    } else {
+                        format!(": {debug_message}")
+                    }
+                ))
+            }
+        }
+    }
+
+    /// Names a dispatch error using the node's metadata
+    fn describe(&self, error: DispatchError) -> String {
+        match error {
+            DispatchError::Module { pallet, error } => {
+                let metadata = self.api.metadata();
+                metadata
+                    .pallet_by_index(pallet)
+                    .and_then(|pallet| {
+                        pallet
+                            .error_variant_by_index(error)
+                            .map(|variant| format!("{}::{}", pallet.name(), variant.name))
+                    })
+                    .unwrap_or_else(|| format!("module error {pallet}:{error}"))
+            }
+            DispatchError::Other(variant) => format!("dispatch error (variant {variant})"),
+        }
+    }
+}
+
+/// Topics and data of the events a finalized call emitted, in order
+pub struct Emitted(Vec<(Vec<H256>, Vec<u8>)>);
+
+impl Emitted {
+    /// The first event of type `E` the call emitted
+    pub fn event<E: Event>(&self) -> Result<E> {
+        let event = self
+            .0
+            .iter()
+            .find_map(|(topics, data)| E::decode_emitted(topics, data))
+            .with_context(|| format!("the contract emitted no {} event", E::LABEL))?;
+        Ok(event?)
+    }
+}
+
+/// Successful dry run with the gas the real call needs
+struct DryRun<R> {
+    value: R,
+    gas_required: Weight,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use echopay_client::messages;
+
+    #[test]
+    fn finds_emitted_events() {
+        use echopay_client::events::{OperationScheduled, PaymentRecorded};
+
+        let data = (
+            4u64,
+            AccountId32([1; 32]),
+            AccountId32([2; 32]),
+            500u128,
+            "DOT",
+            "Send 5 DOT to Bob",
+            None::<String>,
+            0u64,
+        )
+            .encode();
+        let emitted = Emitted(vec![
+            (vec![H256([0; 32])], vec![1, 2, 3]),
+            (vec![H256(PaymentRecorded::SIGNATURE_TOPIC)], data),
+        ]);
+        assert_eq!(emitted.event::<PaymentRecorded>().unwrap().payment_id, 4);
+
+        let error = emitted.event::<OperationScheduled>().unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "the contract emitted no OperationScheduled event"
+        );
+    }
+
+    #[test]
+    fn decodes_dry_run_results() {
+        let bytes = (
+            Weight {
+                ref_time: 1,
+                proof_size: 2,
+            },
+            Weight {
+                ref_time: 3,
+                proof_size: 4,
+            },
+            1u8,
+            5u128,
+            Vec::<u8>::new(),
+            0u8,
+            REVERT_FLAG,
+            vec![0u8, 1, 0],
+        )
+            .encode();
+        let result = ContractResult::<ExecReturnValue>::decode(&mut &bytes[..]).unwrap();
+        assert_eq!(
+            result.gas_required,
+            Weight {
+                ref_time: 3,
+                proof_size: 4
+            }
+        );
+        let output = result.result.unwrap();
+        assert_eq!(output.flags, REVERT_FLAG);
+        assert_eq!(
+            messages::RecordPayment::decode_output(&output.data).unwrap(),
+            Err(ContractError::Unauthorized)
+        );
+
+        let bytes = (
+            Weight {
+                ref_time: 0,
+                proof_size: 0,
+            },
+            Weight {
+                ref_time: 0,
+                proof_size: 0,
+            },
+            0u8,
+            0u128,
+            Vec::<u8>::new(),
+            1u8,
+            3u8,
+            8u8,
+            [11u8, 0, 0, 0],
+        )
+            .encode();
+        let result = ContractResult::<ExecReturnValue>::decode(&mut &bytes[..]).unwrap();
+        assert!(matches!(
+            result.result,
+            Err(DispatchError::Module {
+                pallet: 8,
+                error: 11
+            })
+        ));
+    }
+}
@@ -0,0 +1,442 @@
+//! `echopay` — command-line client for the EchoPay `payment_recorder` contract
+//!
+//! Deploys the contract to a node running `pallet-contracts` (such as a local
+//! `substrate-contracts-node`), records payments, manages ownership and reads
+//! payment history. Results print as tables, or as JSON with `--output json`.
+
+mod contract;
+mod output;
+
+use std::{path::PathBuf, str::FromStr};
+
+use anyhow::{Context, Result};
+use clap::{Parser, Subcommand};
+use serde::Serialize;
+use subxt::utils::AccountId32;
+use subxt_signer::{sr25519::Keypair, SecretUri};
+
+use contract::RecorderClient;
+use echopay_client::{
+    events, messages,
+    types::{PaymentId, PaymentRecord, PaymentStatus},
+};
+use echopay_export::{Format, Statement, Status};
+use output::{print_payments, print_value, OutputFormat};
+
+#[derive(Debug, Parser)]
+#[command(name = "echopay", version, about)]
+struct Cli {
+    /// WebSocket endpoint of the node
+    #[arg(
+        long,
+        global = true,
+        env = "ECHOPAY_URL",
+        default_value = "ws://127.0.0.1:9944"
+    )]
+    url: String,
+
+    /// Secret URI of the signing account, e.g. `//Alice` or a mnemonic
+    #[arg(long, global = true, env = "ECHOPAY_SURI", default_value = "//Alice")]
+    suri: String,
+
+    /// How results are printed
+    #[arg(long, global = true, value_enum, default_value_t = OutputFormat::Table)]
+    output: OutputFormat,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Uploads and instantiates the contract
+    Deploy {
+        /// Path to the contract's `.wasm` file built by `cargo contract build`
+        #[arg(long)]
+        wasm: PathBuf,
+        /// Hex-encoded salt, to deploy the same code more than once
+        #[arg(long)]
+        salt: Option<String>,
+    },
+    /// Records a voice payment sent by the signer
+    RecordPayment {
+        #[command(flatten)]
+        contract: ContractArg,
+        /// Recipient's SS58 address
+        #[arg(long)]
+        recipient: AccountId32,
+        /// Amount in the currency's smallest unit
+        #[arg(long)]
+        amount: u128,
+        /// The voice command as transcribed
+        #[arg(long = "voice-command")]
+        voice_command: String,
+        /// Currency code, e.g. DOT
+        #[arg(long, default_value = "DOT")]
+        currency: String,
+        /// Network the payment was made on
+        #[arg(long, default_value = "polkadot")]
+        network: String,
+        /// Recognition confidence (0-100)
+        #[arg(long, default_value_t = 100)]
+        confidence: u8,
+        /// BCP 47 tag of the language the command was spoken in, e.g. es
+        #[arg(long)]
+        locale: Option<String>,
+    },
+    /// Schedules handing the contract to a new owner (owner only)
+    TransferOwnership {
+        #[command(flatten)]
+        contract: ContractArg,
+        /// The nominee's SS58 address
+        #[arg(long)]
+        new_owner: AccountId32,
+    },
+    /// Executes a scheduled admin operation whose delay has passed
+    ExecuteOperation {
+        #[command(flatten)]
+        contract: ContractArg,
+        /// The operation's ID
+        id: u64,
+    },
+    /// Completes an ownership transfer (nominated owner only)
+    AcceptOwnership {
+        #[command(flatten)]
+        contract: ContractArg,
+    },
+    /// Sets the locale the signer speaks voice commands in, for clients to parse them with
+    SetLocale {
+        #[command(flatten)]
+        contract: ContractArg,
+        /// BCP 47 tag such as `es` or `pt-BR`; omit to clear it
+        locale: Option<String>,
+    },
+    /// Shows the locale a user speaks voice commands in
+    Locale {
+        #[command(flatten)]
+        contract: ContractArg,
+        /// The user's SS58 address; defaults to the signer
+        user: Option<AccountId32>,
+    },
+    /// Shows the contract's owner
+    Owner {
+        #[command(flatten)]
+        contract: ContractArg,
+    },
+    /// Shows a single payment
+    Payment {
+        #[command(flatten)]
+        contract: ContractArg,
+        /// The payment's ID
+        id: PaymentId,
+    },
+    /// Lists a user's payments
+    History {
+        #[command(flatten)]
+        contract: ContractArg,
+        /// The user's SS58 address; defaults to the signer
+        user: Option<AccountId32>,
+    },
+    /// Lists a user's most recent payments
+    Recent {
+        #[command(flatten)]
+        contract: ContractArg,
+        /// The user's SS58 address; defaults to the signer
+        user: Option<AccountId32>,
+        /// Maximum number of payments to show
+        #[arg(long, default_value_t = 10)]
+        limit: u32,
+    },
+    /// Exports a user's payments for accounting software
+    Export {
+        #[command(flatten)]
+        contract: ContractArg,
+        /// The user's SS58 address; defaults to the signer
+        user: Option<AccountId32>,
+        #[command(flatten)]
+        export: ExportArgs,
+    },
+    /// Shows how many payments a user made and their total amount
+    Stats {
+        #[command(flatten)]
+        contract: ContractArg,
+        /// The user's SS58 address; defaults to the signer
+        user: Option<AccountId32>,
+    },
+    /// Shows the number of payments recorded by the contract
+    Total {
+        #[command(flatten)]
+        contract: ContractArg,
+    },
+}
+
+#[derive(Debug, clap::Args)]
+struct ContractArg {
+    /// SS58 address of the deployed contract
+    #[arg(long, env = "ECHOPAY_CONTRACT")]
+    contract: AccountId32,
+}
+
+#[derive(Debug, clap::Args)]
+struct ExportArgs {
+    /// File format: csv, jsonl, ofx, qif, pain.001 or camt.053
+    #[arg(long, default_value = "csv")]
+    format: Format,
+    /// File to write; defaults to standard output
+    #[arg(long)]
+    out: Option<PathBuf>,
+}
+
+impl ExportArgs {
+    fn write(&self, statement: &Statement) -> Result<()> {
+        let skipped = match &self.out {
+            Some(path) => {
+                let mut file = std::fs::File::create(path)
+                    .with_context(|| format!("failed to create {}", path.display()))?;
+                echopay_export::write(self.format, statement, &mut file)?
+            }
+            None => echopay_export::write(self.format, statement, &mut std::io::stdout().lock())?,
+        };
+        for skipped in skipped {
+            eprintln!("warning: {skipped}");
+        }
+        Ok(())
+    }
+}
+
+/// A record for export, as a payment made by `sender`
+///
+/// Records do not store their sender: it is the user whose history they
+/// were read from.
+fn to_export(record: &PaymentRecord, sender: &AccountId32) -> echopay_export::Payment {
+    echopay_export::Payment {
+        id: record.id,
+        sender: sender.to_string(),
+        recipient: record.recipient.to_string(),
+        amount: record.amount,
+        currency: record.currency.clone(),
+        voice_command: record.voice_command.clone(),
+        locale: record.locale.clone(),
+        timestamp: record.timestamp,
+        status: match record.status {
+            PaymentStatus::Completed => Status::Completed,
+            PaymentStatus::PartiallyRefunded => Status::PartiallyRefunded,
+            PaymentStatus::Refunded => Status::Refunded,
+            PaymentStatus::Disputed => Status::Disputed,
+            PaymentStatus::PendingConfirmation => Status::PendingConfirmation,
+            PaymentStatus::Expired => Status::Expired,
+        },
+        refunded: record.refunded_amount,
+    }
+}
+
+#[derive(Serialize)]
+struct Deployed {
+    contract: AccountId32,
+}
+
+#[derive(Serialize)]
+struct Recorded {
+    payment_id: PaymentId,
+}
+
+#[derive(Serialize)]
+struct Scheduled {
+    operation_id: u64,
+}
+
+#[derive(Serialize)]
+struct Done {
+    ok: bool,
+}
+
+#[derive(Serialize)]
+struct Owner {
+    owner: AccountId32,
+}
+
+#[derive(Serialize)]
+struct Locale {
+    user: AccountId32,
+    locale: Option<String>,
+}
+
+#[derive(Serialize)]
+struct UserStats {
+    user: AccountId32,
+    payments: u32,
+    total_amount: u128,
+}
+
+#[derive(Serialize)]
+struct Total {
+    total_payments: u64,
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let cli = Cli::parse();
+    let uri = SecretUri::from_str(&cli.suri).context("invalid --suri")?;
+    let signer = Keypair::from_uri(&uri).context("invalid --suri")?;
+    let client = RecorderClient::connect(&cli.url, signer).await?;
+    let format = cli.output;
+
+    match cli.command {
+        Command::Deploy { wasm, salt } => {
+            let code = std::fs::read(&wasm)
+                .with_context(|| format!("failed to read {}", wasm.display()))?;
+            let salt = match salt {
+                Some(salt) => {
+                    hex::decode(salt.trim_start_matches("0x")).context("invalid --salt")?
+                }
+                None => std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)?
+                    .as_nanos()
+                    .to_le_bytes()
+                    .to_vec(),
+            };
+            let contract = client.deploy(code, salt).await?;
+            print_value(format, &Deployed { contract })
+        }
+        Command::RecordPayment {
+            contract,
+            recipient,
+            amount,
+            voice_command,
+            currency,
+            network,
+            confidence,
+            locale,
+        } => {
+            let message = messages::record_payment(
+                recipient,
+                amount,
+                voice_command,
+                currency,
+                network,
+                confidence,
+                locale,
+            );
+            let recorded: events::PaymentRecorded =
+                client.call(&contract.contract, &message).await?.event()?;
+            print_value(
+                format,
+                &Recorded {
+                    payment_id: recorded.payment_id,
+                },
+            )
+        }
+        Command::TransferOwnership {
+            contract,
+            new_owner,
+        } => {
+            let message = messages::transfer_ownership(new_owner);
+            let scheduled: events::OperationScheduled =
+                client.call(&contract.contract, &message).await?.event()?;
+            print_value(
+                format,
+                &Scheduled {
+                    operation_id: scheduled.operation_id,
+                },
+            )
+        }
+        Command::ExecuteOperation { contract, id } => {
+            client
+                .call(&contract.contract, &messages::execute_operation(id))
+                .await?;
+            print_value(format, &Done { ok: true })
+        }
+        Command::AcceptOwnership { contract } => {
+            client
+                .call(&contract.contract, &messages::accept_ownership())
+                .await?;
+            print_value(format, &Done { ok: true })
+        }
+        Command::SetLocale { contract, locale } => {
+            client
+                .call(&contract.contract, &messages::set_command_locale(locale))
+                .await?;
+            print_value(format, &Done { ok: true })
+        }
+        Command::Locale { contract, user } => {
+            let user = user.unwrap_or_else(|| client.account());
+            let locale = client
+                .query(
+                    &contract.contract,
+                    &messages::get_command_locale(user.clone()),
+                )
+                .await?;
+            print_value(format, &Locale { user, locale })
+        }
+        Command::Owner { contract } => {
+            let owner = client
+                .query(&contract.contract, &messages::get_owner())
+                .await?;
+            print_value(format, &Owner { owner })
+        }
+        Command::Payment { contract, id } => {
+            let record = client
+                .query(&contract.contract, &messages::get_payment(id))
+                .await?;
+            let record = record.with_context(|| format!("no payment with ID {id}"))?;
+            print_payments(format, &[record])
+        }
+        Command::History { contract, user } => {
+            let user = user.unwrap_or_else(|| client.account());
+            let records = client
+                .query(&contract.contract, &messages::get_payment_history(user))
+                .await?;
+            print_payments(format, &records)
+        }
+        Command::Recent {
+            contract,
+            user,
+            limit,
+        } => {
+            let user = user.unwrap_or_else(|| client.account());
+            let records = client
+                .query(
+                    &contract.contract,
+                    &messages::get_recent_payments(user, limit),
+                )
+                .await?;
+            print_payments(format, &records)
+        }
+        Command::Export {
+            contract,
+            user,
+            export,
+        } => {
+            let user = user.unwrap_or_else(|| client.account());
+            let records = client
+                .query(
+                    &contract.contract,
+                    &messages::get_payment_history(user.clone()),
+                )
+                .await?;
+            let payments: Vec<_> = records.iter().map(|r| to_export(r, &user)).collect();
+            let account = user.to_string();
+            export.write(&Statement::of(&account, &payments))
+        }
+        Command::Stats { contract, user } => {
+            let user = user.unwrap_or_else(|| client.account());
+            let (payments, total_amount) = client
+                .query(&contract.contract, &messages::get_user_stats(user.clone()))
+                .await?;
+            print_value(
+                format,
+                &UserStats {
+                    user,
+                    payments,
+                    total_amount,
+                },
+            )
+        }
+        Command::Total { contract } => {
+            let total_payments = client
+                .query(&contract.contract, &messages::get_total_payments())
+                .await?;
+            print_value(format, &Total { total_payments })
+        }
+    }
+}
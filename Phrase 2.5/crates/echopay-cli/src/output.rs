@@ -0,0 +1,94 @@
+//! Printing query results as tables or JSON
+
+use clap::ValueEnum;
+use comfy_table::{presets::UTF8_FULL, Table};
+use serde::Serialize;
+
+use echopay_client::types::{PaymentAsset, PaymentRecord};
+
+/// How results are printed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// Human-readable tables
+    Table,
+    /// One JSON document per command, for scripts
+    Json,
+}
+
+/// Prints a value as JSON, or as a two-column table of its fields
+pub fn print_value<T: Serialize>(format: OutputFormat, value: &T) -> anyhow::Result<()> {
+    let json = serde_json::to_value(value)?;
+    match format {
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&json)?),
+        OutputFormat::Table => {
+            let mut table = Table::new();
+            table.load_preset(UTF8_FULL);
+            match json {
+                serde_json::Value::Object(fields) => {
+                    table.set_header(["Field", "Value"]);
+                    for (name, value) in fields {
+                        table.add_row([name, plain(&value)]);
+                    }
+                }
+                value => {
+                    table.add_row([plain(&value)]);
+                }
+            }
+            println!("{table}");
+        }
+    }
+    Ok(())
+}
+
+/// Prints payment records as JSON, or as a table with one row per payment
+pub fn print_payments(format: OutputFormat, records: &[PaymentRecord]) -> anyhow::Result<()> {
+    if format == OutputFormat::Json {
+        println!("{}", serde_json::to_string_pretty(records)?);
+        return Ok(());
+    }
+
+    let mut table = Table::new();
+    table.load_preset(UTF8_FULL).set_header([
+        "ID",
+        "Recipient",
+        "Amount",
+        "Currency",
+        "Asset",
+        "Status",
+        "Confidence",
+        "Timestamp",
+        "Voice command",
+    ]);
+    for record in records {
+        table.add_row([
+            record.id.to_string(),
+            record.recipient.to_string(),
+            record.amount.to_string(),
+            record.currency.clone(),
+            asset_label(&record.asset),
+            format!("{:?}", record.status),
+            record.confidence.to_string(),
+            record.timestamp.to_string(),
+            record.voice_command.clone(),
+        ]);
+    }
+    println!("{table}");
+    Ok(())
+}
+
+fn asset_label(asset: &PaymentAsset) -> String {
+    match asset {
+        PaymentAsset::Native => String::from("native"),
+        PaymentAsset::Psp22(token) => format!("psp22:{token}"),
+        PaymentAsset::PalletAsset(asset_id) => format!("asset:{asset_id}"),
+    }
+}
+
+/// Renders a JSON value without quotes around strings
+fn plain(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::String(text) => text.clone(),
+        serde_json::Value::Null => String::from("-"),
+        value => value.to_string(),
+    }
+}
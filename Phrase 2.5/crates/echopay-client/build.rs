@@ -0,0 +1,823 @@
+//! Generates the typed client from the `payment_recorder` contract
+//!
+//! ink! derives a contract's metadata from its source: every `#[ink(message)]`
+//! and `#[ink(constructor)]` of the storage struct's `impl` blocks becomes a
+//! message whose selector is the first four bytes of the BLAKE2b-256 hash of
+//! its name, unless it sets `selector = ...`, and whose arguments are
+//! SCALE-encoded in declaration order. This script reads the same source and
+//! writes, to `payment_recorder.rs` in `OUT_DIR`:
+//!
+//! - a mirror of every public type the contract derives `Decode` for;
+//! - a call struct and builder function per message and constructor;
+//! - a struct per `#[ink(event)]`, with the signature topic ink! gives it.
+//!
+//! When `ECHOPAY_CONTRACT_METADATA` is the absolute path of the `.json` or
+//! `.contract` file `cargo contract build` wrote, the messages and events
+//! are also checked against it and the build fails if a label, selector,
+//! argument or signature topic differs.
+
+use std::{collections::BTreeMap, env, fs, path::PathBuf};
+
+use blake2::{digest::consts::U32, Blake2b, Digest};
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::{format_ident, quote, ToTokens};
+use syn::{
+    Attribute, Expr, Fields, FnArg, ImplItem, Item, ItemEnum, ItemStruct, Lit, Pat, ReturnType,
+    Type, Visibility,
+};
+
+const CONTRACT: &str = "../../contracts/payment_recorder/lib.rs";
+
+/// Arrays longer than this have no `serde` implementation
+const SERDE_ARRAY_LIMIT: usize = 32;
+
+fn main() {
+    let manifest_dir = PathBuf::from(env::var_os("CARGO_MANIFEST_DIR").unwrap());
+    let path = manifest_dir.join(CONTRACT);
+    println!("cargo:rerun-if-changed={}", path.display());
+    println!("cargo:rerun-if-env-changed=ECHOPAY_CONTRACT_METADATA");
+
+    let source = fs::read_to_string(&path)
+        .unwrap_or_else(|error| panic!("failed to read {}: {error}", path.display()));
+    let file = syn::parse_file(&source)
+        .unwrap_or_else(|error| panic!("failed to parse {}: {error}", path.display()));
+    let contract = Contract::new(&file);
+
+    if let Some(metadata) = env::var_os("ECHOPAY_CONTRACT_METADATA") {
+        let metadata = PathBuf::from(metadata);
+        println!("cargo:rerun-if-changed={}", metadata.display());
+        contract.check(&metadata);
+    }
+
+    let code = contract.generate();
+    let code = prettyplease::unparse(&syn::parse2(code).expect("generated code parses"));
+    let out = PathBuf::from(env::var_os("OUT_DIR").unwrap()).join("payment_recorder.rs");
+    fs::write(&out, code)
+        .unwrap_or_else(|error| panic!("failed to write {}: {error}", out.display()));
+}
+
+/// What the client is generated from
+struct Contract<'a> {
+    aliases: Vec<&'a syn::ItemType>,
+    structs: Vec<&'a ItemStruct>,
+    enums: Vec<&'a ItemEnum>,
+    constructors: Vec<Callable<'a>>,
+    messages: Vec<Callable<'a>>,
+    events: Vec<&'a ItemStruct>,
+}
+
+/// A message or constructor
+struct Callable<'a> {
+    function: &'a syn::ImplItemFn,
+    selector: [u8; 4],
+    payable: bool,
+    mutates: bool,
+}
+
+impl<'a> Callable<'a> {
+    fn label(&self) -> String {
+        self.function.sig.ident.to_string()
+    }
+
+    fn args(&self) -> impl Iterator<Item = (&'a Ident, &'a Type)> {
+        let function = self.function;
+        function
+            .sig
+            .inputs
+            .iter()
+            .filter_map(move |input| match input {
+                FnArg::Typed(arg) => match &*arg.pat {
+                    Pat::Ident(pat) => Some((&pat.ident, &*arg.ty)),
+                    _ => panic!("{}: arguments must be plain names", function.sig.ident),
+                },
+                FnArg::Receiver(_) => None,
+            })
+    }
+}
+
+impl<'a> Contract<'a> {
+    fn new(file: &'a syn::File) -> Self {
+        let mut contract = Self {
+            aliases: Vec::new(),
+            structs: Vec::new(),
+            enums: Vec::new(),
+            constructors: Vec::new(),
+            messages: Vec::new(),
+            events: Vec::new(),
+        };
+        for item in &file.items {
+            if let Item::Type(alias) = item {
+                if is_public(&alias.vis) && alias.generics.params.is_empty() {
+                    contract.aliases.push(alias);
+                }
+            }
+        }
+        let module = file
+            .items
+            .iter()
+            .find_map(|item| match item {
+                Item::Mod(module) if has_attribute(&module.attrs, "contract") => {
+                    module.content.as_ref()
+                }
+                _ => None,
+            })
+            .expect("no #[ink::contract] module");
+
+        let storage = module
+            .1
+            .iter()
+            .find_map(|item| match item {
+                Item::Struct(item) if ink_attribute(&item.attrs, "storage") => Some(&item.ident),
+                _ => None,
+            })
+            .expect("no #[ink(storage)] struct");
+
+        for item in &module.1 {
+            match item {
+                Item::Type(alias) if is_public(&alias.vis) && alias.generics.params.is_empty() => {
+                    contract.aliases.push(alias)
+                }
+                Item::Struct(item) if ink_attribute(&item.attrs, "event") => {
+                    contract.events.push(item)
+                }
+                Item::Struct(item) if is_public(&item.vis) && derives_decode(&item.attrs) => {
+                    contract.structs.push(item)
+                }
+                Item::Enum(item) if is_public(&item.vis) && derives_decode(&item.attrs) => {
+                    contract.enums.push(item)
+                }
+                Item::Impl(block) if block.trait_.is_none() && is_type(&block.self_ty, storage) => {
+                    for item in &block.items {
+                        let ImplItem::Fn(function) = item else {
+                            continue;
+                        };
+                        if let Some(callable) = callable(function, "constructor") {
+                            contract.constructors.push(callable);
+                        } else if let Some(callable) = callable(function, "message") {
+                            contract.messages.push(callable);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        contract
+    }
+
+    fn generate(&self) -> TokenStream {
+        let aliases = self.aliases.iter().map(|alias| {
+            let docs = docs(&alias.attrs);
+            let ident = &alias.ident;
+            let ty = map_type(&alias.ty);
+            quote! { #(#docs)* pub type #ident = #ty; }
+        });
+        let structs = self.structs.iter().map(|item| generate_struct(item));
+        let enums = self.enums.iter().map(|item| generate_enum(item));
+        let constructors = self
+            .constructors
+            .iter()
+            .map(|callable| generate_callable(callable, false));
+        let messages = self
+            .messages
+            .iter()
+            .map(|callable| generate_callable(callable, true));
+        let events = self.events.iter().map(|item| generate_event(item));
+
+        quote! {
+            pub mod types {
+                //! The contract's public types, decoded by position like the contract encodes them
+
+                use scale::{Decode, Encode};
+                use serde::Serialize;
+
+                pub use crate::{AccountId32, H256};
+
+                #(#aliases)*
+                #(#structs)*
+                #(#enums)*
+            }
+
+            pub mod constructors {
+                //! Calls that instantiate the contract
+
+                use scale::Encode;
+
+                #[allow(unused_imports)]
+                use crate::types::*;
+
+                #(#constructors)*
+            }
+
+            pub mod messages {
+                //! Calls of the contract's messages, one struct and builder function each
+
+                use scale::Encode;
+
+                #[allow(unused_imports)]
+                use crate::types::*;
+
+                #(#messages)*
+            }
+
+            pub mod events {
+                //! The contract's events, decoded from `Contracts::ContractEmitted`
+
+                use scale::Decode;
+
+                #[allow(unused_imports)]
+                use crate::types::*;
+
+                #(#events)*
+            }
+        }
+    }
+
+    /// Fails the build if the metadata disagrees with the source
+    fn check(&self, path: &PathBuf) {
+        let text = fs::read_to_string(path)
+            .unwrap_or_else(|error| panic!("failed to read {}: {error}", path.display()));
+        let metadata: serde_json::Value = serde_json::from_str(&text)
+            .unwrap_or_else(|error| panic!("{} is not JSON: {error}", path.display()));
+
+        let mut errors = Vec::new();
+        for (kind, callables) in [
+            ("constructors", &self.constructors),
+            ("messages", &self.messages),
+        ] {
+            let declared: BTreeMap<String, &serde_json::Value> = metadata["spec"][kind]
+                .as_array()
+                .unwrap_or_else(|| panic!("{} has no spec.{kind}", path.display()))
+                .iter()
+                .map(|spec| (spec["label"].as_str().unwrap_or_default().to_owned(), spec))
+                .collect();
+            for callable in callables.iter() {
+                let label = callable.label();
+                let Some(spec) = declared.get(&label) else {
+                    errors.push(format!("{label} is not in the metadata"));
+                    continue;
+                };
+                let selector = format!("0x{}", hex(&callable.selector));
+                if spec["selector"] != selector.as_str() {
+                    errors.push(format!(
+                        "{label} has selector {} in the metadata, {selector} in the source",
+                        spec["selector"]
+                    ));
+                }
+                let args: Vec<String> = callable.args().map(|(name, _)| name.to_string()).collect();
+                let declared_args: Vec<&str> = spec["args"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .map(|arg| arg["label"].as_str().unwrap_or_default())
+                    .collect();
+                if args != declared_args {
+                    errors.push(format!(
+                        "{label} takes {declared_args:?} in the metadata, {args:?} in the source"
+                    ));
+                }
+                for ((name, ty), arg) in callable
+                    .args()
+                    .zip(spec["args"].as_array().into_iter().flatten())
+                {
+                    let display_name = arg["type"]["displayName"]
+                        .as_array()
+                        .and_then(|path| path.last())
+                        .and_then(|name| name.as_str());
+                    if let (Some(display_name), Some(ident)) = (display_name, outer_ident(ty)) {
+                        if display_name != ident {
+                            errors.push(format!(
+                                "{label}: {name} is a {display_name} in the metadata, a {ident} in the source"
+                            ));
+                        }
+                    }
+                }
+                if spec["payable"].as_bool() != Some(callable.payable) {
+                    errors.push(format!("{label}: payable differs"));
+                }
+                if kind == "messages" && spec["mutates"].as_bool() != Some(callable.mutates) {
+                    errors.push(format!("{label}: mutates differs"));
+                }
+            }
+            if declared.len() != callables.len() {
+                errors.push(format!(
+                    "the metadata declares {} {kind}, the source {}",
+                    declared.len(),
+                    callables.len()
+                ));
+            }
+        }
+
+        let declared: BTreeMap<&str, &serde_json::Value> = metadata["spec"]["events"]
+            .as_array()
+            .unwrap_or_else(|| panic!("{} has no spec.events", path.display()))
+            .iter()
+            .map(|spec| (spec["label"].as_str().unwrap_or_default(), spec))
+            .collect();
+        for item in &self.events {
+            let label = item.ident.to_string();
+            let Some(spec) = declared.get(label.as_str()) else {
+                errors.push(format!("event {label} is not in the metadata"));
+                continue;
+            };
+            let fields: Vec<String> = item
+                .fields
+                .iter()
+                .filter_map(|field| field.ident.as_ref().map(Ident::to_string))
+                .collect();
+            let declared_fields: Vec<&str> = spec["args"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .map(|arg| arg["label"].as_str().unwrap_or_default())
+                .collect();
+            if fields != declared_fields {
+                errors.push(format!(
+                    "event {label} has {declared_fields:?} in the metadata, {fields:?} in the source"
+                ));
+            }
+            // Version 4 metadata has no signature topics
+            if let Some(topic) = spec["signature_topic"].as_str() {
+                let expected = format!("0x{}", hex(&signature_topic(item)));
+                if topic != expected {
+                    errors.push(format!(
+                        "event {label} has signature topic {topic} in the metadata, {expected} in the source"
+                    ));
+                }
+            }
+        }
+        if declared.len() != self.events.len() {
+            errors.push(format!(
+                "the metadata declares {} events, the source {}",
+                declared.len(),
+                self.events.len()
+            ));
+        }
+
+        if !errors.is_empty() {
+            panic!(
+                "{} does not match {CONTRACT}:\n{}",
+                path.display(),
+                errors.join("\n")
+            );
+        }
+    }
+}
+
+/// The message or constructor `function` is, if it is one
+fn callable<'a>(function: &'a syn::ImplItemFn, kind: &str) -> Option<Callable<'a>> {
+    let attribute = function
+        .attrs
+        .iter()
+        .find(|attribute| ink_attribute(std::slice::from_ref(attribute), kind))?;
+    let label = function.sig.ident.to_string();
+    let mut callable = Callable {
+        function,
+        selector: selector(&label),
+        payable: false,
+        mutates: matches!(
+            function.sig.inputs.first(),
+            Some(FnArg::Receiver(receiver)) if receiver.mutability.is_some()
+        ),
+    };
+    attribute
+        .parse_nested_meta(|meta| {
+            if meta.path.is_ident("payable") {
+                callable.payable = true;
+            } else if meta.path.is_ident("selector") {
+                let value: Lit = meta.value()?.parse()?;
+                let Lit::Int(value) = value else {
+                    return Err(meta.error("only integer selectors are supported"));
+                };
+                callable.selector = value.base10_parse::<u32>()?.to_be_bytes();
+            } else if meta.input.peek(syn::Token![=]) {
+                meta.value()?.parse::<Expr>()?;
+            }
+            Ok(())
+        })
+        .unwrap_or_else(|error| panic!("{label}: {error}"));
+    Some(callable)
+}
+
+/// The selector ink! gives a message or constructor that does not set one
+fn selector(label: &str) -> [u8; 4] {
+    let hash = Blake2b::<U32>::digest(label.as_bytes());
+    [hash[0], hash[1], hash[2], hash[3]]
+}
+
+/// The topic ink! publishes first for an event: the BLAKE2b-256 hash of its
+/// name and field types as written, e.g. `Paused(bool,AccountId)`
+fn signature_topic(item: &ItemStruct) -> [u8; 32] {
+    let types: Vec<String> = item
+        .fields
+        .iter()
+        .map(|field| field.ty.to_token_stream().to_string().replace(' ', ""))
+        .collect();
+    let signature = format!("{}({})", item.ident, types.join(","));
+    Blake2b::<U32>::digest(signature.as_bytes()).into()
+}
+
+fn generate_event(item: &ItemStruct) -> TokenStream {
+    let docs = docs(&item.attrs);
+    let ident = &item.ident;
+    let label = ident.to_string();
+    let topic = signature_topic(item)
+        .map(|byte| syn::LitInt::new(&format!("0x{byte:02x}"), Span::call_site()));
+    let fields = generate_fields(&item.fields, true);
+    quote! {
+        #(#docs)*
+        #[derive(Debug, Clone, PartialEq, Eq, Decode)]
+        pub struct #ident #fields
+
+        impl crate::Event for #ident {
+            const LABEL: &'static str = #label;
+            const SIGNATURE_TOPIC: [u8; 32] = [#(#topic),*];
+        }
+    }
+}
+
+fn generate_callable(callable: &Callable, is_message: bool) -> TokenStream {
+    let label = callable.label();
+    let builder = &callable.function.sig.ident;
+    let ident = format_ident!("{}", camel_case(&label));
+    let docs = docs(&callable.function.attrs);
+    let struct_doc = format!(" A `{label}` call; see [`{builder}`]");
+    let selector = callable
+        .selector
+        .map(|byte| syn::LitInt::new(&format!("0x{byte:02x}"), Span::call_site()));
+    let payable = callable.payable;
+
+    let names: Vec<&Ident> = callable.args().map(|(name, _)| name).collect();
+    let types: Vec<TokenStream> = callable.args().map(|(_, ty)| map_type(ty)).collect();
+    let too_many = (names.len() > 7).then(|| quote!(#[allow(clippy::too_many_arguments)]));
+    let call = if names.is_empty() {
+        quote! { pub struct #ident; }
+    } else {
+        quote! { pub struct #ident { #(pub #names: #types,)* } }
+    };
+    let construct = if names.is_empty() {
+        quote!(#ident)
+    } else {
+        quote!(#ident { #(#names,)* })
+    };
+    let kind = if is_message {
+        quote!(crate::Message)
+    } else {
+        quote!(crate::Constructor)
+    };
+    let message_items = is_message.then(|| {
+        let output = match &callable.function.sig.output {
+            ReturnType::Default => quote!(()),
+            ReturnType::Type(_, ty) => map_type(ty),
+        };
+        let mutates = callable.mutates;
+        quote! {
+            const MUTATES: bool = #mutates;
+            type Output = #output;
+        }
+    });
+
+    quote! {
+        #[doc = #struct_doc]
+        #[derive(Debug, Clone, PartialEq, Eq, Encode)]
+        #call
+
+        impl #kind for #ident {
+            const LABEL: &'static str = #label;
+            const SELECTOR: [u8; 4] = [#(#selector),*];
+            const PAYABLE: bool = #payable;
+            #message_items
+        }
+
+        #(#docs)*
+        #too_many
+        pub fn #builder(#(#names: #types),*) -> #ident {
+            #construct
+        }
+    }
+}
+
+fn generate_struct(item: &ItemStruct) -> TokenStream {
+    let docs = docs(&item.attrs);
+    let ident = &item.ident;
+    let fields = generate_fields(&item.fields, true);
+    let semicolon = (!matches!(item.fields, Fields::Named(_))).then(|| quote!(;));
+    quote! {
+        #(#docs)*
+        #[derive(Debug, Clone, PartialEq, Eq, Encode, Decode, Serialize)]
+        pub struct #ident #fields #semicolon
+    }
+}
+
+fn generate_enum(item: &ItemEnum) -> TokenStream {
+    let item_docs = docs(&item.attrs);
+    let ident = renamed(&item.ident);
+    let variants = item.variants.iter().map(|variant| {
+        let docs = docs(&variant.attrs);
+        let codec = codec(&variant.attrs);
+        let name = &variant.ident;
+        let fields = generate_fields(&variant.fields, false);
+        let discriminant = variant
+            .discriminant
+            .as_ref()
+            .map(|(_, value)| quote!(= #value));
+        quote! { #(#docs)* #(#codec)* #name #fields #discriminant }
+    });
+    let display = (ident == "ContractError").then(|| generate_display(item));
+    quote! {
+        #(#item_docs)*
+        #[derive(Debug, Clone, PartialEq, Eq, Encode, Decode, Serialize)]
+        pub enum #ident { #(#variants,)* }
+        #display
+    }
+}
+
+fn generate_fields(fields: &Fields, public: bool) -> TokenStream {
+    let visibility = public.then(|| quote!(pub));
+    let field = |field: &syn::Field| {
+        let docs = docs(&field.attrs);
+        let codec = codec(&field.attrs);
+        let ty = map_type(&field.ty);
+        let serde = is_long_array(&field.ty)
+            .then(|| quote!(#[serde(serialize_with = "crate::serialize_hex")]));
+        (quote! { #(#docs)* #(#codec)* #serde }, ty)
+    };
+    match fields {
+        Fields::Named(named) => {
+            let fields = named.named.iter().map(|f| {
+                let (attributes, ty) = field(f);
+                let name = &f.ident;
+                quote! { #attributes #visibility #name: #ty }
+            });
+            quote! { { #(#fields,)* } }
+        }
+        Fields::Unnamed(unnamed) => {
+            let fields = unnamed.unnamed.iter().map(|f| {
+                let (attributes, ty) = field(f);
+                quote! { #attributes #visibility #ty }
+            });
+            quote! { ( #(#fields,)* ) }
+        }
+        Fields::Unit => TokenStream::new(),
+    }
+}
+
+/// `Display` for the contract's errors: the first line of each variant's
+/// documentation, then its fields
+fn generate_display(item: &ItemEnum) -> TokenStream {
+    let arms = item.variants.iter().map(|variant| {
+        let name = &variant.ident;
+        let text = doc_text(&variant.attrs);
+        let line = text.lines().next().unwrap_or_default().trim();
+        let mut message = lowercase_first(if line.is_empty() {
+            name.to_string()
+        } else {
+            line.to_owned()
+        })
+        .replace('{', "{{")
+        .replace('}', "}}");
+        match &variant.fields {
+            Fields::Named(named) => {
+                let fields: Vec<&Ident> = named
+                    .named
+                    .iter()
+                    .filter_map(|f| f.ident.as_ref())
+                    .collect();
+                let parts: Vec<String> = fields.iter().map(|f| format!("{f} {{{f}}}")).collect();
+                message.push_str(&format!(" ({})", parts.join(", ")));
+                quote! { Self::#name { #(#fields),* } => write!(f, #message) }
+            }
+            Fields::Unnamed(unnamed) => {
+                let fields: Vec<Ident> = (0..unnamed.unnamed.len())
+                    .map(|i| format_ident!("field{i}"))
+                    .collect();
+                let parts = vec!["{}"; fields.len()].join(", ");
+                message.push_str(&format!(" ({parts})"));
+                quote! { Self::#name(#(#fields),*) => write!(f, #message, #(#fields),*) }
+            }
+            Fields::Unit => quote! { Self::#name => f.write_str(#message) },
+        }
+    });
+    quote! {
+        impl core::fmt::Display for ContractError {
+            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                match self { #(#arms,)* }
+            }
+        }
+
+        impl std::error::Error for ContractError {}
+    }
+}
+
+/// The client's type for a type of the contract's source
+///
+/// The environment's types become their `subxt` equivalents and the
+/// contract's one-argument `Result` its full form; paths are dropped, as
+/// every type the contract uses is a primitive, from the prelude or
+/// generated.
+fn map_type(ty: &Type) -> TokenStream {
+    match ty {
+        Type::Path(path) if path.qself.is_none() => {
+            let segment = path.path.segments.last().expect("non-empty path");
+            let args: Vec<TokenStream> = match &segment.arguments {
+                syn::PathArguments::None => Vec::new(),
+                syn::PathArguments::AngleBracketed(args) => args
+                    .args
+                    .iter()
+                    .map(|arg| match arg {
+                        syn::GenericArgument::Type(ty) => map_type(ty),
+                        other => panic!("unsupported generic argument {}", other.to_token_stream()),
+                    })
+                    .collect(),
+                syn::PathArguments::Parenthesized(_) => {
+                    panic!("unsupported type {}", ty.to_token_stream())
+                }
+            };
+            match (segment.ident.to_string().as_str(), args.as_slice()) {
+                ("AccountId", []) => quote!(AccountId32),
+                ("Hash", []) => quote!(H256),
+                ("Balance", []) => quote!(u128),
+                ("Timestamp", []) => quote!(u64),
+                ("BlockNumber", []) => quote!(u32),
+                ("Result", [ok]) => quote!(core::result::Result<#ok, ContractError>),
+                ("Result", [ok, err]) => quote!(core::result::Result<#ok, #err>),
+                (_, []) => renamed(&segment.ident).into_token_stream(),
+                (_, args) => {
+                    let ident = renamed(&segment.ident);
+                    quote!(#ident<#(#args),*>)
+                }
+            }
+        }
+        Type::Tuple(tuple) => {
+            let elems = tuple.elems.iter().map(map_type);
+            quote!((#(#elems,)*))
+        }
+        Type::Array(array) => {
+            let elem = map_type(&array.elem);
+            let len = &array.len;
+            quote!([#elem; #len])
+        }
+        Type::Paren(inner) => map_type(&inner.elem),
+        Type::Group(inner) => map_type(&inner.elem),
+        other => panic!("unsupported type {}", other.to_token_stream()),
+    }
+}
+
+/// The contract's `Error` is `ContractError`, so it reads well next to other errors
+fn renamed(ident: &Ident) -> Ident {
+    if ident == "Error" {
+        Ident::new("ContractError", Span::call_site())
+    } else {
+        ident.clone()
+    }
+}
+
+/// The last path segment of a type, as metadata display names end with it
+fn outer_ident(ty: &Type) -> Option<String> {
+    match ty {
+        Type::Path(path) => path.path.segments.last().map(|s| s.ident.to_string()),
+        _ => None,
+    }
+}
+
+fn is_long_array(ty: &Type) -> bool {
+    let Type::Array(array) = ty else {
+        return false;
+    };
+    match &array.len {
+        Expr::Lit(syn::ExprLit {
+            lit: Lit::Int(len), ..
+        }) => len
+            .base10_parse::<usize>()
+            .is_ok_and(|len| len > SERDE_ARRAY_LIMIT),
+        _ => false,
+    }
+}
+
+fn is_public(visibility: &Visibility) -> bool {
+    matches!(visibility, Visibility::Public(_))
+}
+
+fn is_type(ty: &Type, ident: &Ident) -> bool {
+    matches!(ty, Type::Path(path) if path.path.is_ident(ident))
+}
+
+/// Whether an item has `#[ink::<name>]` or `#[ink::<name>(...)]`
+fn has_attribute(attrs: &[Attribute], name: &str) -> bool {
+    attrs.iter().any(|attribute| {
+        let segments: Vec<String> = attribute
+            .path()
+            .segments
+            .iter()
+            .map(|s| s.ident.to_string())
+            .collect();
+        segments == ["ink", name]
+    })
+}
+
+/// Whether an item has `#[ink(<name>, ...)]`
+fn ink_attribute(attrs: &[Attribute], name: &str) -> bool {
+    attrs.iter().any(|attribute| {
+        let mut found = false;
+        if attribute.path().is_ident("ink") {
+            let _ = attribute.parse_nested_meta(|meta| {
+                found |= meta.path.is_ident(name);
+                if meta.input.peek(syn::Token![=]) {
+                    meta.value()?.parse::<Expr>()?;
+                }
+                Ok(())
+            });
+        }
+        found
+    })
+}
+
+/// Whether an item derives `scale::Decode`, through ink! or directly
+fn derives_decode(attrs: &[Attribute]) -> bool {
+    attrs.iter().any(|attribute| {
+        let path = attribute.path();
+        let is_derive = path.is_ident("derive")
+            || path
+                .segments
+                .last()
+                .is_some_and(|s| s.ident == "scale_derive");
+        is_derive
+            && attribute
+                .meta
+                .to_token_stream()
+                .into_iter()
+                .any(|token| token.to_string().contains("Decode"))
+    })
+}
+
+fn docs(attrs: &[Attribute]) -> Vec<&Attribute> {
+    attrs
+        .iter()
+        .filter(|attribute| attribute.path().is_ident("doc"))
+        .collect()
+}
+
+fn codec(attrs: &[Attribute]) -> Vec<&Attribute> {
+    attrs
+        .iter()
+        .filter(|attribute| attribute.path().is_ident("codec"))
+        .collect()
+}
+
+fn doc_text(attrs: &[Attribute]) -> String {
+    let mut text = String::new();
+    for attribute in docs(attrs) {
+        if let syn::Meta::NameValue(syn::MetaNameValue {
+            value:
+                Expr::Lit(syn::ExprLit {
+                    lit: Lit::Str(line),
+                    ..
+                }),
+            ..
+        }) = &attribute.meta
+        {
+            text.push_str(&line.value());
+            text.push('\n');
+        }
+    }
+    text
+}
+
+/// Lowercases the first letter, unless it starts an acronym such as `PSP22`
+fn lowercase_first(text: String) -> String {
+    let mut chars = text.chars();
+    let first = chars.next();
+    if chars
+        .next()
+        .is_some_and(|second| second.is_uppercase() || second.is_ascii_digit())
+    {
+        return text;
+    }
+    first
+        .into_iter()
+        .flat_map(char::to_lowercase)
+        .chain(chars_after_first(&text))
+        .collect()
+}
+
+fn chars_after_first(text: &str) -> std::str::Chars<'_> {
+    let mut chars = text.chars();
+    chars.next();
+    chars
+}
+
+fn camel_case(label: &str) -> String {
+    label
+        .split('_')
+        .map(|word| {
+            let mut chars = word.chars();
+            chars
+                .next()
+                .map(|first| first.to_uppercase().chain(chars).collect::<String>())
+                .unwrap_or_default()
+        })
+        .collect()
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
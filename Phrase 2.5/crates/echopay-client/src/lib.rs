@@ -0,0 +1,287 @@
+//! Typed calls to the EchoPay `payment_recorder` contract
+//!
+//! [`messages`] has a call struct and a builder function for every message
+//! of the contract, [`constructors`] the same for its constructor,
+//! [`events`] a struct for every event it emits, and [`types`] mirrors the
+//! public types they take and return. All four are generated at build time
+//! from `contracts/payment_recorder/lib.rs`, with selectors computed the way
+//! ink! computes them, so a renamed message or a changed argument changes the
+//! builders and breaks callers at compile time rather than on chain. Setting
+//! `ECHOPAY_CONTRACT_METADATA` to the absolute path of the metadata
+//! `cargo contract build` wrote also checks the generated selectors and
+//! arguments against it.
+//!
+//! A call's [`Message::call_data`] is the `data` of a `Contracts::call`
+//! extrinsic or `ContractsApi_call` dry run, and [`Message::decode_output`]
+//! decodes the contract's reply:
+//!
+//! ```
+//! use echopay_client::{messages, Message};
+//!
+//! let call = messages::get_payment(7);
+//! assert_eq!(call.call_data()[..4], messages::GetPayment::SELECTOR);
+//! assert_eq!(call.call_data()[4..], 7u64.to_le_bytes());
+//!
+//! // `Ok(None)`: the message ran and found no payment
+//! assert_eq!(messages::GetPayment::decode_output(&[0, 0]).unwrap(), None);
+//! ```
+
+use scale::{Decode, DecodeAll, Encode};
+use serde::Serializer;
+
+pub use subxt::utils::{AccountId32, H256};
+
+include!(concat!(env!("OUT_DIR"), "/payment_recorder.rs"));
+
+/// A call of one of the contract's messages
+pub trait Message: Encode {
+    /// The message's name
+    const LABEL: &'static str;
+    /// The message's ink! selector
+    const SELECTOR: [u8; 4];
+    /// Whether the message accepts a transferred value
+    const PAYABLE: bool;
+    /// Whether the message changes the contract's state, and so needs a transaction
+    const MUTATES: bool;
+    /// What the message returns
+    type Output: Decode;
+
+    /// Call data: the selector followed by the encoded arguments
+    fn call_data(&self) -> Vec<u8> {
+        let mut data = Self::SELECTOR.to_vec();
+        self.encode_to(&mut data);
+        data
+    }
+
+    /// Decodes the `MessageResult` a call of the message returned
+    fn decode_output(data: &[u8]) -> Result<Self::Output, Error> {
+        Ok(MessageResult::<Self::Output>::decode_all(&mut &data[..])??)
+    }
+}
+
+/// A call of one of the contract's constructors
+pub trait Constructor: Encode {
+    /// The constructor's name
+    const LABEL: &'static str;
+    /// The constructor's ink! selector
+    const SELECTOR: [u8; 4];
+    /// Whether the constructor accepts an endowment
+    const PAYABLE: bool;
+
+    /// Instantiation data: the selector followed by the encoded arguments
+    fn call_data(&self) -> Vec<u8> {
+        let mut data = Self::SELECTOR.to_vec();
+        self.encode_to(&mut data);
+        data
+    }
+}
+
+/// An event the contract emits
+pub trait Event: Decode {
+    /// The event's name
+    const LABEL: &'static str;
+    /// The first topic of the event, identifying it among the contract's events
+    const SIGNATURE_TOPIC: [u8; 32];
+
+    /// Decodes the data of a `Contracts::ContractEmitted` event, if its topics mark it as this event
+    fn decode_emitted(topics: &[H256], data: &[u8]) -> Option<Result<Self, Error>> {
+        if topics.first()?.0 != Self::SIGNATURE_TOPIC {
+            return None;
+        }
+        Some(Self::decode_all(&mut &data[..]).map_err(Error::Decode))
+    }
+}
+
+/// Why a contract's reply could not be decoded
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// The reply is not what the message returns
+    #[error("unexpected return value; does the contract match this client? ({0})")]
+    Decode(#[from] scale::Error),
+    /// ink! could not dispatch the call
+    #[error(transparent)]
+    Lang(#[from] LangError),
+}
+
+/// Error ink! reports when a message cannot be dispatched at all
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Decode, thiserror::Error)]
+pub enum LangError {
+    /// The call data did not match any message or could not be decoded
+    #[codec(index = 1)]
+    #[error("the contract could not decode the call data; is the contract address right?")]
+    CouldNotReadInput,
+}
+
+/// What every ink! message returns on the wire
+pub type MessageResult<T> = Result<T, LangError>;
+
+/// Serializes byte arrays too long for `serde`, such as signatures, as hex
+fn serialize_hex<T: AsRef<[u8]>, S: Serializer>(
+    bytes: &T,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&format!("0x{}", hex::encode(bytes)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use messages::*;
+    use types::*;
+
+    #[test]
+    fn computes_ink_selectors() {
+        // Selector of `new` in ink!'s own metadata
+        assert_eq!(constructors::New::SELECTOR, [0x9b, 0xae, 0x9d, 0x5e]);
+        assert_eq!(constructors::new().call_data(), [0x9b, 0xae, 0x9d, 0x5e]);
+        assert_eq!(GetTotalPayments::LABEL, "get_total_payments");
+        const {
+            assert!(RecordPayment::MUTATES && !GetPayment::MUTATES);
+            assert!(PayInvoice::PAYABLE && !RecordPayment::PAYABLE);
+        }
+    }
+
+    #[test]
+    fn encodes_arguments_in_declaration_order() {
+        let recipient = AccountId32([7; 32]);
+        let call = record_payment(
+            recipient.clone(),
+            500,
+            "Send 5 DOT to Bob".into(),
+            "DOT".into(),
+            "polkadot".into(),
+            95,
+            Some("en".into()),
+        );
+        let data = call.call_data();
+        assert_eq!(data[..4], RecordPayment::SELECTOR);
+        assert_eq!(
+            data[4..],
+            (
+                recipient,
+                500u128,
+                "Send 5 DOT to Bob",
+                "DOT",
+                "polkadot",
+                95u8,
+                Some("en")
+            )
+                .encode()[..]
+        );
+        assert_eq!(get_total_payments().call_data().len(), 4);
+    }
+
+    #[test]
+    fn decodes_payment_record() {
+        let recipient = AccountId32([7; 32]);
+        let bytes = (
+            3u64,
+            recipient.clone(),
+            500u128,
+            "Send 5 DOT to Bob",
+            "DOT",
+            0u8,
+            "polkadot",
+            1_700_000_000_000u64,
+            95u8,
+            None::<u64>,
+            Some(2u64),
+            1u8,
+            100u128,
+            None::<AccountId32>,
+            Some("es-ES"),
+        )
+            .encode();
+
+        let record = PaymentRecord::decode(&mut &bytes[..]).unwrap();
+        assert_eq!(record.id, 3);
+        assert_eq!(record.recipient, recipient);
+        assert_eq!(record.voice_command, "Send 5 DOT to Bob");
+        assert_eq!(record.locale.as_deref(), Some("es-ES"));
+        assert_eq!(record.asset, PaymentAsset::Native);
+        assert_eq!(record.invoice_id, Some(2));
+        assert_eq!(record.status, PaymentStatus::PartiallyRefunded);
+        assert_eq!(record.refunded_amount, 100);
+    }
+
+    #[test]
+    fn decodes_contract_errors_by_position() {
+        assert_eq!(
+            ContractError::decode(&mut &[0u8][..]).unwrap(),
+            ContractError::Unauthorized
+        );
+
+        let bytes = (10u8, 4u32).encode();
+        let error = ContractError::decode(&mut &bytes[..]).unwrap();
+        assert_eq!(error, ContractError::BatchItemFailed { index: 4 });
+        assert_eq!(
+            error.to_string(),
+            "an item of an all-or-nothing batch failed; nothing was recorded (index 4)"
+        );
+
+        let bytes = (32u8, 250u128).encode();
+        assert_eq!(
+            ContractError::decode(&mut &bytes[..]).unwrap(),
+            ContractError::SpendingLimitExceeded { remaining: 250 }
+        );
+        assert_eq!(
+            ContractError::Unauthorized.to_string(),
+            "unauthorized access attempt"
+        );
+    }
+
+    #[test]
+    fn decodes_message_results() {
+        let ok = RecordPayment::decode_output(&[0u8, 0, 9, 0, 0, 0, 0, 0, 0, 0]).unwrap();
+        assert_eq!(ok, Ok(9));
+
+        let err = RecordPayment::decode_output(&[0u8, 1, 46]).unwrap();
+        assert_eq!(err, Err(ContractError::ContractPaused));
+
+        let lang = GetTotalPayments::decode_output(&[1u8, 1]).unwrap_err();
+        assert!(matches!(lang, Error::Lang(LangError::CouldNotReadInput)));
+
+        let trailing = GetTotalPayments::decode_output(&[0u8, 1, 0, 0, 0, 0, 0, 0, 0, 0]);
+        assert!(matches!(trailing, Err(Error::Decode(_))));
+    }
+
+    #[test]
+    fn decodes_emitted_events() {
+        use events::{PaymentRecorded, PendingPaymentExpired};
+
+        // BLAKE2b-256 of the event's signature, as ink! computes it
+        let topic = "e05fbdba20cf8db456482946f44193687f201e07d6e119ee25ba3d7a52e56153";
+        assert_eq!(hex::encode(PaymentRecorded::SIGNATURE_TOPIC), topic);
+
+        let recipient = AccountId32([7; 32]);
+        let data = (
+            9u64,
+            AccountId32([1; 32]),
+            recipient.clone(),
+            500u128,
+            "DOT",
+            "Send 5 DOT to Bob",
+            None::<String>,
+            1_700_000_000_000u64,
+        )
+            .encode();
+        let topics = [H256(PaymentRecorded::SIGNATURE_TOPIC), H256([9; 32])];
+
+        let event = PaymentRecorded::decode_emitted(&topics, &data)
+            .unwrap()
+            .unwrap();
+        assert_eq!(event.payment_id, 9);
+        assert_eq!(event.recipient, recipient);
+        assert_eq!(event.locale, None);
+        assert!(PendingPaymentExpired::decode_emitted(&topics, &data).is_none());
+        assert!(PaymentRecorded::decode_emitted(&topics, &data[1..])
+            .unwrap()
+            .is_err());
+    }
+
+    #[test]
+    fn serializes_signatures_as_hex() {
+        let json = serde_json::to_string(&IntentSignature::Sr25519([0xab; 64])).unwrap();
+        assert_eq!(json, format!(r#"{{"Sr25519":"0x{}"}}"#, "ab".repeat(64)));
+    }
+}
@@ -0,0 +1,117 @@
+//! CSV with a header row, one payment per row
+
+use std::io;
+
+use serde::Serialize;
+
+use crate::{format_amount, format_timestamp, Payment, Statement, Status};
+
+/// A row; the field order is the column order
+#[derive(Serialize)]
+pub(crate) struct Row<'a> {
+    id: u64,
+    timestamp: String,
+    sender: &'a str,
+    recipient: &'a str,
+    /// In whole units of `currency`
+    amount: String,
+    currency: &'a str,
+    voice_command: &'a str,
+    locale: Option<&'a str>,
+    status: Status,
+    /// In whole units of `currency`
+    refunded: String,
+}
+
+impl<'a> From<&'a Payment> for Row<'a> {
+    fn from(payment: &'a Payment) -> Self {
+        Self {
+            id: payment.id,
+            timestamp: format_timestamp(payment.timestamp),
+            sender: &payment.sender,
+            recipient: &payment.recipient,
+            amount: format_amount(payment.amount, &payment.currency),
+            currency: &payment.currency,
+            voice_command: &payment.voice_command,
+            locale: payment.locale.as_deref(),
+            status: payment.status,
+            refunded: format_amount(payment.refunded, &payment.currency),
+        }
+    }
+}
+
+pub(crate) fn write(statement: &Statement, out: &mut dyn io::Write) -> io::Result<()> {
+    let mut writer = ::csv::Writer::from_writer(out);
+    if statement.payments.is_empty() {
+        // `serialize` writes the header with the first row
+        writer.write_record([
+            "id",
+            "timestamp",
+            "sender",
+            "recipient",
+            "amount",
+            "currency",
+            "voice_command",
+            "locale",
+            "status",
+            "refunded",
+        ])?;
+    }
+    for payment in statement.payments {
+        writer.serialize(Row::from(payment))?;
+    }
+    writer.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        tests::{payments, render, ALICE, BOB},
+        Format, Statement, Status,
+    };
+
+    #[test]
+    fn writes_one_row_per_payment() {
+        let csv = render(Format::Csv, &Statement::new(&payments()));
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(
+            lines[0],
+            "id,timestamp,sender,recipient,amount,currency,voice_command,locale,status,refunded"
+        );
+        assert_eq!(
+            lines[1],
+            format!(
+                "1,2023-11-14T22:13:20.000Z,{ALICE},{BOB},2.5000000000,DOT,Send 2.5 DOT to Bob,en,completed,0.0000000000"
+            )
+        );
+        assert_eq!(
+            lines[2],
+            format!(
+                r#"2,2023-11-14T22:14:20.123Z,{BOB},{ALICE},1.250000,USDC,"Paga a Alice ""uno coma veinticinco"" USDC & <gracias>",es,completed,0.000000"#
+            )
+        );
+        assert!(lines[3].ends_with(",7,XYZ,Send 7 XYZ to Bob,,completed,0"));
+    }
+
+    #[test]
+    fn writes_the_status_and_refunds() {
+        let mut payments = payments();
+        payments[0].status = Status::PartiallyRefunded;
+        payments[0].refunded = 5_000_000_000;
+        let csv = render(Format::Csv, &Statement::new(&payments[..1]));
+        assert!(csv
+            .lines()
+            .nth(1)
+            .unwrap()
+            .ends_with(",2.5000000000,DOT,Send 2.5 DOT to Bob,en,partially_refunded,0.5000000000"));
+    }
+
+    #[test]
+    fn writes_the_header_without_payments() {
+        let csv = render(Format::Csv, &Statement::new(&[]));
+        assert_eq!(
+            csv,
+            "id,timestamp,sender,recipient,amount,currency,voice_command,locale,status,refunded\n"
+        );
+    }
+}
@@ -0,0 +1,433 @@
+//! ISO 20022 credit transfer initiations and account statements
+//!
+//! Payments become `pain.001.001.09` credit transfers, grouped into one
+//! payment information block per sender, currency and day, or
+//! `camt.053.001.08` statements, one per currency, from the point of view of
+//! the statement's owner. The recipient and sender are account proxies of
+//! type `SS58`, as addresses do not fit the 34-character account number,
+//! which is `NOTPROVIDED`; the voice command is the unstructured remittance
+//! information. Amounts are net of refunds.
+//!
+//! ISO 20022 amounts have at most five decimal places and currencies are
+//! three uppercase letters, so payments of finer amounts or with codes such
+//! as `USDC` are left out of the message, rather than rounded or renamed, and
+//! returned as [`Skipped`]. Message and creation times come from the latest
+//! exported payment, so exporting the same payments twice gives the same
+//! message.
+
+use std::io::{self, Write};
+
+use echopay_intent::Amount;
+
+use crate::{datetime, decimals, escape, format_timestamp, Payment, Skipped, Statement};
+
+const PAIN_001: &str = "urn:iso:std:iso:20022:tech:xsd:pain.001.001.09";
+const CAMT_053: &str = "urn:iso:std:iso:20022:tech:xsd:camt.053.001.08";
+
+/// Most decimal places of `ActiveOrHistoricCurrencyAndAmount`
+const FRACTION_DIGITS: u8 = 5;
+/// Most digits of `ActiveOrHistoricCurrencyAndAmount`
+const TOTAL_DIGITS: usize = 18;
+/// Length of `Max140Text`, which remittance information is split into
+const REMITTANCE_LENGTH: usize = 140;
+/// Length of `Max35Text`, which identifiers are limited to
+const ID_LENGTH: usize = 35;
+
+/// Writes a `pain.001.001.09` customer credit transfer initiation
+pub(crate) fn write_pain001(
+    statement: &Statement,
+    out: &mut dyn io::Write,
+) -> io::Result<Vec<Skipped>> {
+    let (payments, skipped) = representable(statement)?;
+    let statement = &Statement {
+        payments: &payments,
+        ..*statement
+    };
+    let header = Header::new(statement)?;
+    let mut groups: Vec<(&str, &str, String, Vec<&Payment>)> = Vec::new();
+    for payment in statement.payments {
+        let day = datetime(payment.timestamp).format("%Y-%m-%d").to_string();
+        match groups.iter_mut().find(|(sender, currency, date, _)| {
+            *sender == payment.sender && *currency == payment.currency && *date == day
+        }) {
+            Some((.., payments)) => payments.push(payment),
+            None => groups.push((&payment.sender, &payment.currency, day, vec![payment])),
+        }
+    }
+
+    let mut xml = Xml::new(out);
+    xml.declaration()?;
+    xml.open_with("Document", "xmlns", PAIN_001)?;
+    xml.open("CstmrCdtTrfInitn")?;
+    xml.open("GrpHdr")?;
+    xml.leaf("MsgId", &header.message_id)?;
+    xml.leaf("CreDtTm", &header.created)?;
+    xml.leaf("NbOfTxs", &statement.payments.len().to_string())?;
+    xml.open("InitgPty")?;
+    xml.leaf("Nm", "EchoPay")?;
+    xml.close("InitgPty")?;
+    xml.close("GrpHdr")?;
+    for (sender, _, day, payments) in groups {
+        xml.open("PmtInf")?;
+        xml.leaf(
+            "PmtInfId",
+            &id_range(payments.first().copied(), payments.last().copied()),
+        )?;
+        xml.leaf("PmtMtd", "TRF")?;
+        xml.leaf("NbOfTxs", &payments.len().to_string())?;
+        xml.leaf("CtrlSum", &control_sum(&payments)?)?;
+        xml.open("ReqdExctnDt")?;
+        xml.leaf("Dt", &day)?;
+        xml.close("ReqdExctnDt")?;
+        xml.open("Dbtr")?;
+        xml.leaf("Nm", sender)?;
+        xml.close("Dbtr")?;
+        xml.account("DbtrAcct", Some(sender))?;
+        xml.open("DbtrAgt")?;
+        xml.not_provided("FinInstnId")?;
+        xml.close("DbtrAgt")?;
+        for payment in payments {
+            xml.open("CdtTrfTxInf")?;
+            xml.open("PmtId")?;
+            xml.leaf("InstrId", &payment.id.to_string())?;
+            xml.leaf("EndToEndId", &payment.id.to_string())?;
+            xml.close("PmtId")?;
+            xml.open("Amt")?;
+            xml.amount("InstdAmt", payment.net_amount(), &payment.currency)?;
+            xml.close("Amt")?;
+            xml.open("Cdtr")?;
+            xml.leaf("Nm", &payment.recipient)?;
+            xml.close("Cdtr")?;
+            xml.account("CdtrAcct", Some(&payment.recipient))?;
+            xml.remittance(&payment.voice_command)?;
+            xml.close("CdtTrfTxInf")?;
+        }
+        xml.close("PmtInf")?;
+    }
+    xml.close("CstmrCdtTrfInitn")?;
+    xml.close("Document")?;
+    xml.out.flush()?;
+    Ok(skipped)
+}
+
+/// Writes a `camt.053.001.08` bank to customer statement
+pub(crate) fn write_camt053(
+    statement: &Statement,
+    out: &mut dyn io::Write,
+) -> io::Result<Vec<Skipped>> {
+    let (payments, skipped) = representable(statement)?;
+    let statement = &Statement {
+        payments: &payments,
+        ..*statement
+    };
+    let header = Header::new(statement)?;
+    let mut xml = Xml::new(out);
+    xml.declaration()?;
+    xml.open_with("Document", "xmlns", CAMT_053)?;
+    xml.open("BkToCstmrStmt")?;
+    xml.open("GrpHdr")?;
+    xml.leaf("MsgId", &header.message_id)?;
+    xml.leaf("CreDtTm", &header.created)?;
+    xml.close("GrpHdr")?;
+    for (currency, payments) in statement.by_currency() {
+        let from = payments
+            .iter()
+            .map(|p| p.timestamp)
+            .min()
+            .unwrap_or_default();
+        let to = payments
+            .iter()
+            .map(|p| p.timestamp)
+            .max()
+            .unwrap_or_default();
+        xml.open("Stmt")?;
+        xml.leaf("Id", &statement_id(currency, &payments))?;
+        xml.leaf("CreDtTm", &header.created)?;
+        xml.open("FrToDt")?;
+        xml.leaf("FrDtTm", &format_timestamp(from))?;
+        xml.leaf("ToDtTm", &format_timestamp(to))?;
+        xml.close("FrToDt")?;
+        xml.account("Acct", statement.account)?;
+
+        let (credit, net) = net(statement, &payments);
+        xml.open("Bal")?;
+        xml.open("Tp")?;
+        xml.open("CdOrPrtry")?;
+        xml.leaf("Cd", "CLBD")?;
+        xml.close("CdOrPrtry")?;
+        xml.close("Tp")?;
+        xml.amount("Amt", net, currency)?;
+        xml.leaf("CdtDbtInd", if credit { "CRDT" } else { "DBIT" })?;
+        xml.open("Dt")?;
+        xml.leaf("DtTm", &format_timestamp(to))?;
+        xml.close("Dt")?;
+        xml.close("Bal")?;
+
+        for payment in payments {
+            let credit = statement.is_credit(payment);
+            xml.open("Ntry")?;
+            xml.leaf("NtryRef", &payment.id.to_string())?;
+            xml.amount("Amt", payment.net_amount(), &payment.currency)?;
+            xml.leaf("CdtDbtInd", if credit { "CRDT" } else { "DBIT" })?;
+            xml.open("Sts")?;
+            xml.leaf("Cd", "BOOK")?;
+            xml.close("Sts")?;
+            xml.open("BookgDt")?;
+            xml.leaf("DtTm", &format_timestamp(payment.timestamp))?;
+            xml.close("BookgDt")?;
+            xml.open("BkTxCd")?;
+            xml.open("Domn")?;
+            xml.leaf("Cd", "PMNT")?;
+            xml.open("Fmly")?;
+            xml.leaf("Cd", if credit { "RCDT" } else { "ICDT" })?;
+            xml.leaf("SubFmlyCd", "OTHR")?;
+            xml.close("Fmly")?;
+            xml.close("Domn")?;
+            xml.close("BkTxCd")?;
+            xml.open("NtryDtls")?;
+            xml.open("TxDtls")?;
+            xml.open("Refs")?;
+            xml.leaf("InstrId", &payment.id.to_string())?;
+            xml.leaf("EndToEndId", &payment.id.to_string())?;
+            xml.close("Refs")?;
+            xml.open("RltdPties")?;
+            xml.party("Dbtr", &payment.sender)?;
+            xml.account("DbtrAcct", Some(&payment.sender))?;
+            xml.party("Cdtr", &payment.recipient)?;
+            xml.account("CdtrAcct", Some(&payment.recipient))?;
+            xml.close("RltdPties")?;
+            xml.remittance(&payment.voice_command)?;
+            xml.close("TxDtls")?;
+            xml.close("NtryDtls")?;
+            xml.close("Ntry")?;
+        }
+        xml.close("Stmt")?;
+    }
+    xml.close("BkToCstmrStmt")?;
+    xml.close("Document")?;
+    xml.out.flush()?;
+    Ok(skipped)
+}
+
+/// The group header's message ID and creation time
+struct Header {
+    message_id: String,
+    created: String,
+}
+
+impl Header {
+    fn new(statement: &Statement) -> io::Result<Self> {
+        let latest = statement
+            .payments
+            .iter()
+            .map(|p| p.timestamp)
+            .max()
+            .ok_or_else(|| invalid("ISO 20022 messages need at least one payment".into()))?;
+        Ok(Self {
+            message_id: id_range(statement.payments.first(), statement.payments.last()),
+            created: format_timestamp(latest),
+        })
+    }
+}
+
+/// `EP<first>-<last>` with the payment IDs in hex, at most 35 characters
+fn id_range(first: Option<&Payment>, last: Option<&Payment>) -> String {
+    let id = |payment: Option<&Payment>| payment.map_or(0, |p| p.id);
+    format!("EP{:X}-{:X}", id(first), id(last))
+}
+
+/// `<currency>EP<first>-<last>`, cut to `Max35Text` when the IDs are long
+fn statement_id(currency: &str, payments: &[&Payment]) -> String {
+    let id = id_range(payments.first().copied(), payments.last().copied());
+    format!("{currency}{id}").chars().take(ID_LENGTH).collect()
+}
+
+/// Sum of the amounts, which share a currency
+fn control_sum(payments: &[&Payment]) -> io::Result<String> {
+    let total = payments
+        .iter()
+        .try_fold(0u128, |total, p| total.checked_add(p.net_amount()))
+        .ok_or_else(|| invalid("payment amounts overflow".into()))?;
+    let currency = payments.first().map_or("", |p| p.currency.as_str());
+    amount(total, currency)
+}
+
+/// Whether the owner's net is a credit, and its size
+fn net(statement: &Statement, payments: &[&Payment]) -> (bool, u128) {
+    let (credits, debits) = payments
+        .iter()
+        .fold((0u128, 0u128), |(credits, debits), p| {
+            if statement.is_credit(p) {
+                (credits.saturating_add(p.net_amount()), debits)
+            } else {
+                (credits, debits.saturating_add(p.net_amount()))
+            }
+        });
+    if credits >= debits {
+        (true, credits - debits)
+    } else {
+        (false, debits - credits)
+    }
+}
+
+/// The payments whose currency and amount can be represented, and the others
+///
+/// Fails if that leaves no payment, as a message needs at least one.
+fn representable(statement: &Statement) -> io::Result<(Vec<Payment>, Vec<Skipped>)> {
+    let mut payments = Vec::new();
+    let mut skipped = Vec::new();
+    for payment in statement.payments {
+        match check(payment) {
+            Ok(()) => payments.push(payment.clone()),
+            Err(reason) => skipped.push(Skipped {
+                id: payment.id,
+                reason,
+            }),
+        }
+    }
+    if payments.is_empty() {
+        if let Some(first) = skipped.first() {
+            return Err(invalid(format!(
+                "no payment can be represented in ISO 20022; {first}"
+            )));
+        }
+    }
+    Ok((payments, skipped))
+}
+
+/// Why the payment's currency or amount cannot be represented, if it cannot
+fn check(payment: &Payment) -> Result<(), String> {
+    let currency = &payment.currency;
+    if currency.len() != 3 || !currency.bytes().all(|b| b.is_ascii_uppercase()) {
+        return Err(format!("{currency} is not a three-letter currency code"));
+    }
+    amount(payment.net_amount(), currency)
+        .map(drop)
+        .map_err(|error| error.to_string())
+}
+
+/// An amount as `ActiveOrHistoricCurrencyAndAmount` allows it
+fn amount(units: u128, currency: &str) -> io::Result<String> {
+    let amount = Amount::from_base_units(units, decimals(currency));
+    let text = amount.to_string();
+    if amount.scale() > FRACTION_DIGITS {
+        return Err(invalid(format!(
+            "{text} {currency} has more than {FRACTION_DIGITS} decimal places"
+        )));
+    }
+    if text.bytes().filter(u8::is_ascii_digit).count() > TOTAL_DIGITS {
+        return Err(invalid(format!(
+            "{text} {currency} has more than {TOTAL_DIGITS} digits"
+        )));
+    }
+    Ok(text)
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// Writes indented elements
+struct Xml<'a> {
+    out: io::BufWriter<&'a mut dyn io::Write>,
+    depth: usize,
+}
+
+impl<'a> Xml<'a> {
+    fn new(out: &'a mut dyn io::Write) -> Self {
+        Self {
+            out: io::BufWriter::new(out),
+            depth: 0,
+        }
+    }
+
+    fn declaration(&mut self) -> io::Result<()> {
+        writeln!(self.out, r#"<?xml version="1.0" encoding="UTF-8"?>"#)
+    }
+
+    fn indent(&mut self) -> io::Result<()> {
+        write!(self.out, "{:1$}", "", self.depth * 2)
+    }
+
+    fn open(&mut self, tag: &str) -> io::Result<()> {
+        self.indent()?;
+        writeln!(self.out, "<{tag}>")?;
+        self.depth += 1;
+        Ok(())
+    }
+
+    fn open_with(&mut self, tag: &str, attribute: &str, value: &str) -> io::Result<()> {
+        self.indent()?;
+        writeln!(self.out, r#"<{tag} {attribute}="{value}">"#)?;
+        self.depth += 1;
+        Ok(())
+    }
+
+    fn close(&mut self, tag: &str) -> io::Result<()> {
+        self.depth -= 1;
+        self.indent()?;
+        writeln!(self.out, "</{tag}>")
+    }
+
+    fn leaf(&mut self, tag: &str, text: &str) -> io::Result<()> {
+        self.indent()?;
+        writeln!(self.out, "<{tag}>{}</{tag}>", escape(text))
+    }
+
+    /// An `ActiveOrHistoricCurrencyAndAmount` of a [`check`]ed currency
+    fn amount(&mut self, tag: &str, units: u128, currency: &str) -> io::Result<()> {
+        let amount = amount(units, currency)?;
+        self.indent()?;
+        writeln!(self.out, r#"<{tag} Ccy="{currency}">{amount}</{tag}>"#)
+    }
+
+    /// An account with no number and, if known, its address as proxy
+    fn account(&mut self, tag: &str, address: Option<&str>) -> io::Result<()> {
+        self.open(tag)?;
+        self.open("Id")?;
+        self.open("Othr")?;
+        self.leaf("Id", "NOTPROVIDED")?;
+        self.close("Othr")?;
+        self.close("Id")?;
+        if let Some(address) = address {
+            self.open("Prxy")?;
+            self.open("Tp")?;
+            self.leaf("Prtry", "SS58")?;
+            self.close("Tp")?;
+            self.leaf("Id", address)?;
+            self.close("Prxy")?;
+        }
+        self.close(tag)
+    }
+
+    /// A `Party40Choice` naming an address
+    fn party(&mut self, tag: &str, address: &str) -> io::Result<()> {
+        self.open(tag)?;
+        self.open("Pty")?;
+        self.leaf("Nm", address)?;
+        self.close("Pty")?;
+        self.close(tag)
+    }
+
+    /// A financial institution known by no identifier
+    fn not_provided(&mut self, tag: &str) -> io::Result<()> {
+        self.open(tag)?;
+        self.open("Othr")?;
+        self.leaf("Id", "NOTPROVIDED")?;
+        self.close("Othr")?;
+        self.close(tag)
+    }
+
+    /// Unstructured remittance information, in `Max140Text` pieces
+    fn remittance(&mut self, text: &str) -> io::Result<()> {
+        let chars: Vec<char> = text.chars().collect();
+        if chars.is_empty() {
+            return Ok(());
+        }
+        self.open("RmtInf")?;
+        for piece in chars.chunks(REMITTANCE_LENGTH) {
+            self.leaf("Ustrd", &piece.iter().collect::<String>())?;
+        }
+        self.close("RmtInf")
+    }
+}
@@ -0,0 +1,88 @@
+//! JSON Lines: one JSON object per payment and line
+//!
+//! Objects have the CSV columns, with amounts as decimal strings so no
+//! precision is lost, plus `amount_base_units`, `refunded_base_units` and
+//! `decimals`.
+
+use std::io::{self, Write};
+
+use serde::Serialize;
+
+use crate::{csv::Row, decimals, Statement};
+
+#[derive(Serialize)]
+struct Line<'a> {
+    #[serde(flatten)]
+    row: Row<'a>,
+    amount_base_units: String,
+    refunded_base_units: String,
+    decimals: u8,
+}
+
+pub(crate) fn write(statement: &Statement, out: &mut dyn io::Write) -> io::Result<()> {
+    let mut out = io::BufWriter::new(out);
+    for payment in statement.payments {
+        let line = Line {
+            row: Row::from(payment),
+            amount_base_units: payment.amount.to_string(),
+            refunded_base_units: payment.refunded.to_string(),
+            decimals: decimals(&payment.currency),
+        };
+        serde_json::to_writer(&mut out, &line)?;
+        out.write_all(b"\n")?;
+    }
+    out.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use crate::{
+        tests::{payments, render, ALICE, BOB},
+        Format, Statement, Status,
+    };
+
+    #[test]
+    fn writes_one_object_per_line() {
+        let jsonl = render(Format::JsonLines, &Statement::new(&payments()));
+        let lines: Vec<Value> = jsonl
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(
+            lines[0],
+            json!({
+                "id": 1,
+                "timestamp": "2023-11-14T22:13:20.000Z",
+                "sender": ALICE,
+                "recipient": BOB,
+                "amount": "2.5000000000",
+                "currency": "DOT",
+                "voice_command": "Send 2.5 DOT to Bob",
+                "locale": "en",
+                "status": "completed",
+                "refunded": "0.0000000000",
+                "amount_base_units": "25000000000",
+                "refunded_base_units": "0",
+                "decimals": 10,
+            })
+        );
+        assert_eq!(lines[2]["locale"], Value::Null);
+        assert_eq!(lines[2]["decimals"], 0);
+    }
+
+    #[test]
+    fn writes_the_status_and_refunds() {
+        let mut payments = payments();
+        payments[1].status = Status::Refunded;
+        payments[1].refunded = 1_250_000;
+        let jsonl = render(Format::JsonLines, &Statement::new(&payments[1..2]));
+        let line: Value = serde_json::from_str(jsonl.trim_end()).unwrap();
+        assert_eq!(line["status"], "refunded");
+        assert_eq!(line["amount"], "1.250000");
+        assert_eq!(line["refunded"], "1.250000");
+        assert_eq!(line["refunded_base_units"], "1250000");
+    }
+}
@@ -0,0 +1,441 @@
+//! Exports EchoPay payment history for accounting tools
+//!
+//! Payments read from the contract or the indexer are converted to
+//! [`Payment`] and written with [`write`] in one of six [`Format`]s:
+//!
+//! - CSV and JSON Lines, one payment per row or line, for spreadsheets and
+//!   scripts;
+//! - OFX and QIF, bank statement formats most personal finance and
+//!   accounting software imports, with one statement or account per currency;
+//! - ISO 20022 `pain.001` credit transfer initiations and `camt.053`
+//!   statements, for treasury and banking systems.
+//!
+//! Amounts are printed with exactly as many decimal places as their currency
+//! has (DOT 10, USDC 6, ...; see [`CURRENCIES`](echopay_intent::CURRENCIES)),
+//! and in base units for currencies EchoPay does not know. Timestamps are
+//! ISO 8601 in UTC.
+//!
+//! CSV and JSON Lines list every payment with its [`Status`] and refunded
+//! amount. The other formats book what the payment moved for good: payments
+//! awaiting confirmation, expired or refunded in full are left out and
+//! returned as [`Skipped`], and the rest count at their amount net of
+//! refunds.
+//!
+//! ```
+//! use echopay_export::{write, Format, Payment, Statement, Status};
+//!
+//! let payment = Payment {
+//!     id: 1,
+//!     sender: "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY".into(),
+//!     recipient: "5FHneW46xGXgs5mUiveU4sbTyGBzmstUspZC92UhjJM694ty".into(),
+//!     amount: 25_000_000_000,
+//!     currency: "DOT".into(),
+//!     voice_command: "Send 2.5 DOT to Bob".into(),
+//!     locale: None,
+//!     timestamp: 1_700_000_000_000,
+//!     status: Status::Completed,
+//!     refunded: 0,
+//! };
+//! let mut csv = Vec::new();
+//! write(Format::Csv, &Statement::new(&[payment]), &mut csv).unwrap();
+//! let csv = String::from_utf8(csv).unwrap();
+//! assert!(csv.lines().nth(1).unwrap().starts_with("1,2023-11-14T22:13:20.000Z,"));
+//! assert!(csv.contains(",2.5000000000,DOT,"));
+//! ```
+
+mod csv;
+mod iso20022;
+mod jsonl;
+mod ofx;
+mod qif;
+
+use std::{fmt, io, str::FromStr};
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use echopay_intent::{Amount, Currency};
+use serde::Serialize;
+
+/// A recorded payment to export
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Payment {
+    pub id: u64,
+    /// SS58 address of the payer
+    pub sender: String,
+    /// SS58 address of the payee
+    pub recipient: String,
+    /// Amount in the currency's smallest unit
+    pub amount: u128,
+    /// Currency code, e.g. `DOT`
+    pub currency: String,
+    /// The voice command as transcribed
+    pub voice_command: String,
+    /// BCP 47 tag of the language the command was spoken in
+    pub locale: Option<String>,
+    /// Milliseconds since the Unix epoch
+    pub timestamp: u64,
+    pub status: Status,
+    /// Amount refunded to the sender so far, in the currency's smallest unit
+    pub refunded: u128,
+}
+
+impl Payment {
+    /// The amount less what has been refunded
+    pub fn net_amount(&self) -> u128 {
+        self.amount.saturating_sub(self.refunded)
+    }
+
+    /// Why the payment is not booked in statement and transfer formats, if
+    /// it is not
+    fn unbooked(&self) -> Option<&'static str> {
+        match self.status {
+            Status::PendingConfirmation => Some("it is awaiting the sender's confirmation"),
+            Status::Expired => Some("it expired unconfirmed"),
+            _ if self.net_amount() == 0 => Some("it was refunded in full"),
+            _ => None,
+        }
+    }
+}
+
+/// Where a payment stands, as the contract reports it
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Status {
+    /// Recorded and not refunded
+    #[default]
+    Completed,
+    /// Part of the amount has been refunded
+    PartiallyRefunded,
+    /// The full amount has been refunded
+    Refunded,
+    /// The payer has opened a dispute that is awaiting an arbiter
+    Disputed,
+    /// Recorded below the sender's confidence policy and not confirmed yet
+    PendingConfirmation,
+    /// Not confirmed in time; never counted as paid
+    Expired,
+}
+
+/// Payments to export, and whose statement they make up
+#[derive(Debug, Clone, Copy)]
+pub struct Statement<'a> {
+    pub payments: &'a [Payment],
+    /// SS58 address of the statement's owner
+    ///
+    /// In OFX, QIF and camt.053, payments it received are credits and all
+    /// others debits; without an owner every payment is a debit. It is also
+    /// the OFX account ID and the camt.053 account proxy.
+    pub account: Option<&'a str>,
+}
+
+impl<'a> Statement<'a> {
+    /// A statement without an owner
+    pub fn new(payments: &'a [Payment]) -> Self {
+        Self {
+            payments,
+            account: None,
+        }
+    }
+
+    /// The statement of `account`
+    pub fn of(account: &'a str, payments: &'a [Payment]) -> Self {
+        Self {
+            payments,
+            account: Some(account),
+        }
+    }
+
+    /// Whether `payment` adds to the owner's balance
+    fn is_credit(&self, payment: &Payment) -> bool {
+        self.account
+            .is_some_and(|account| payment.recipient == account && payment.sender != account)
+    }
+
+    /// The counterparty of `payment`, from the owner's point of view
+    fn counterparty<'p>(&self, payment: &'p Payment) -> &'p str {
+        if self.is_credit(payment) {
+            &payment.sender
+        } else {
+            &payment.recipient
+        }
+    }
+
+    /// The payments grouped by currency, in order of first appearance
+    fn by_currency(&self) -> Vec<(&'a str, Vec<&'a Payment>)> {
+        let mut groups: Vec<(&str, Vec<&Payment>)> = Vec::new();
+        for payment in self.payments {
+            match groups
+                .iter_mut()
+                .find(|(currency, _)| *currency == payment.currency)
+            {
+                Some((_, payments)) => payments.push(payment),
+                None => groups.push((&payment.currency, vec![payment])),
+            }
+        }
+        groups
+    }
+}
+
+/// A file format payments can be exported in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// Comma-separated values with a header row
+    Csv,
+    /// One JSON object per line
+    JsonLines,
+    /// Open Financial Exchange 2.2, one bank statement per currency
+    Ofx,
+    /// Quicken Interchange Format, one bank account per currency
+    Qif,
+    /// ISO 20022 `pain.001.001.09` customer credit transfer initiation
+    Pain001,
+    /// ISO 20022 `camt.053.001.08` bank to customer statement, one per currency
+    Camt053,
+}
+
+impl Format {
+    /// Every format, in the order listed in help text
+    pub const ALL: [Format; 6] = [
+        Format::Csv,
+        Format::JsonLines,
+        Format::Ofx,
+        Format::Qif,
+        Format::Pain001,
+        Format::Camt053,
+    ];
+
+    /// Name of the format as given on the command line
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            Self::JsonLines => "jsonl",
+            Self::Ofx => "ofx",
+            Self::Qif => "qif",
+            Self::Pain001 => "pain.001",
+            Self::Camt053 => "camt.053",
+        }
+    }
+
+    /// The usual file extension, without the dot
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Pain001 | Self::Camt053 => "xml",
+            format => format.name(),
+        }
+    }
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    /// Parses a [`Format::name`], ignoring case; `json-lines` and `ndjson`
+    /// are also JSON Lines, and the ISO 20022 names may omit the dot
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        match text.to_ascii_lowercase().as_str() {
+            "csv" => Ok(Self::Csv),
+            "jsonl" | "json-lines" | "ndjson" => Ok(Self::JsonLines),
+            "ofx" => Ok(Self::Ofx),
+            "qif" => Ok(Self::Qif),
+            "pain.001" | "pain001" => Ok(Self::Pain001),
+            "camt.053" | "camt053" => Ok(Self::Camt053),
+            _ => Err(format!(
+                "unknown export format `{text}`; expected one of csv, jsonl, ofx, qif, pain.001, camt.053"
+            )),
+        }
+    }
+}
+
+/// A payment left out of an export because the format cannot represent it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Skipped {
+    /// ID of the payment
+    pub id: u64,
+    /// Why the format cannot represent it
+    pub reason: String,
+}
+
+impl fmt::Display for Skipped {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "payment {} was not exported: {}", self.id, self.reason)
+    }
+}
+
+/// Writes `statement` to `out` in `format`
+///
+/// Returns the payments left out of the file: in every format but CSV and
+/// JSON Lines, those that did not move funds for good and those the format
+/// cannot represent.
+pub fn write(
+    format: Format,
+    statement: &Statement,
+    out: &mut dyn io::Write,
+) -> io::Result<Vec<Skipped>> {
+    let (payments, mut skipped) = booked(statement);
+    let booked = &Statement {
+        payments: &payments,
+        ..*statement
+    };
+    match format {
+        Format::Csv => return csv::write(statement, out).map(|()| Vec::new()),
+        Format::JsonLines => return jsonl::write(statement, out).map(|()| Vec::new()),
+        Format::Ofx => ofx::write(booked, out)?,
+        Format::Qif => qif::write(booked, out)?,
+        Format::Pain001 => skipped.extend(iso20022::write_pain001(booked, out)?),
+        Format::Camt053 => skipped.extend(iso20022::write_camt053(booked, out)?),
+    }
+    Ok(skipped)
+}
+
+/// The payments that moved funds for good, and the others
+fn booked(statement: &Statement) -> (Vec<Payment>, Vec<Skipped>) {
+    let mut payments = Vec::new();
+    let mut skipped = Vec::new();
+    for payment in statement.payments {
+        match payment.unbooked() {
+            None => payments.push(payment.clone()),
+            Some(reason) => skipped.push(Skipped {
+                id: payment.id,
+                reason: reason.into(),
+            }),
+        }
+    }
+    (payments, skipped)
+}
+
+/// Decimal places of a currency's smallest unit; 0 for unknown currencies,
+/// whose amounts are then printed in base units
+pub fn decimals(currency: &str) -> u8 {
+    Currency::by_code(currency).map_or(0, |currency| currency.decimals)
+}
+
+/// A payment's amount in whole units, with all of its currency's decimals
+pub fn format_amount(amount: u128, currency: &str) -> String {
+    Amount::new(amount, decimals(currency)).to_string()
+}
+
+/// A timestamp in milliseconds as ISO 8601 in UTC, e.g. `2023-11-14T22:13:20.000Z`
+pub fn format_timestamp(timestamp: u64) -> String {
+    datetime(timestamp).to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+/// Escapes text for XML element content
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// Timestamps beyond what `chrono` represents are clamped to its maximum
+fn datetime(timestamp: u64) -> DateTime<Utc> {
+    i64::try_from(timestamp)
+        .ok()
+        .and_then(DateTime::from_timestamp_millis)
+        .unwrap_or(DateTime::<Utc>::MAX_UTC)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    pub const ALICE: &str = "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY";
+    pub const BOB: &str = "5FHneW46xGXgs5mUiveU4sbTyGBzmstUspZC92UhjJM694ty";
+
+    /// Alice pays Bob 2.5 DOT, Bob pays Alice 1.25 USDC, then Alice pays Bob
+    /// 7 of an unknown currency
+    pub fn payments() -> Vec<Payment> {
+        vec![
+            Payment {
+                id: 1,
+                sender: ALICE.into(),
+                recipient: BOB.into(),
+                amount: 25_000_000_000,
+                currency: "DOT".into(),
+                voice_command: "Send 2.5 DOT to Bob".into(),
+                locale: Some("en".into()),
+                timestamp: 1_700_000_000_000,
+                status: Status::Completed,
+                refunded: 0,
+            },
+            Payment {
+                id: 2,
+                sender: BOB.into(),
+                recipient: ALICE.into(),
+                amount: 1_250_000,
+                currency: "USDC".into(),
+                voice_command: "Paga a Alice \"uno coma veinticinco\" USDC & <gracias>".into(),
+                locale: Some("es".into()),
+                timestamp: 1_700_000_060_123,
+                status: Status::Completed,
+                refunded: 0,
+            },
+            Payment {
+                id: 3,
+                sender: ALICE.into(),
+                recipient: BOB.into(),
+                amount: 7,
+                currency: "XYZ".into(),
+                voice_command: "Send 7 XYZ to Bob".into(),
+                locale: None,
+                timestamp: 1_700_086_400_000,
+                status: Status::Completed,
+                refunded: 0,
+            },
+        ]
+    }
+
+    pub fn render(format: Format, statement: &Statement) -> String {
+        let mut out = Vec::new();
+        write(format, statement, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn formats_amounts_per_currency() {
+        assert_eq!(format_amount(25_000_000_000, "DOT"), "2.5000000000");
+        assert_eq!(format_amount(1, "usdc"), "0.000001");
+        assert_eq!(
+            format_amount(10u128.pow(18), "GLMR"),
+            "1.000000000000000000"
+        );
+        assert_eq!(format_amount(7, "XYZ"), "7");
+    }
+
+    #[test]
+    fn formats_timestamps_as_iso_8601() {
+        assert_eq!(format_timestamp(0), "1970-01-01T00:00:00.000Z");
+        assert_eq!(
+            format_timestamp(1_700_000_060_123),
+            "2023-11-14T22:14:20.123Z"
+        );
+    }
+
+    #[test]
+    fn parses_formats() {
+        for format in Format::ALL {
+            assert_eq!(format.to_string().parse(), Ok(format));
+        }
+        assert_eq!("NDJSON".parse(), Ok(Format::JsonLines));
+        assert_eq!("pain001".parse(), Ok(Format::Pain001));
+        assert_eq!(Format::Camt053.extension(), "xml");
+        assert!("xlsx".parse::<Format>().is_err());
+    }
+
+    #[test]
+    fn takes_the_owner_point_of_view() {
+        let payments = payments();
+        let statement = Statement::of(ALICE, &payments);
+        assert!(!statement.is_credit(&payments[0]));
+        assert!(statement.is_credit(&payments[1]));
+        assert_eq!(statement.counterparty(&payments[1]), BOB);
+        assert!(!Statement::new(&payments).is_credit(&payments[1]));
+
+        let groups = statement.by_currency();
+        let currencies: Vec<&str> = groups.iter().map(|(currency, _)| *currency).collect();
+        assert_eq!(currencies, ["DOT", "USDC", "XYZ"]);
+    }
+}
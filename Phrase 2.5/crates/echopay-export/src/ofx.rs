@@ -0,0 +1,252 @@
+//! Open Financial Exchange 2.2 bank statements
+//!
+//! OFX statements have a single currency, so payments are split into one
+//! statement per currency, in the order the currencies first appear.
+//! Transactions are for the amount net of refunds, and each statement's
+//! ledger balance is the net of its transactions, as the balance of the
+//! account itself is unknown. Addresses are cut to the field lengths the
+//! specification allows: 22 characters for the account ID and 32 for the
+//! counterparty's name.
+
+use std::io::{self, Write};
+
+use crate::{datetime, escape, format_amount, Payment, Statement};
+
+const ACCOUNT_ID_LENGTH: usize = 22;
+const NAME_LENGTH: usize = 32;
+const MEMO_LENGTH: usize = 255;
+
+pub(crate) fn write(statement: &Statement, out: &mut dyn io::Write) -> io::Result<()> {
+    let mut out = io::BufWriter::new(out);
+    let latest = statement.payments.iter().map(|p| p.timestamp).max();
+    writeln!(
+        out,
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="no"?>"#
+    )?;
+    writeln!(
+        out,
+        r#"<?OFX OFXHEADER="200" VERSION="220" SECURITY="NONE" OLDFILEUID="NONE" NEWFILEUID="NONE"?>"#
+    )?;
+    writeln!(out, "<OFX>")?;
+    writeln!(out, "  <SIGNONMSGSRSV1>")?;
+    writeln!(out, "    <SONRS>")?;
+    status(&mut out, 6)?;
+    writeln!(
+        out,
+        "      <DTSERVER>{}</DTSERVER>",
+        date(latest.unwrap_or_default())
+    )?;
+    writeln!(out, "      <LANGUAGE>ENG</LANGUAGE>")?;
+    writeln!(out, "    </SONRS>")?;
+    writeln!(out, "  </SIGNONMSGSRSV1>")?;
+    writeln!(out, "  <BANKMSGSRSV1>")?;
+    let account = truncate(statement.account.unwrap_or("ECHOPAY"), ACCOUNT_ID_LENGTH);
+    for (index, (currency, payments)) in statement.by_currency().into_iter().enumerate() {
+        let start = payments
+            .iter()
+            .map(|p| p.timestamp)
+            .min()
+            .unwrap_or_default();
+        let end = payments
+            .iter()
+            .map(|p| p.timestamp)
+            .max()
+            .unwrap_or_default();
+        writeln!(out, "    <STMTTRNRS>")?;
+        writeln!(out, "      <TRNUID>{}</TRNUID>", index + 1)?;
+        status(&mut out, 6)?;
+        writeln!(out, "      <STMTRS>")?;
+        writeln!(out, "        <CURDEF>{}</CURDEF>", escape(currency))?;
+        writeln!(out, "        <BANKACCTFROM>")?;
+        writeln!(out, "          <BANKID>ECHOPAY</BANKID>")?;
+        writeln!(out, "          <ACCTID>{}</ACCTID>", escape(account))?;
+        writeln!(out, "          <ACCTTYPE>CHECKING</ACCTTYPE>")?;
+        writeln!(out, "        </BANKACCTFROM>")?;
+        writeln!(out, "        <BANKTRANLIST>")?;
+        writeln!(out, "          <DTSTART>{}</DTSTART>", date(start))?;
+        writeln!(out, "          <DTEND>{}</DTEND>", date(end))?;
+        for payment in &payments {
+            transaction(&mut out, statement, payment)?;
+        }
+        writeln!(out, "        </BANKTRANLIST>")?;
+        writeln!(out, "        <LEDGERBAL>")?;
+        writeln!(
+            out,
+            "          <BALAMT>{}</BALAMT>",
+            net(statement, &payments, currency)
+        )?;
+        writeln!(out, "          <DTASOF>{}</DTASOF>", date(end))?;
+        writeln!(out, "        </LEDGERBAL>")?;
+        writeln!(out, "      </STMTRS>")?;
+        writeln!(out, "    </STMTTRNRS>")?;
+    }
+    writeln!(out, "  </BANKMSGSRSV1>")?;
+    writeln!(out, "</OFX>")?;
+    out.flush()
+}
+
+fn transaction(out: &mut impl Write, statement: &Statement, payment: &Payment) -> io::Result<()> {
+    let credit = statement.is_credit(payment);
+    let amount = format_amount(payment.net_amount(), &payment.currency);
+    writeln!(out, "          <STMTTRN>")?;
+    writeln!(
+        out,
+        "            <TRNTYPE>{}</TRNTYPE>",
+        if credit { "CREDIT" } else { "DEBIT" }
+    )?;
+    writeln!(
+        out,
+        "            <DTPOSTED>{}</DTPOSTED>",
+        date(payment.timestamp)
+    )?;
+    writeln!(
+        out,
+        "            <TRNAMT>{}{amount}</TRNAMT>",
+        if credit { "" } else { "-" }
+    )?;
+    writeln!(out, "            <FITID>{}</FITID>", payment.id)?;
+    writeln!(
+        out,
+        "            <NAME>{}</NAME>",
+        escape(truncate(statement.counterparty(payment), NAME_LENGTH))
+    )?;
+    writeln!(
+        out,
+        "            <MEMO>{}</MEMO>",
+        escape(truncate(&payment.voice_command, MEMO_LENGTH))
+    )?;
+    writeln!(out, "          </STMTTRN>")
+}
+
+fn status(out: &mut impl Write, indent: usize) -> io::Result<()> {
+    let pad = " ".repeat(indent);
+    writeln!(out, "{pad}<STATUS>")?;
+    writeln!(out, "{pad}  <CODE>0</CODE>")?;
+    writeln!(out, "{pad}  <SEVERITY>INFO</SEVERITY>")?;
+    writeln!(out, "{pad}</STATUS>")
+}
+
+/// Credits less debits, in whole units
+fn net(statement: &Statement, payments: &[&Payment], currency: &str) -> String {
+    let (credits, debits) = payments
+        .iter()
+        .fold((0u128, 0u128), |(credits, debits), p| {
+            if statement.is_credit(p) {
+                (credits.saturating_add(p.net_amount()), debits)
+            } else {
+                (credits, debits.saturating_add(p.net_amount()))
+            }
+        });
+    if credits >= debits {
+        format_amount(credits - debits, currency)
+    } else {
+        format!("-{}", format_amount(debits - credits, currency))
+    }
+}
+
+/// OFX's date format, e.g. `20231114221320.000[0:GMT]`
+fn date(timestamp: u64) -> String {
+    datetime(timestamp)
+        .format("%Y%m%d%H%M%S%.3f[0:GMT]")
+        .to_string()
+}
+
+fn truncate(text: &str, chars: usize) -> &str {
+    text.char_indices()
+        .nth(chars)
+        .map_or(text, |(end, _)| &text[..end])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        tests::{payments, render, ALICE},
+        Format, Status,
+    };
+
+    /// The text of every `tag` element, in order
+    fn elements<'a>(ofx: &'a str, tag: &str) -> Vec<&'a str> {
+        let open = format!("<{tag}>");
+        let close = format!("</{tag}>");
+        ofx.split(open.as_str())
+            .skip(1)
+            .map(|rest| rest.split(close.as_str()).next().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn writes_a_statement_per_currency() {
+        let ofx = render(Format::Ofx, &Statement::of(ALICE, &payments()));
+        assert!(ofx.starts_with("<?xml"));
+        assert!(ofx.contains(r#"<?OFX OFXHEADER="200" VERSION="220""#));
+        assert_eq!(elements(&ofx, "CURDEF"), ["DOT", "USDC", "XYZ"]);
+        assert_eq!(elements(&ofx, "ACCTID"), [&ALICE[..22]; 3]);
+        assert_eq!(elements(&ofx, "TRNTYPE"), ["DEBIT", "CREDIT", "DEBIT"]);
+        assert_eq!(
+            elements(&ofx, "TRNAMT"),
+            ["-2.5000000000", "1.250000", "-7"]
+        );
+        assert_eq!(
+            elements(&ofx, "BALAMT"),
+            ["-2.5000000000", "1.250000", "-7"]
+        );
+        assert_eq!(elements(&ofx, "FITID"), ["1", "2", "3"]);
+        assert_eq!(
+            elements(&ofx, "DTPOSTED"),
+            [
+                "20231114221320.000[0:GMT]",
+                "20231114221420.123[0:GMT]",
+                "20231115221320.000[0:GMT]"
+            ]
+        );
+        assert_eq!(elements(&ofx, "DTSERVER"), ["20231115221320.000[0:GMT]"]);
+        assert_eq!(
+            elements(&ofx, "NAME")[1],
+            "5FHneW46xGXgs5mUiveU4sbTyGBzmstU"
+        );
+        assert_eq!(
+            elements(&ofx, "MEMO")[1],
+            "Paga a Alice \"uno coma veinticinco\" USDC &amp; &lt;gracias&gt;"
+        );
+        assert_eq!(ofx.matches("<OFX>").count(), ofx.matches("</OFX>").count());
+    }
+
+    #[test]
+    fn nets_the_ledger_balance() {
+        let mut payments = payments();
+        payments[1].currency = "DOT".into();
+        payments[1].amount = 30_000_000_000;
+        let ofx = render(Format::Ofx, &Statement::of(ALICE, &payments));
+        assert_eq!(elements(&ofx, "BALAMT"), ["0.5000000000", "-7"]);
+
+        let ofx = render(Format::Ofx, &Statement::new(&payments));
+        assert_eq!(elements(&ofx, "BALAMT"), ["-5.5000000000", "-7"]);
+        assert_eq!(elements(&ofx, "ACCTID"), ["ECHOPAY", "ECHOPAY"]);
+    }
+
+    #[test]
+    fn books_payments_net_of_refunds() {
+        let mut payments = payments();
+        payments[0].status = Status::PartiallyRefunded;
+        payments[0].refunded = 5_000_000_000;
+        payments[1].status = Status::Refunded;
+        payments[1].refunded = 1_250_000;
+        payments[2].status = Status::PendingConfirmation;
+        let mut out = Vec::new();
+        let skipped =
+            crate::write(Format::Ofx, &Statement::of(ALICE, &payments), &mut out).unwrap();
+        let ofx = String::from_utf8(out).unwrap();
+        assert_eq!(elements(&ofx, "CURDEF"), ["DOT"]);
+        assert_eq!(elements(&ofx, "TRNAMT"), ["-2.0000000000"]);
+        assert_eq!(elements(&ofx, "BALAMT"), ["-2.0000000000"]);
+        let skipped: Vec<u64> = skipped.iter().map(|s| s.id).collect();
+        assert_eq!(skipped, [2, 3]);
+    }
+
+    #[test]
+    fn truncates_by_characters() {
+        assert_eq!(truncate("añadir", 2), "añ");
+        assert_eq!(truncate("abc", 5), "abc");
+    }
+}
@@ -0,0 +1,85 @@
+//! Quicken Interchange Format
+//!
+//! QIF has no currency field, so each currency gets its own bank account,
+//! named `EchoPay <code>`, declared with an `!Account` record before its
+//! transactions, which are for the amount net of refunds. Dates are ISO 8601
+//! calendar dates, which current importers accept and which cannot be
+//! mistaken for day-month order.
+
+use std::io::{self, Write};
+
+use crate::{datetime, format_amount, Statement};
+
+pub(crate) fn write(statement: &Statement, out: &mut dyn io::Write) -> io::Result<()> {
+    let mut out = io::BufWriter::new(out);
+    for (currency, payments) in statement.by_currency() {
+        writeln!(out, "!Account")?;
+        writeln!(out, "N{}", line(&format!("EchoPay {currency}")))?;
+        writeln!(out, "TBank")?;
+        writeln!(out, "^")?;
+        writeln!(out, "!Type:Bank")?;
+        for payment in payments {
+            let sign = if statement.is_credit(payment) {
+                ""
+            } else {
+                "-"
+            };
+            writeln!(out, "D{}", datetime(payment.timestamp).format("%Y-%m-%d"))?;
+            writeln!(
+                out,
+                "T{sign}{}",
+                format_amount(payment.net_amount(), &payment.currency)
+            )?;
+            writeln!(out, "N{}", payment.id)?;
+            writeln!(out, "P{}", statement.counterparty(payment))?;
+            writeln!(out, "M{}", line(&payment.voice_command))?;
+            writeln!(out, "^")?;
+        }
+    }
+    out.flush()
+}
+
+/// QIF fields end at the line break
+fn line(text: &str) -> String {
+    text.replace(['\r', '\n'], " ")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        tests::{payments, render, ALICE, BOB},
+        Format, Statement, Status,
+    };
+
+    #[test]
+    fn writes_an_account_per_currency() {
+        let mut payments = payments();
+        payments[0].voice_command = "Send 2.5 DOT\nto Bob".into();
+        let qif = render(Format::Qif, &Statement::of(ALICE, &payments[..2]));
+        let expected = format!(
+            "!Account\nNEchoPay DOT\nTBank\n^\n!Type:Bank\n\
+             D2023-11-14\nT-2.5000000000\nN1\nP{BOB}\nMSend 2.5 DOT to Bob\n^\n\
+             !Account\nNEchoPay USDC\nTBank\n^\n!Type:Bank\n\
+             D2023-11-14\nT1.250000\nN2\nP{BOB}\n\
+             MPaga a Alice \"uno coma veinticinco\" USDC & <gracias>\n^\n"
+        );
+        assert_eq!(qif, expected);
+    }
+
+    #[test]
+    fn books_payments_net_of_refunds() {
+        let mut payments = payments();
+        payments[0].status = Status::Disputed;
+        payments[0].refunded = 5_000_000_000;
+        payments[1].status = Status::Refunded;
+        payments[1].refunded = 1_250_000;
+        let qif = render(Format::Qif, &Statement::of(ALICE, &payments[..2]));
+        assert!(qif.contains("\nT-2.0000000000\n"));
+        assert!(!qif.contains("USDC"));
+    }
+
+    #[test]
+    fn writes_nothing_without_payments() {
+        assert_eq!(render(Format::Qif, &Statement::new(&[])), "");
+    }
+}
@@ -0,0 +1,376 @@
+//! Checks the ISO 20022 exports and validates them against the official schemas
+//!
+//! Schema validation runs `xmllint` from libxml2 against the
+//! `pain.001.001.09.xsd` and `camt.053.001.08.xsd` published in the ISO 20022
+//! message catalogue, kept in `tests/schemas`. It is skipped, with a note on
+//! standard error, where `xmllint` is not installed or a schema is missing.
+
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+};
+
+use echopay_export::{write, Format, Payment, Skipped, Statement, Status};
+use roxmltree::{Document, Node};
+
+const ALICE: &str = "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY";
+const BOB: &str = "5FHneW46xGXgs5mUiveU4sbTyGBzmstUspZC92UhjJM694ty";
+const CHARLIE: &str = "5FLSigC9HGRKVhB9FiEo4Y3koPsNmBmLJbpXg2mp1hXcS59Y";
+
+const PAIN_001: &str = "pain.001.001.09";
+const CAMT_053: &str = "camt.053.001.08";
+
+/// The path of `tests/schemas/<schema>.xsd`
+fn schema_path(schema: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/schemas")
+        .join(format!("{schema}.xsd"))
+}
+
+/// Whether `xmllint` can be run
+fn has_xmllint() -> bool {
+    Command::new("xmllint")
+        .arg("--version")
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .is_ok_and(|status| status.success())
+}
+
+/// Checks `xml` against `tests/schemas/<schema>.xsd`, returning what xmllint reports
+fn xmllint(xml: &str, schema: &str) -> Result<(), String> {
+    let path = schema_path(schema);
+    assert!(
+        path.exists(),
+        "{} is missing; download it from the ISO 20022 message catalogue",
+        path.display()
+    );
+    let mut child = Command::new("xmllint")
+        .args(["--noout", "--nonet", "--schema"])
+        .arg(&path)
+        .arg("-")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("xmllint is not installed");
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(xml.as_bytes())
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).into_owned())
+    }
+}
+
+/// Parses a message and checks it is in the schema's namespace
+fn parse<'a>(xml: &'a str, schema: &str) -> Document<'a> {
+    let document = Document::parse(xml).unwrap();
+    assert_eq!(
+        document.root_element().tag_name().namespace(),
+        Some(format!("urn:iso:std:iso:20022:tech:xsd:{schema}").as_str())
+    );
+    document
+}
+
+/// The texts of the descendants of `node` with this path below it
+fn texts<'a>(node: Node<'a, 'a>, path: &str) -> Vec<&'a str> {
+    let mut nodes = vec![node];
+    for name in path.split('/') {
+        nodes = nodes
+            .iter()
+            .flat_map(|n| n.children())
+            .filter(|n| n.tag_name().name() == name)
+            .collect();
+    }
+    nodes.iter().map(|n| n.text().unwrap_or_default()).collect()
+}
+
+fn payment(id: u64, sender: &str, recipient: &str, amount: u128, currency: &str) -> Payment {
+    Payment {
+        id,
+        sender: sender.into(),
+        recipient: recipient.into(),
+        amount,
+        currency: currency.into(),
+        voice_command: format!("Send {amount} {currency} & thanks <3"),
+        locale: Some("en".into()),
+        timestamp: 1_700_000_000_000 + id * 60_000,
+        status: Status::Completed,
+        refunded: 0,
+    }
+}
+
+/// Alice's payments and one to her, over two days and in two currencies
+fn payments() -> Vec<Payment> {
+    let mut payments = vec![
+        payment(1, ALICE, BOB, 25_000_000_000, "DOT"),
+        payment(2, ALICE, CHARLIE, 10_000_000_000, "DOT"),
+        payment(3, BOB, ALICE, 5_000_000_000, "DOT"),
+        payment(4, ALICE, BOB, 120_000_000_000, "KSM"),
+        payment(30, ALICE, BOB, 1_000_000, "DOT"),
+    ];
+    payments[1].voice_command = "pay Charlie one DOT ".repeat(10);
+    payments[4].timestamp += 86_400_000;
+    payments
+}
+
+fn render(format: Format, statement: &Statement) -> std::io::Result<String> {
+    let mut out = Vec::new();
+    let skipped = write(format, statement, &mut out)?;
+    assert_eq!(skipped, []);
+    Ok(String::from_utf8(out).unwrap())
+}
+
+#[test]
+fn writes_credit_transfers() {
+    let payments = payments();
+    let xml = render(Format::Pain001, &Statement::of(ALICE, &payments)).unwrap();
+    let document = parse(&xml, PAIN_001);
+    let initiation = document.root_element().first_element_child().unwrap();
+
+    assert_eq!(texts(initiation, "GrpHdr/MsgId"), ["EP1-1E"]);
+    assert_eq!(
+        texts(initiation, "GrpHdr/CreDtTm"),
+        ["2023-11-15T22:43:20.000Z"]
+    );
+    assert_eq!(texts(initiation, "GrpHdr/NbOfTxs"), ["5"]);
+    assert_eq!(
+        texts(initiation, "PmtInf/Dbtr/Nm"),
+        [ALICE, BOB, ALICE, ALICE]
+    );
+    assert_eq!(
+        texts(initiation, "PmtInf/ReqdExctnDt/Dt"),
+        ["2023-11-14", "2023-11-14", "2023-11-14", "2023-11-15"]
+    );
+    assert_eq!(
+        texts(initiation, "PmtInf/CtrlSum"),
+        ["3.5", "0.5", "0.12", "0.0001"]
+    );
+    assert_eq!(
+        texts(initiation, "PmtInf/CdtTrfTxInf/Amt/InstdAmt"),
+        ["2.5", "1", "0.5", "0.12", "0.0001"]
+    );
+    assert_eq!(
+        texts(initiation, "PmtInf/CdtTrfTxInf/Cdtr/Nm"),
+        [BOB, CHARLIE, ALICE, BOB, BOB]
+    );
+    assert_eq!(
+        texts(initiation, "PmtInf/CdtTrfTxInf/CdtrAcct/Prxy/Id")[0],
+        BOB
+    );
+
+    let remittance = texts(initiation, "PmtInf/CdtTrfTxInf/RmtInf/Ustrd");
+    assert_eq!(remittance[0], "Send 25000000000 DOT & thanks <3");
+    assert_eq!(remittance[1].chars().count(), 140);
+    assert_eq!(
+        format!("{}{}", remittance[1], remittance[2]),
+        payments[1].voice_command
+    );
+}
+
+#[test]
+fn writes_statements() {
+    let payments = payments();
+    let xml = render(Format::Camt053, &Statement::of(ALICE, &payments)).unwrap();
+    let document = parse(&xml, CAMT_053);
+    let statements = document.root_element().first_element_child().unwrap();
+
+    assert_eq!(texts(statements, "Stmt/Id"), ["DOTEP1-1E", "KSMEP4-4"]);
+    assert_eq!(texts(statements, "Stmt/Acct/Prxy/Id"), [ALICE, ALICE]);
+    assert_eq!(texts(statements, "Stmt/Bal/Amt"), ["3.0001", "0.12"]);
+    assert_eq!(texts(statements, "Stmt/Bal/CdtDbtInd"), ["DBIT", "DBIT"]);
+    assert_eq!(
+        texts(statements, "Stmt/Ntry/CdtDbtInd"),
+        ["DBIT", "DBIT", "CRDT", "DBIT", "DBIT"]
+    );
+    assert_eq!(
+        texts(statements, "Stmt/Ntry/BkTxCd/Domn/Fmly/Cd"),
+        ["ICDT", "ICDT", "RCDT", "ICDT", "ICDT"]
+    );
+    assert_eq!(
+        texts(statements, "Stmt/Ntry/BookgDt/DtTm")[2],
+        "2023-11-14T22:16:20.000Z"
+    );
+    assert_eq!(
+        texts(
+            statements,
+            "Stmt/Ntry/NtryDtls/TxDtls/RltdPties/Cdtr/Pty/Nm"
+        )[2],
+        ALICE
+    );
+    let currencies: Vec<&str> = statements
+        .descendants()
+        .filter(|n| n.has_tag_name("Amt"))
+        .filter_map(|n| n.attribute("Ccy"))
+        .collect();
+    assert_eq!(
+        currencies,
+        ["DOT", "DOT", "DOT", "DOT", "DOT", "KSM", "KSM"]
+    );
+}
+
+#[test]
+fn keeps_identifiers_within_35_characters() {
+    let mut payments = [
+        payment(1, ALICE, BOB, 1_000_000, "DOT"),
+        payment(2, BOB, ALICE, 1_000_000, "DOT"),
+    ];
+    payments[0].id = u64::MAX - 1;
+    payments[1].id = u64::MAX;
+    let xml = render(Format::Camt053, &Statement::of(ALICE, &payments)).unwrap();
+    let document = parse(&xml, CAMT_053);
+    let statements = document.root_element().first_element_child().unwrap();
+    assert_eq!(
+        texts(statements, "GrpHdr/MsgId"),
+        ["EPFFFFFFFFFFFFFFFE-FFFFFFFFFFFFFFFF"]
+    );
+    assert_eq!(
+        texts(statements, "Stmt/Id"),
+        ["DOTEPFFFFFFFFFFFFFFFE-FFFFFFFFFFFFF"]
+    );
+
+    let xml = render(Format::Pain001, &Statement::new(&payments)).unwrap();
+    let document = parse(&xml, PAIN_001);
+    let initiation = document.root_element().first_element_child().unwrap();
+    for id in texts(initiation, "PmtInf/PmtInfId") {
+        assert!(id.len() <= 35, "{id}");
+    }
+}
+
+#[test]
+fn writes_statements_without_an_owner() {
+    let payments = payments();
+    let xml = render(Format::Camt053, &Statement::new(&payments)).unwrap();
+    let document = parse(&xml, CAMT_053);
+    let statement = document.root_element().first_element_child().unwrap();
+    assert_eq!(texts(statement, "Stmt/Acct/Id/Othr/Id"), ["NOTPROVIDED"; 2]);
+    assert!(texts(statement, "Stmt/Acct/Prxy").is_empty());
+}
+
+#[test]
+fn skips_what_iso_20022_cannot_represent() {
+    let mut payments = payments();
+    payments.extend([
+        payment(5, ALICE, BOB, 1, "DOT"),
+        payment(6, ALICE, BOB, 1_000_000, "USDC"),
+        payment(7, ALICE, BOB, u128::MAX, "XYZ"),
+    ]);
+    let mut out = Vec::new();
+    let skipped = write(Format::Pain001, &Statement::new(&payments), &mut out).unwrap();
+    let reasons: Vec<(u64, &str)> = skipped
+        .iter()
+        .map(|Skipped { id, reason }| (*id, reason.as_str()))
+        .collect();
+    assert_eq!(
+        reasons,
+        [
+            (5, "0.0000000001 DOT has more than 5 decimal places"),
+            (6, "USDC is not a three-letter currency code"),
+            (
+                7,
+                "340282366920938463463374607431768211455 XYZ has more than 18 digits"
+            ),
+        ]
+    );
+    assert_eq!(
+        skipped[0].to_string(),
+        "payment 5 was not exported: 0.0000000001 DOT has more than 5 decimal places"
+    );
+
+    let xml = String::from_utf8(out).unwrap();
+    let document = parse(&xml, PAIN_001);
+    let initiation = document.root_element().first_element_child().unwrap();
+    assert_eq!(texts(initiation, "GrpHdr/NbOfTxs"), ["5"]);
+
+    let error = |payments: &[Payment]| {
+        write(Format::Camt053, &Statement::new(payments), &mut Vec::new()).unwrap_err()
+    };
+    let nothing_left = error(&payments[5..]);
+    assert_eq!(nothing_left.kind(), std::io::ErrorKind::InvalidData);
+    assert!(nothing_left
+        .to_string()
+        .contains("payment 5 was not exported"));
+    assert!(error(&[]).to_string().contains("at least one payment"));
+}
+
+#[test]
+fn books_payments_net_of_refunds() {
+    let mut payments = payments();
+    payments[0].status = Status::PartiallyRefunded;
+    payments[0].refunded = 5_000_000_000;
+    payments[2].status = Status::Refunded;
+    payments[2].refunded = 5_000_000_000;
+    payments[3].status = Status::PendingConfirmation;
+    payments[4].status = Status::Expired;
+    let statement = Statement::of(ALICE, &payments);
+
+    let mut out = Vec::new();
+    let skipped = write(Format::Pain001, &statement, &mut out).unwrap();
+    let skipped: Vec<u64> = skipped.iter().map(|s| s.id).collect();
+    assert_eq!(skipped, [3, 4, 30]);
+    let xml = String::from_utf8(out).unwrap();
+    let document = parse(&xml, PAIN_001);
+    let initiation = document.root_element().first_element_child().unwrap();
+    assert_eq!(texts(initiation, "GrpHdr/NbOfTxs"), ["2"]);
+    assert_eq!(texts(initiation, "PmtInf/CtrlSum"), ["3"]);
+    assert_eq!(
+        texts(initiation, "PmtInf/CdtTrfTxInf/Amt/InstdAmt"),
+        ["2", "1"]
+    );
+
+    let mut out = Vec::new();
+    let skipped = write(Format::Camt053, &statement, &mut out).unwrap();
+    assert_eq!(skipped.len(), 3);
+    assert_eq!(
+        skipped[0].to_string(),
+        "payment 3 was not exported: it was refunded in full"
+    );
+    let xml = String::from_utf8(out).unwrap();
+    let document = parse(&xml, CAMT_053);
+    let statements = document.root_element().first_element_child().unwrap();
+    assert_eq!(texts(statements, "Stmt/Ntry/NtryRef"), ["1", "2"]);
+    assert_eq!(texts(statements, "Stmt/Ntry/Amt"), ["2", "1"]);
+    assert_eq!(texts(statements, "Stmt/Bal/Amt"), ["3"]);
+}
+
+#[test]
+fn exports_match_the_official_schemas() {
+    if !has_xmllint() {
+        eprintln!("skipping schema validation: xmllint is not installed");
+        return;
+    }
+    let missing: Vec<PathBuf> = [PAIN_001, CAMT_053]
+        .into_iter()
+        .map(schema_path)
+        .filter(|path| !path.exists())
+        .collect();
+    if !missing.is_empty() {
+        eprintln!("skipping schema validation: {missing:?} missing; see tests/schemas/README.md");
+        return;
+    }
+
+    let payments = payments();
+    for (format, schema) in [(Format::Pain001, PAIN_001), (Format::Camt053, CAMT_053)] {
+        for statement in [Statement::of(ALICE, &payments), Statement::new(&payments)] {
+            let xml = render(format, &statement).unwrap();
+            if let Err(errors) = xmllint(&xml, schema) {
+                panic!("{format} does not match {schema}.xsd:\n{errors}");
+            }
+        }
+    }
+
+    // The schemas do catch what the writer could get wrong
+    let xml = render(Format::Pain001, &Statement::new(&payments)).unwrap();
+    let invalid = |from: &str, to: &str| xmllint(&xml.replacen(from, to, 1), PAIN_001);
+    assert!(invalid("<PmtMtd>TRF", "<PmtMtd>SEPA").is_err());
+    assert!(invalid("Ccy=\"DOT\">2.5<", "Ccy=\"DOT\">2.500001<").is_err());
+    assert!(invalid("<MsgId>EP1-1E</MsgId>", "").is_err());
+    assert!(invalid("<PmtMtd>TRF</PmtMtd>", "").is_err());
+}
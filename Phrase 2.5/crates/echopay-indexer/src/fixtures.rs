@@ -0,0 +1,215 @@
+//! Contract metadata and encoded events for tests
+//!
+//! The metadata is generated from mirrors of the contract's event fields,
+//! so the tests need neither a built contract nor a node.
+
+use scale::Encode;
+use scale_info::{MetaType, PortableRegistry, Registry, TypeInfo};
+use serde_json::json;
+use subxt::utils::{AccountId32, H256};
+
+use crate::metadata::ContractMetadata;
+
+/// Signature topic of `PaymentRecorded` in the version 5 metadata
+pub const PAYMENT_RECORDED: H256 = H256([0x11; 32]);
+
+/// Signature topic of `ConfidencePolicyChanged` in the version 5 metadata
+pub const POLICY_CHANGED: H256 = H256([0x22; 32]);
+
+/// Events that change a recorded payment, in declaration order after
+/// `ConfidencePolicyChanged`, with their signature topics
+pub const PAYMENT_CHANGES: [(&str, H256); 6] = [
+    ("PaymentPendingConfirmation", H256([0x40; 32])),
+    ("PaymentConfirmed", H256([0x41; 32])),
+    ("PendingPaymentExpired", H256([0x42; 32])),
+    ("PaymentRefunded", H256([0x43; 32])),
+    ("DisputeOpened", H256([0x44; 32])),
+    ("DisputeClosed", H256([0x45; 32])),
+];
+
+#[derive(TypeInfo, Encode)]
+pub struct AccountId(pub [u8; 32]);
+
+#[derive(TypeInfo, Encode)]
+pub struct ConfidenceTier {
+    pub min_amount: u128,
+    pub min_confidence: u8,
+}
+
+#[derive(TypeInfo, Encode)]
+pub struct ConfidencePolicy {
+    pub min_confidence: u8,
+    pub tiers: Vec<ConfidenceTier>,
+}
+
+/// Every variant, so the encoding matches the contract's
+#[allow(dead_code)]
+#[derive(TypeInfo, Encode)]
+pub enum DisputeOutcome {
+    RefundPayer,
+    RejectClaim,
+}
+
+#[allow(dead_code)]
+#[derive(TypeInfo, Encode)]
+pub enum DisputeStatus {
+    Open,
+    Resolved(DisputeOutcome),
+    Lapsed,
+}
+
+/// The account whose 32 bytes are all `byte`
+pub fn account(byte: u8) -> AccountId32 {
+    AccountId32([byte; 32])
+}
+
+/// Metadata declaring `PaymentRecorded`, `ConfidencePolicyChanged` and then
+/// the [`PAYMENT_CHANGES`]
+pub fn metadata(version: u32) -> ContractMetadata {
+    let mut registry = Registry::new();
+    let mut arg = |label: &str, indexed: bool, ty: MetaType| {
+        json!({
+            "label": label,
+            "indexed": indexed,
+            "type": { "type": registry.register_type(&ty).id, "displayName": [] },
+            "docs": [],
+        })
+    };
+    let payment_args = vec![
+        arg("payment_id", true, MetaType::new::<u64>()),
+        arg("sender", true, MetaType::new::<AccountId>()),
+        arg("recipient", true, MetaType::new::<AccountId>()),
+        arg("amount", false, MetaType::new::<u128>()),
+        arg("currency", false, MetaType::new::<String>()),
+        arg("voice_command", false, MetaType::new::<String>()),
+        arg("locale", false, MetaType::new::<Option<String>>()),
+        arg("timestamp", false, MetaType::new::<u64>()),
+    ];
+    let policy_args = vec![
+        arg("user", true, MetaType::new::<AccountId>()),
+        arg("policy", false, MetaType::new::<Option<ConfidencePolicy>>()),
+    ];
+    let change_args = [
+        vec![
+            arg("payment_id", true, MetaType::new::<u64>()),
+            arg("sender", true, MetaType::new::<AccountId>()),
+            arg("confidence", false, MetaType::new::<u8>()),
+            arg("expires_at", false, MetaType::new::<u64>()),
+        ],
+        vec![
+            arg("payment_id", true, MetaType::new::<u64>()),
+            arg("sender", true, MetaType::new::<AccountId>()),
+        ],
+        vec![
+            arg("payment_id", true, MetaType::new::<u64>()),
+            arg("sender", true, MetaType::new::<AccountId>()),
+        ],
+        vec![
+            arg("original_sender", true, MetaType::new::<AccountId>()),
+            arg("refunder", true, MetaType::new::<AccountId>()),
+            arg("payment_id", false, MetaType::new::<u64>()),
+            arg("amount", false, MetaType::new::<u128>()),
+            arg("total_refunded", false, MetaType::new::<u128>()),
+            arg("reason", false, MetaType::new::<String>()),
+        ],
+        vec![
+            arg("dispute_id", true, MetaType::new::<u64>()),
+            arg("payer", true, MetaType::new::<AccountId>()),
+            arg("recipient", true, MetaType::new::<AccountId>()),
+            arg("payment_id", false, MetaType::new::<u64>()),
+            arg("evidence_hash", false, MetaType::new::<[u8; 32]>()),
+            arg("deadline", false, MetaType::new::<u64>()),
+        ],
+        vec![
+            arg("dispute_id", true, MetaType::new::<u64>()),
+            arg("status", false, MetaType::new::<DisputeStatus>()),
+            arg("refunded", false, MetaType::new::<u128>()),
+        ],
+    ];
+    let topic = |topic: H256| (version >= 5).then(|| format!("{topic:?}"));
+    let types = json!(PortableRegistry::from(registry))["types"].take();
+    let mut events = vec![
+        json!({
+            "label": "PaymentRecorded",
+            "args": payment_args,
+            "signature_topic": topic(PAYMENT_RECORDED),
+            "docs": [],
+        }),
+        json!({
+            "label": "ConfidencePolicyChanged",
+            "args": policy_args,
+            "signature_topic": topic(POLICY_CHANGED),
+            "docs": [],
+        }),
+    ];
+    for ((label, signature), args) in PAYMENT_CHANGES.into_iter().zip(change_args) {
+        events.push(json!({
+            "label": label,
+            "args": args,
+            "signature_topic": topic(signature),
+            "docs": [],
+        }));
+    }
+    let metadata = json!({
+        "version": if version >= 5 { json!(version.to_string()) } else { json!(version) },
+        "types": types,
+        "spec": { "events": events },
+    });
+    ContractMetadata::from_json(&metadata.to_string()).expect("valid metadata")
+}
+
+/// A payment's fields as the contract emits them
+pub struct Payment<'a> {
+    pub payment_id: u64,
+    pub sender: u8,
+    pub recipient: u8,
+    pub amount: u128,
+    pub currency: &'a str,
+    pub locale: Option<&'a str>,
+    pub timestamp: u64,
+}
+
+impl Payment<'_> {
+    /// The topics and data of its `PaymentRecorded` event
+    pub fn event(&self, version: u32) -> (Vec<H256>, Vec<u8>) {
+        let fields = (
+            self.payment_id,
+            AccountId([self.sender; 32]),
+            AccountId([self.recipient; 32]),
+            self.amount,
+            self.currency,
+            format!(
+                "send {} {} to {}",
+                self.amount, self.currency, self.recipient
+            ),
+            self.locale,
+            self.timestamp,
+        );
+        event(version, 0, PAYMENT_RECORDED, fields)
+    }
+}
+
+/// The topics and data of a [`PAYMENT_CHANGES`] event with `fields`
+pub fn payment_change(version: u32, label: &str, fields: impl Encode) -> (Vec<H256>, Vec<u8>) {
+    let index = PAYMENT_CHANGES
+        .iter()
+        .position(|(name, _)| *name == label)
+        .expect("a payment change event");
+    event(version, 2 + index as u8, PAYMENT_CHANGES[index].1, fields)
+}
+
+/// The topics and data of the `index`th declared event, in the given layout
+pub fn event(
+    version: u32,
+    index: u8,
+    signature: H256,
+    fields: impl Encode,
+) -> (Vec<H256>, Vec<u8>) {
+    if version >= 5 {
+        (vec![signature], fields.encode())
+    } else {
+        let mut data = vec![index];
+        fields.encode_to(&mut data);
+        (Vec::new(), data)
+    }
+}
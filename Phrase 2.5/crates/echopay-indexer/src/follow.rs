@@ -0,0 +1,537 @@
+//! Following the chain and keeping the store on its best fork
+//!
+//! The follower imports every new best block. When a block's parent is not
+//! the block stored at the height below, it walks back through parent hashes
+//! until it meets the stored chain, undoes the stored blocks above that
+//! point and imports the new fork in order. Finalized blocks are marked as
+//! such and never undone; a fork below them means the database and node
+//! disagree, which stops the follower rather than rewriting history.
+//!
+//! Reorgs and events the metadata cannot decode do not stop the follower;
+//! they are reported to the caller as [`Notice`]s.
+
+use std::{fmt, future::Future};
+
+use anyhow::{bail, Context, Result};
+use futures::StreamExt;
+use scale::Decode;
+use subxt::{
+    utils::{AccountId32, H256},
+    OnlineClient, PolkadotConfig,
+};
+
+use crate::{
+    metadata::ContractMetadata,
+    store::{BlockRef, IndexedBlock, IndexedEvent, Store},
+};
+
+/// A block with the indexed contract's raw events
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChainBlock {
+    pub number: u64,
+    pub hash: H256,
+    pub parent_hash: H256,
+    pub events: Vec<RawEvent>,
+}
+
+/// The data and topics of a `Contracts::ContractEmitted` event
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RawEvent {
+    /// Position among all of the block's events
+    pub index: u32,
+    pub topics: Vec<H256>,
+    pub data: Vec<u8>,
+}
+
+/// Something the follower did or saw that the caller may want to log
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Notice {
+    /// Stored blocks were undone to switch to another fork
+    Reorg {
+        /// Number of blocks undone
+        undone: usize,
+        /// The highest block kept
+        above: u64,
+        /// The stored head before the reorg
+        head: BlockRef,
+    },
+    /// An event was stored raw because the metadata could not decode it
+    Undecodable {
+        block: u64,
+        /// Position among all of the block's events
+        index: u32,
+        error: String,
+    },
+}
+
+impl fmt::Display for Notice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Notice::Reorg {
+                undone,
+                above,
+                head,
+            } => write!(
+                f,
+                "reorg: undid {undone} block(s) above {above}, up to {} ({:?})",
+                head.number, head.hash
+            ),
+            Notice::Undecodable {
+                block,
+                index,
+                error,
+            } => write!(
+                f,
+                "warning: storing undecodable event {index} of block {block}: {error}"
+            ),
+        }
+    }
+}
+
+/// The outcome of [`Follower::import`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Imported {
+    /// The imported block's number
+    pub number: u64,
+    /// Reorgs and undecodable events met on the way, in order
+    pub notices: Vec<Notice>,
+}
+
+/// Where blocks come from: a node, or a scripted chain in tests
+pub trait Chain {
+    /// The block with `hash`
+    fn block(&self, hash: H256) -> impl Future<Output = Result<ChainBlock>> + Send;
+
+    /// The hash of the best chain's block at `number`, if it has one
+    fn block_hash(&self, number: u64) -> impl Future<Output = Result<Option<H256>>> + Send;
+
+    /// The hash of the latest finalized block
+    fn finalized_head(&self) -> impl Future<Output = Result<H256>> + Send;
+}
+
+/// A node reached over RPC, filtered to one contract's events
+pub struct NodeChain {
+    api: OnlineClient<PolkadotConfig>,
+    contract: AccountId32,
+}
+
+impl NodeChain {
+    /// Connects to the node at `url`
+    pub async fn connect(url: &str, contract: AccountId32) -> Result<Self> {
+        let api = OnlineClient::<PolkadotConfig>::from_url(url)
+            .await
+            .with_context(|| format!("failed to connect to {url}"))?;
+        Ok(Self { api, contract })
+    }
+}
+
+impl Chain for NodeChain {
+    async fn block(&self, hash: H256) -> Result<ChainBlock> {
+        let block = self.api.blocks().at(hash).await?;
+        let mut events = Vec::new();
+        for event in block.events().await?.iter() {
+            let event = event?;
+            if event.pallet_name() != "Contracts" || event.variant_name() != "ContractEmitted" {
+                continue;
+            }
+            let (contract, data) = <(AccountId32, Vec<u8>)>::decode(&mut event.field_bytes())
+                .context("malformed Contracts::ContractEmitted event")?;
+            if contract == self.contract {
+                events.push(RawEvent {
+                    index: event.index(),
+                    topics: event.topics().to_vec(),
+                    data,
+                });
+            }
+        }
+        Ok(ChainBlock {
+            number: block.number().into(),
+            hash,
+            parent_hash: block.header().parent_hash,
+            events,
+        })
+    }
+
+    async fn block_hash(&self, number: u64) -> Result<Option<H256>> {
+        Ok(self.api.rpc().block_hash(Some(number.into())).await?)
+    }
+
+    async fn finalized_head(&self) -> Result<H256> {
+        Ok(self.api.rpc().finalized_head().await?)
+    }
+}
+
+/// Imports blocks from a [`Chain`] into a [`Store`]
+pub struct Follower<C> {
+    chain: C,
+    store: Store,
+    metadata: ContractMetadata,
+    /// First block to index when the store is empty
+    start: u64,
+}
+
+impl<C: Chain> Follower<C> {
+    /// A follower indexing from block `start`, or from where `store` left off
+    pub fn new(chain: C, store: Store, metadata: ContractMetadata, start: u64) -> Self {
+        Self {
+            chain,
+            store,
+            metadata,
+            start,
+        }
+    }
+
+    /// The store being written to
+    pub fn store(&self) -> &Store {
+        &self.store
+    }
+
+    /// Makes the block with `hash` the stored head, undoing stored blocks
+    /// on another fork and importing any missing ancestors
+    ///
+    /// Blocks below the start block are ignored.
+    ///
+    /// # Returns
+    /// * `Result<Imported>` - The block's number and any notices
+    pub async fn import(&mut self, hash: H256) -> Result<Imported> {
+        let finalized = self.store.finalized_head()?;
+        let mut fork = Vec::new();
+        let mut block = self.chain.block(hash).await?;
+        let number = block.number;
+        loop {
+            if block.number < self.start || self.store.block_hash(block.number)? == Some(block.hash)
+            {
+                break;
+            }
+            if let Some(finalized) = finalized.filter(|f| f.number >= block.number) {
+                bail!(
+                    "block {} ({:?}) conflicts with finalized block {} ({:?})",
+                    block.number,
+                    block.hash,
+                    finalized.number,
+                    finalized.hash
+                );
+            }
+            let parent_stored = block.number > self.start
+                && self.store.block_hash(block.number - 1)? == Some(block.parent_hash);
+            let at_start = block.number == self.start;
+            let parent_hash = block.parent_hash;
+            fork.push(block);
+            if parent_stored || at_start {
+                break;
+            }
+            block = self.chain.block(parent_hash).await?;
+        }
+
+        let mut notices = Vec::new();
+        let Some(oldest) = fork.last() else {
+            return Ok(Imported { number, notices });
+        };
+        if let Some(head) = self
+            .store
+            .head()?
+            .filter(|head| head.number >= oldest.number)
+        {
+            let Some(above) = oldest.number.checked_sub(1) else {
+                bail!(
+                    "the node's genesis {:?} is not the indexed one",
+                    oldest.hash
+                );
+            };
+            let undone = self.store.rollback(above)?;
+            notices.push(Notice::Reorg {
+                undone,
+                above,
+                head,
+            });
+        }
+        for block in fork.into_iter().rev() {
+            let indexed = self.decode(block, &mut notices);
+            self.store.insert(&indexed)?;
+        }
+        Ok(Imported { number, notices })
+    }
+
+    /// Imports the block with `hash` and marks it and its ancestors finalized
+    pub async fn finalize(&mut self, hash: H256) -> Result<Imported> {
+        let imported = self.import(hash).await?;
+        self.store.finalize(imported.number)?;
+        Ok(imported)
+    }
+
+    /// Imports the finalized chain block by block, from the stored head or
+    /// the start block up to the node's finalized head
+    ///
+    /// Each block's notices are passed to `notify` as it is imported.
+    pub async fn catch_up(&mut self, mut notify: impl FnMut(Notice)) -> Result<()> {
+        let finalized = self.chain.block(self.chain.finalized_head().await?).await?;
+        let next = match self.store.head()? {
+            Some(head) => head.number + 1,
+            None => self.start,
+        };
+        for number in next..finalized.number {
+            let hash = self
+                .chain
+                .block_hash(number)
+                .await?
+                .with_context(|| format!("the node has no block {number}"))?;
+            self.import(hash)
+                .await?
+                .notices
+                .into_iter()
+                .for_each(&mut notify);
+        }
+        self.finalize(finalized.hash)
+            .await?
+            .notices
+            .into_iter()
+            .for_each(notify);
+        Ok(())
+    }
+
+    fn decode(&self, block: ChainBlock, notices: &mut Vec<Notice>) -> IndexedBlock {
+        let events = block
+            .events
+            .into_iter()
+            .map(|event| {
+                let decoded = match self.metadata.decode_event(&event.topics, &event.data) {
+                    Ok(decoded) => Some(decoded),
+                    Err(error) => {
+                        notices.push(Notice::Undecodable {
+                            block: block.number,
+                            index: event.index,
+                            error: format!("{error:#}"),
+                        });
+                        None
+                    }
+                };
+                IndexedEvent {
+                    index: event.index,
+                    topics: event.topics,
+                    data: event.data,
+                    decoded,
+                }
+            })
+            .collect();
+        IndexedBlock {
+            number: block.number,
+            hash: block.hash,
+            parent_hash: block.parent_hash,
+            events,
+        }
+    }
+}
+
+impl Follower<NodeChain> {
+    /// Catches up to the finalized head, then follows new best and finalized
+    /// blocks until a subscription ends, passing notices to `notify`
+    pub async fn run(&mut self, mut notify: impl FnMut(Notice)) -> Result<()> {
+        self.catch_up(&mut notify).await?;
+        let mut best = self.chain.api.blocks().subscribe_best().await?;
+        let mut finalized = self.chain.api.blocks().subscribe_finalized().await?;
+        loop {
+            tokio::select! {
+                block = best.next() => {
+                    let Some(block) = block else { break };
+                    let imported = self.import(block?.hash()).await?;
+                    imported.notices.into_iter().for_each(&mut notify);
+                }
+                block = finalized.next() => {
+                    let Some(block) = block else { break };
+                    let imported = self.finalize(block?.hash()).await?;
+                    imported.notices.into_iter().for_each(&mut notify);
+                }
+            }
+        }
+        bail!("the node closed the block subscription")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::{
+        fixtures::{self, account, Payment},
+        store::PaymentQuery,
+    };
+
+    /// A chain whose forks are scripted by hand
+    #[derive(Default)]
+    struct FakeChain {
+        blocks: HashMap<H256, ChainBlock>,
+        best: Vec<H256>,
+        finalized: usize,
+    }
+
+    impl FakeChain {
+        /// Adds a block on top of `parent`, or the genesis, and makes it best
+        fn push(&mut self, parent: Option<H256>, fork: u8, payment_ids: &[u64]) -> H256 {
+            let number = parent.map_or(0, |parent| self.blocks[&parent].number + 1);
+            let mut hash = H256::from_low_u64_be(number);
+            hash.0[0] = fork;
+            let events = payment_ids
+                .iter()
+                .enumerate()
+                .map(|(index, &payment_id)| {
+                    let (topics, data) = Payment {
+                        payment_id,
+                        sender: fork,
+                        recipient: 9,
+                        amount: 10,
+                        currency: "DOT",
+                        locale: None,
+                        timestamp: payment_id,
+                    }
+                    .event(4);
+                    RawEvent {
+                        index: index as u32,
+                        topics,
+                        data,
+                    }
+                })
+                .collect();
+            self.blocks.insert(
+                hash,
+                ChainBlock {
+                    number,
+                    hash,
+                    parent_hash: parent.unwrap_or_default(),
+                    events,
+                },
+            );
+            self.best.truncate(number as usize);
+            self.best.push(hash);
+            hash
+        }
+
+        /// Adds a chain of blocks with one payment each
+        fn extend(&mut self, mut parent: Option<H256>, fork: u8, payment_ids: &[u64]) -> H256 {
+            for &payment_id in payment_ids {
+                parent = Some(self.push(parent, fork, &[payment_id]));
+            }
+            parent.unwrap()
+        }
+    }
+
+    impl Chain for &FakeChain {
+        async fn block(&self, hash: H256) -> Result<ChainBlock> {
+            self.blocks.get(&hash).cloned().context("unknown block")
+        }
+
+        async fn block_hash(&self, number: u64) -> Result<Option<H256>> {
+            Ok(self.best.get(number as usize).copied())
+        }
+
+        async fn finalized_head(&self) -> Result<H256> {
+            Ok(self.best[self.finalized])
+        }
+    }
+
+    fn follower(chain: &FakeChain, start: u64) -> Follower<&FakeChain> {
+        let store = Store::open_in_memory(&account(0xcc)).unwrap();
+        Follower::new(chain, store, fixtures::metadata(4), start)
+    }
+
+    fn ids(follower: &Follower<&FakeChain>) -> Vec<u64> {
+        follower
+            .store()
+            .payments(&PaymentQuery::default())
+            .unwrap()
+            .iter()
+            .map(|payment| payment.payment_id)
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn switches_to_the_new_best_fork() {
+        let mut chain = FakeChain::default();
+        let genesis = chain.push(None, 1, &[]);
+        let a1 = chain.extend(Some(genesis), 1, &[1]);
+        let a3 = chain.extend(Some(a1), 1, &[2, 3]);
+        let b4 = chain.extend(Some(a1), 2, &[12, 13, 14]);
+
+        let mut follower = follower(&chain, 0);
+        follower.import(a3).await.unwrap();
+        assert_eq!(ids(&follower), [1, 2, 3]);
+
+        let imported = follower.import(b4).await.unwrap();
+        assert_eq!(imported.number, 4);
+        assert_eq!(
+            imported.notices,
+            [Notice::Reorg {
+                undone: 2,
+                above: 1,
+                head: BlockRef {
+                    number: 3,
+                    hash: a3
+                },
+            }]
+        );
+        assert_eq!(ids(&follower), [1, 12, 13, 14]);
+        assert_eq!(follower.store().head().unwrap().unwrap().hash, b4);
+
+        // Importing a stored block, or back onto the old fork, is just as safe
+        assert_eq!(follower.import(a1).await.unwrap().notices, []);
+        assert_eq!(ids(&follower), [1, 12, 13, 14]);
+        follower.import(a3).await.unwrap();
+        assert_eq!(ids(&follower), [1, 2, 3]);
+    }
+
+    #[tokio::test]
+    async fn refuses_to_undo_finalized_blocks() {
+        let mut chain = FakeChain::default();
+        let genesis = chain.push(None, 1, &[]);
+        let a2 = chain.extend(Some(genesis), 1, &[1, 2]);
+        let b3 = chain.extend(Some(genesis), 2, &[11, 12, 13]);
+
+        let mut follower = follower(&chain, 0);
+        follower.finalize(a2).await.unwrap();
+        let error = follower.import(b3).await.unwrap_err();
+        assert!(error.to_string().contains("finalized"), "{error}");
+        assert_eq!(ids(&follower), [1, 2]);
+        assert_eq!(follower.store().head().unwrap().unwrap().hash, a2);
+    }
+
+    #[tokio::test]
+    async fn catches_up_from_the_start_block() {
+        let mut chain = FakeChain::default();
+        let genesis = chain.push(None, 1, &[]);
+        chain.extend(Some(genesis), 1, &[1, 2, 3, 4, 5]);
+        chain.finalized = 4;
+        let mut undecodable = chain.blocks[&chain.best[3]].clone();
+        undecodable.events[0].data.push(0);
+        chain.blocks.insert(undecodable.hash, undecodable);
+
+        let mut follower = follower(&chain, 2);
+        let mut notices = Vec::new();
+        follower
+            .catch_up(|notice| notices.push(notice))
+            .await
+            .unwrap();
+        assert!(
+            matches!(
+                notices.as_slice(),
+                [Notice::Undecodable {
+                    block: 3,
+                    index: 0,
+                    ..
+                }]
+            ),
+            "{notices:?}"
+        );
+        assert_eq!(ids(&follower), [2, 4]);
+        let finalized = follower.store().finalized_head().unwrap().unwrap();
+        assert_eq!(finalized.hash, chain.best[4]);
+        let events = follower.store().events(None, None).unwrap();
+        assert_eq!(events.len(), 3);
+        assert_eq!(events[1].block_number, 3);
+        assert_eq!(events[1].name, None);
+
+        // Blocks below the start are ignored; later ones carry on from the head
+        follower.import(chain.best[1]).await.unwrap();
+        follower.import(chain.best[5]).await.unwrap();
+        assert_eq!(ids(&follower), [2, 4, 5]);
+    }
+}
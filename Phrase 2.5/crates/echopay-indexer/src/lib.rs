@@ -0,0 +1,14 @@
+//! Indexes the events of a deployed `payment_recorder` contract into SQLite
+//!
+//! [`follow::Follower`] imports blocks from a node, [`metadata`] decodes the
+//! contract's events with the metadata `cargo contract build` writes, and
+//! [`store::Store`] keeps them, with every `PaymentRecorded` event also
+//! stored as a payment that can be queried by sender, recipient, currency
+//! and time range.
+
+pub mod follow;
+pub mod metadata;
+pub mod store;
+
+#[cfg(test)]
+mod fixtures;
@@ -0,0 +1,216 @@
+//! `echopay-indexer` — indexes `payment_recorder` events into SQLite
+//!
+//! `run` follows a node and keeps the database on its best chain; the
+//! other commands query the database and print JSON.
+
+use std::{
+    path::PathBuf,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{Context, Result};
+use clap::{Parser, Subcommand};
+use echopay_export::{Format, Statement};
+use serde::Serialize;
+use subxt::utils::AccountId32;
+
+use echopay_indexer::{
+    follow::{Follower, NodeChain},
+    metadata::ContractMetadata,
+    store::{PaymentQuery, Store},
+};
+
+#[derive(Debug, Parser)]
+#[command(name = "echopay-indexer", version, about)]
+struct Cli {
+    /// Path to the SQLite database, created if missing
+    #[arg(
+        long,
+        global = true,
+        env = "ECHOPAY_INDEX",
+        default_value = "echopay-index.sqlite"
+    )]
+    db: PathBuf,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Follows a node and indexes the contract's events until stopped
+    Run {
+        /// WebSocket endpoint of the node
+        #[arg(long, env = "ECHOPAY_URL", default_value = "ws://127.0.0.1:9944")]
+        url: String,
+        /// SS58 address of the deployed contract
+        #[arg(long, env = "ECHOPAY_CONTRACT")]
+        contract: AccountId32,
+        /// The contract's `.json` or `.contract` metadata from `cargo contract build`
+        #[arg(long)]
+        metadata: PathBuf,
+        /// First block to index when the database is empty, such as the
+        /// block the contract was deployed in
+        #[arg(long, default_value_t = 0)]
+        from_block: u64,
+    },
+    /// Lists indexed payments, oldest first
+    Payments {
+        #[command(flatten)]
+        filter: PaymentFilter,
+    },
+    /// Exports indexed payments for accounting software, oldest first
+    Export {
+        #[command(flatten)]
+        filter: PaymentFilter,
+        /// SS58 address whose statement this is, receiving credits and
+        /// sending debits; defaults to `--sender`, then `--recipient`
+        #[arg(long)]
+        account: Option<AccountId32>,
+        /// File format: csv, jsonl, ofx, qif, pain.001 or camt.053
+        #[arg(long, default_value = "csv")]
+        format: Format,
+        /// File to write; defaults to standard output
+        #[arg(long)]
+        out: Option<PathBuf>,
+    },
+    /// Lists indexed contract events, oldest first
+    Events {
+        /// Only events with this name, e.g. `OwnershipTransferred`
+        #[arg(long)]
+        name: Option<String>,
+        /// Maximum number of events to list
+        #[arg(long)]
+        limit: Option<u32>,
+    },
+    /// Shows the highest indexed and finalized blocks
+    Status,
+}
+
+#[derive(Debug, clap::Args)]
+struct PaymentFilter {
+    /// Only payments sent by this SS58 address
+    #[arg(long)]
+    sender: Option<AccountId32>,
+    /// Only payments received by this SS58 address
+    #[arg(long)]
+    recipient: Option<AccountId32>,
+    /// Only payments in this currency, e.g. DOT
+    #[arg(long)]
+    currency: Option<String>,
+    /// Earliest time, as RFC 3339 or milliseconds since the Unix epoch
+    #[arg(long, value_parser = parse_time)]
+    from: Option<u64>,
+    /// Time before which payments were made, in the same formats
+    #[arg(long, value_parser = parse_time)]
+    until: Option<u64>,
+    /// Only payments in finalized blocks
+    #[arg(long)]
+    finalized: bool,
+    /// Maximum number of payments
+    #[arg(long)]
+    limit: Option<u32>,
+}
+
+impl From<PaymentFilter> for PaymentQuery {
+    fn from(filter: PaymentFilter) -> Self {
+        Self {
+            sender: filter.sender,
+            recipient: filter.recipient,
+            currency: filter.currency,
+            from: filter.from,
+            until: filter.until,
+            finalized_only: filter.finalized,
+            limit: filter.limit,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct Status {
+    head: Option<u64>,
+    finalized: Option<u64>,
+}
+
+/// Milliseconds since the Unix epoch, or an RFC 3339 timestamp
+fn parse_time(value: &str) -> Result<u64> {
+    if let Ok(millis) = value.parse() {
+        return Ok(millis);
+    }
+    let time: SystemTime = humantime::parse_rfc3339_weak(value)
+        .with_context(|| format!("{value} is neither RFC 3339 nor milliseconds"))?;
+    Ok(u64::try_from(time.duration_since(UNIX_EPOCH)?.as_millis())?)
+}
+
+fn print(value: &impl Serialize) -> Result<()> {
+    println!("{}", serde_json::to_string_pretty(value)?);
+    Ok(())
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let cli = Cli::parse();
+    match cli.command {
+        Command::Run {
+            url,
+            contract,
+            metadata,
+            from_block,
+        } => {
+            let metadata = ContractMetadata::load(&metadata)?;
+            let store = Store::open(&cli.db, &contract)?;
+            let chain = NodeChain::connect(&url, contract).await?;
+            Follower::new(chain, store, metadata, from_block)
+                .run(|notice| eprintln!("{notice}"))
+                .await
+        }
+        Command::Payments { filter } => {
+            let store = Store::open_existing(&cli.db)?;
+            print(&store.payments(&filter.into())?)
+        }
+        Command::Export {
+            filter,
+            account,
+            format,
+            out,
+        } => {
+            let store = Store::open_existing(&cli.db)?;
+            let account = account
+                .or_else(|| filter.sender.clone())
+                .or_else(|| filter.recipient.clone())
+                .map(|account| account.to_string());
+            let payments: Vec<echopay_export::Payment> = store
+                .payments(&filter.into())?
+                .iter()
+                .map(Into::into)
+                .collect();
+            let statement = Statement {
+                payments: &payments,
+                account: account.as_deref(),
+            };
+            let skipped = match out {
+                Some(path) => {
+                    let mut file = std::fs::File::create(&path)
+                        .with_context(|| format!("failed to create {}", path.display()))?;
+                    echopay_export::write(format, &statement, &mut file)?
+                }
+                None => echopay_export::write(format, &statement, &mut std::io::stdout().lock())?,
+            };
+            for skipped in skipped {
+                eprintln!("warning: {skipped}");
+            }
+            Ok(())
+        }
+        Command::Events { name, limit } => {
+            let store = Store::open_existing(&cli.db)?;
+            print(&store.events(name.as_deref(), limit)?)
+        }
+        Command::Status => {
+            let store = Store::open_existing(&cli.db)?;
+            print(&Status {
+                head: store.head()?.map(|block| block.number),
+                finalized: store.finalized_head()?.map(|block| block.number),
+            })
+        }
+    }
+}
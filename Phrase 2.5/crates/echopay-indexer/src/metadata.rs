@@ -0,0 +1,493 @@
+//! Decoding contract events with the metadata `cargo contract build` writes
+//!
+//! Events are decoded dynamically from the type registry in the metadata,
+//! so every event the contract declares can be indexed without a hand-kept
+//! mirror of its fields. Both metadata layouts are understood: version 4,
+//! where the event data starts with the event's index in `spec.events`, and
+//! version 5, where the first topic is the event's signature topic.
+
+use std::path::Path;
+
+use anyhow::{anyhow, bail, Context, Result};
+use scale_info::{form::PortableForm, PortableRegistry, PortableType, TypeDef, TypeDefPrimitive};
+use serde::Deserialize;
+use serde_json::{json, Map, Value as Json};
+use subxt::{
+    ext::scale_value::{self, Composite, Primitive, Value, ValueDef},
+    utils::{AccountId32, H256},
+};
+
+/// The parts of a contract's metadata needed to decode its events
+#[derive(Debug, Clone)]
+pub struct ContractMetadata {
+    version: u32,
+    events: Vec<EventSpec>,
+    registry: PortableRegistry,
+}
+
+/// An event the contract declares
+#[derive(Debug, Clone, Deserialize)]
+pub struct EventSpec {
+    /// Name of the event, e.g. `PaymentRecorded`
+    pub label: String,
+    /// Fields in the order they are encoded
+    pub args: Vec<EventArg>,
+    /// Topic identifying the event in version 5 metadata
+    #[serde(default)]
+    pub signature_topic: Option<String>,
+}
+
+/// A field of an event
+#[derive(Debug, Clone, Deserialize)]
+pub struct EventArg {
+    /// Name of the field
+    pub label: String,
+    /// Whether the field is also published as a topic
+    pub indexed: bool,
+    #[serde(rename = "type")]
+    ty: TypeRef,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct TypeRef {
+    #[serde(rename = "type")]
+    id: u32,
+}
+
+/// Layout of the metadata file; other keys are ignored
+#[derive(Deserialize)]
+struct MetadataFile {
+    version: Json,
+    types: Vec<PortableType>,
+    spec: Spec,
+}
+
+#[derive(Deserialize)]
+struct Spec {
+    events: Vec<EventSpec>,
+}
+
+/// A decoded contract event
+#[derive(Debug, Clone, PartialEq)]
+pub struct DecodedEvent {
+    /// Name of the event, e.g. `PaymentRecorded`
+    pub name: String,
+    /// Fields in declaration order, as JSON
+    ///
+    /// Accounts are SS58 addresses, hashes and byte strings are `0x`-prefixed
+    /// hex, `Option`s are the value or `null`, and integers beyond `u64` are
+    /// decimal strings.
+    pub fields: Map<String, Json>,
+}
+
+impl ContractMetadata {
+    /// Parses the `.json` or `.contract` file written by `cargo contract build`
+    pub fn from_json(json: &str) -> Result<Self> {
+        let file: MetadataFile =
+            serde_json::from_str(json).context("malformed contract metadata")?;
+        let version = match &file.version {
+            Json::String(version) => version.parse().ok(),
+            Json::Number(version) => version.as_u64().and_then(|v| u32::try_from(v).ok()),
+            _ => None,
+        }
+        .ok_or_else(|| anyhow!("unrecognized metadata version {}", file.version))?;
+        if !(4..=5).contains(&version) {
+            bail!("metadata version {version} is not supported; expected 4 or 5");
+        }
+        Ok(Self {
+            version,
+            events: file.spec.events,
+            registry: PortableRegistry { types: file.types },
+        })
+    }
+
+    /// Reads and parses a metadata file
+    pub fn load(path: &Path) -> Result<Self> {
+        let json = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        Self::from_json(&json).with_context(|| format!("in {}", path.display()))
+    }
+
+    /// The events the contract declares
+    pub fn events(&self) -> &[EventSpec] {
+        &self.events
+    }
+
+    /// Decodes the data and topics of a `Contracts::ContractEmitted` event
+    pub fn decode_event(&self, topics: &[H256], data: &[u8]) -> Result<DecodedEvent> {
+        let (spec, mut fields) = if self.version >= 5 {
+            let topic = topics.first().map(|topic| format!("{topic:?}"));
+            let spec = self
+                .events
+                .iter()
+                .find(|spec| spec.signature_topic.is_some() && spec.signature_topic == topic)
+                .ok_or_else(|| anyhow!("no event has signature topic {topic:?}"))?;
+            (spec, data)
+        } else {
+            let (index, fields) = data
+                .split_first()
+                .ok_or_else(|| anyhow!("empty event data"))?;
+            let spec = self
+                .events
+                .get(usize::from(*index))
+                .ok_or_else(|| anyhow!("no event has index {index}"))?;
+            (spec, fields)
+        };
+
+        let mut decoded = Map::new();
+        for arg in &spec.args {
+            let value = scale_value::scale::decode_as_type(&mut fields, arg.ty.id, &self.registry)
+                .map_err(|error| anyhow!("{}.{}: {error}", spec.label, arg.label))?;
+            decoded.insert(arg.label.clone(), self.to_json(&value));
+        }
+        if !fields.is_empty() {
+            bail!(
+                "{} bytes left over after decoding {}",
+                fields.len(),
+                spec.label
+            );
+        }
+        Ok(DecodedEvent {
+            name: spec.label.clone(),
+            fields: decoded,
+        })
+    }
+
+    fn to_json(&self, value: &Value<u32>) -> Json {
+        let ty = self.registry.resolve(value.context);
+        let name = ty
+            .and_then(|ty| ty.path.segments.last())
+            .map(String::as_str);
+        if let Some(bytes) = self.bytes(value) {
+            return match (name, <[u8; 32]>::try_from(bytes.as_slice())) {
+                (Some("AccountId"), Ok(account)) => json!(AccountId32(account).to_string()),
+                _ => json!(format!("0x{}", hex::encode(bytes))),
+            };
+        }
+
+        match &value.value {
+            ValueDef::Composite(composite) if is_sequence(ty) => Json::Array(
+                composite
+                    .values()
+                    .map(|value| self.to_json(value))
+                    .collect(),
+            ),
+            ValueDef::Composite(composite) => self.composite_json(composite),
+            ValueDef::Variant(variant) => {
+                let values: Vec<&Value<u32>> = variant.values.values().collect();
+                match (variant.name.as_str(), values.as_slice()) {
+                    ("None", []) if name == Some("Option") => Json::Null,
+                    ("Some", [inner]) if name == Some("Option") => self.to_json(inner),
+                    (variant_name, []) => json!(variant_name),
+                    (variant_name, _) => {
+                        let mut object = Map::new();
+                        object.insert(
+                            variant_name.to_owned(),
+                            self.composite_json(&variant.values),
+                        );
+                        Json::Object(object)
+                    }
+                }
+            }
+            ValueDef::BitSequence(bits) => Json::Array(bits.iter().map(Json::Bool).collect()),
+            ValueDef::Primitive(primitive) => match primitive {
+                Primitive::Bool(flag) => json!(flag),
+                Primitive::Char(c) => json!(c.to_string()),
+                Primitive::String(text) => json!(text),
+                Primitive::U128(n) => {
+                    u64::try_from(*n).map_or_else(|_| json!(n.to_string()), |n| json!(n))
+                }
+                Primitive::I128(n) => {
+                    i64::try_from(*n).map_or_else(|_| json!(n.to_string()), |n| json!(n))
+                }
+                Primitive::U256(bytes) | Primitive::I256(bytes) => {
+                    json!(format!("0x{}", hex::encode(bytes)))
+                }
+            },
+        }
+    }
+
+    /// An object for named fields, the value of a single unnamed field, or
+    /// an array of several
+    fn composite_json(&self, composite: &Composite<u32>) -> Json {
+        match composite {
+            Composite::Named(fields) => Json::Object(
+                fields
+                    .iter()
+                    .map(|(name, value)| (name.clone(), self.to_json(value)))
+                    .collect(),
+            ),
+            Composite::Unnamed(values) => match values.as_slice() {
+                // Newtypes are their content
+                [inner] => self.to_json(inner),
+                values => Json::Array(values.iter().map(|value| self.to_json(value)).collect()),
+            },
+        }
+    }
+
+    /// The bytes of a `[u8; N]`, `Vec<u8>` or a newtype around one
+    fn bytes(&self, value: &Value<u32>) -> Option<Vec<u8>> {
+        let ValueDef::Composite(composite) = &value.value else {
+            return None;
+        };
+        let values: Vec<&Value<u32>> = composite.values().collect();
+        match self.registry.resolve(value.context).map(|ty| &ty.type_def) {
+            Some(TypeDef::Array(array)) if self.is_u8(array.type_param.id) => {}
+            Some(TypeDef::Sequence(sequence)) if self.is_u8(sequence.type_param.id) => {}
+            Some(TypeDef::Composite(_)) if values.len() == 1 => return self.bytes(values[0]),
+            _ => return None,
+        }
+        values
+            .iter()
+            .map(|value| match value.value {
+                ValueDef::Primitive(Primitive::U128(byte)) => u8::try_from(byte).ok(),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn is_u8(&self, id: u32) -> bool {
+        matches!(
+            self.registry.resolve(id).map(|ty| &ty.type_def),
+            Some(TypeDef::Primitive(TypeDefPrimitive::U8))
+        )
+    }
+}
+
+fn is_sequence(ty: Option<&scale_info::Type<PortableForm>>) -> bool {
+    matches!(
+        ty.map(|ty| &ty.type_def),
+        Some(TypeDef::Sequence(_) | TypeDef::Array(_) | TypeDef::Tuple(_))
+    )
+}
+
+/// A `PaymentRecorded` event
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PaymentRecorded {
+    pub payment_id: u64,
+    pub sender: AccountId32,
+    pub recipient: AccountId32,
+    pub amount: u128,
+    pub currency: String,
+    pub voice_command: String,
+    pub locale: Option<String>,
+    pub timestamp: u64,
+}
+
+impl PaymentRecorded {
+    /// Reads the fields of a decoded `PaymentRecorded` event
+    pub fn from_event(event: &DecodedEvent) -> Result<Self> {
+        if event.name != "PaymentRecorded" {
+            bail!("expected PaymentRecorded, got {}", event.name);
+        }
+        let fields = Fields(event);
+        Ok(Self {
+            payment_id: fields.id("payment_id")?,
+            sender: fields.account("sender")?,
+            recipient: fields.account("recipient")?,
+            amount: fields.number("amount")?,
+            currency: fields.text("currency")?,
+            voice_command: fields.text("voice_command")?,
+            locale: match fields.get("locale")? {
+                Json::Null => None,
+                _ => Some(fields.text("locale")?),
+            },
+            timestamp: fields.id("timestamp")?,
+        })
+    }
+}
+
+/// An event that changes the status or refunds of a recorded payment
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PaymentChange {
+    /// `PaymentPendingConfirmation`
+    Pending { payment_id: u64 },
+    /// `PaymentConfirmed`
+    Confirmed { payment_id: u64 },
+    /// `PendingPaymentExpired`
+    Expired { payment_id: u64 },
+    /// `PaymentRefunded`, with the payment's refunds so far
+    Refunded {
+        payment_id: u64,
+        total_refunded: u128,
+    },
+    /// `DisputeOpened`
+    DisputeOpened { dispute_id: u64, payment_id: u64 },
+    /// `DisputeClosed`, which names the dispute but not the payment
+    DisputeClosed { dispute_id: u64 },
+}
+
+impl PaymentChange {
+    /// Reads a decoded event, or `None` if it does not change a payment
+    pub fn from_event(event: &DecodedEvent) -> Result<Option<Self>> {
+        let fields = Fields(event);
+        Ok(Some(match event.name.as_str() {
+            "PaymentPendingConfirmation" => Self::Pending {
+                payment_id: fields.id("payment_id")?,
+            },
+            "PaymentConfirmed" => Self::Confirmed {
+                payment_id: fields.id("payment_id")?,
+            },
+            "PendingPaymentExpired" => Self::Expired {
+                payment_id: fields.id("payment_id")?,
+            },
+            "PaymentRefunded" => Self::Refunded {
+                payment_id: fields.id("payment_id")?,
+                total_refunded: fields.number("total_refunded")?,
+            },
+            "DisputeOpened" => Self::DisputeOpened {
+                dispute_id: fields.id("dispute_id")?,
+                payment_id: fields.id("payment_id")?,
+            },
+            "DisputeClosed" => Self::DisputeClosed {
+                dispute_id: fields.id("dispute_id")?,
+            },
+            _ => return Ok(None),
+        }))
+    }
+}
+
+/// Typed access to the fields of a decoded event
+struct Fields<'a>(&'a DecodedEvent);
+
+impl Fields<'_> {
+    fn get(&self, name: &str) -> Result<&Json> {
+        self.0
+            .fields
+            .get(name)
+            .ok_or_else(|| anyhow!("{} has no `{name}` field", self.0.name))
+    }
+
+    fn text(&self, name: &str) -> Result<String> {
+        self.get(name)?
+            .as_str()
+            .map(str::to_owned)
+            .ok_or_else(|| anyhow!("{}.{name} is not a string", self.0.name))
+    }
+
+    fn number(&self, name: &str) -> Result<u128> {
+        match self.get(name)? {
+            Json::Number(n) => n.as_u64().map(u128::from),
+            Json::String(n) => n.parse().ok(),
+            _ => None,
+        }
+        .ok_or_else(|| anyhow!("{}.{name} is not an unsigned integer", self.0.name))
+    }
+
+    fn id(&self, name: &str) -> Result<u64> {
+        Ok(u64::try_from(self.number(name)?)?)
+    }
+
+    fn account(&self, name: &str) -> Result<AccountId32> {
+        self.text(name)?
+            .parse()
+            .map_err(|_| anyhow!("{}.{name} is not an account", self.0.name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{self, account, ConfidencePolicy, ConfidenceTier, Payment};
+
+    const PAYMENT: Payment<'static> = Payment {
+        payment_id: 7,
+        sender: 1,
+        recipient: 2,
+        amount: 25_000_000_000,
+        currency: "DOT",
+        locale: Some("es"),
+        timestamp: 1_700_000_000_000,
+    };
+
+    #[test]
+    fn decodes_payments_in_both_layouts() {
+        for version in [4, 5] {
+            let metadata = fixtures::metadata(version);
+            let (topics, data) = PAYMENT.event(version);
+            let event = metadata.decode_event(&topics, &data).unwrap();
+            assert_eq!(event.name, "PaymentRecorded");
+            assert_eq!(
+                Json::Object(event.fields.clone()),
+                json!({
+                    "payment_id": 7,
+                    "sender": account(1).to_string(),
+                    "recipient": account(2).to_string(),
+                    "amount": 25_000_000_000u64,
+                    "currency": "DOT",
+                    "voice_command": "send 25000000000 DOT to 2",
+                    "locale": "es",
+                    "timestamp": 1_700_000_000_000u64,
+                })
+            );
+
+            let payment = PaymentRecorded::from_event(&event).unwrap();
+            assert_eq!(payment.sender, account(1));
+            assert_eq!(payment.amount, 25_000_000_000);
+            assert_eq!(payment.locale.as_deref(), Some("es"));
+        }
+    }
+
+    #[test]
+    fn decodes_nested_types() {
+        let policy = Some(ConfidencePolicy {
+            min_confidence: 80,
+            tiers: vec![ConfidenceTier {
+                min_amount: u128::MAX,
+                min_confidence: 95,
+            }],
+        });
+        let (topics, data) = fixtures::event(
+            5,
+            1,
+            fixtures::POLICY_CHANGED,
+            (fixtures::AccountId([3; 32]), policy),
+        );
+        let event = fixtures::metadata(5).decode_event(&topics, &data).unwrap();
+        assert_eq!(event.name, "ConfidencePolicyChanged");
+        assert_eq!(
+            event.fields["policy"],
+            json!({
+                "min_confidence": 80,
+                "tiers": [{ "min_amount": u128::MAX.to_string(), "min_confidence": 95 }],
+            })
+        );
+
+        let (topics, data) = fixtures::event(
+            4,
+            1,
+            fixtures::POLICY_CHANGED,
+            (fixtures::AccountId([3; 32]), None::<ConfidencePolicy>),
+        );
+        let event = fixtures::metadata(4).decode_event(&topics, &data).unwrap();
+        assert_eq!(event.fields["policy"], Json::Null);
+    }
+
+    #[test]
+    fn rejects_unknown_and_malformed_events() {
+        let metadata = fixtures::metadata(5);
+        let (_, data) = PAYMENT.event(5);
+        assert!(metadata.decode_event(&[H256([0x33; 32])], &data).is_err());
+        assert!(metadata.decode_event(&[], &data).is_err());
+
+        let (topics, mut data) = PAYMENT.event(5);
+        data.push(0);
+        assert!(metadata.decode_event(&topics, &data).is_err());
+        assert!(metadata.decode_event(&topics, &data[..10]).is_err());
+
+        let (_, mut data) = PAYMENT.event(4);
+        data[0] = 9;
+        assert!(fixtures::metadata(4).decode_event(&[], &data).is_err());
+    }
+
+    #[test]
+    fn rejects_unsupported_versions() {
+        let json = r#"{ "version": "3", "types": [], "spec": { "events": [] } }"#;
+        assert!(ContractMetadata::from_json(json).is_err());
+        let json = r#"{ "version": 5, "types": [], "spec": { "events": [] } }"#;
+        assert!(ContractMetadata::from_json(json)
+            .unwrap()
+            .events()
+            .is_empty());
+    }
+}
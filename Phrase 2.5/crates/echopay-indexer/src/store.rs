@@ -0,0 +1,878 @@
+//! SQLite storage for indexed blocks, contract events and payments
+//!
+//! Every indexed block is kept with its hash and parent hash, and events and
+//! payments reference the block they were emitted in, so undoing blocks a
+//! reorg replaced deletes what they contributed. Blocks at or below the
+//! finalized height are never undone.
+//!
+//! Events that confirm, expire, refund or dispute a payment are kept as its
+//! changes, from which its status and refunded amount are derived when it is
+//! read, the same way the contract derives them.
+
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+use echopay_export::Status;
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::Serialize;
+use serde_json::Value as Json;
+use subxt::utils::{AccountId32, H256};
+
+use crate::metadata::{DecodedEvent, PaymentChange, PaymentRecorded};
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS meta (
+        key TEXT PRIMARY KEY,
+        value TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS blocks (
+        number INTEGER PRIMARY KEY,
+        hash BLOB NOT NULL UNIQUE,
+        parent_hash BLOB NOT NULL,
+        finalized INTEGER NOT NULL DEFAULT 0
+    );
+    CREATE TABLE IF NOT EXISTS events (
+        block_number INTEGER NOT NULL REFERENCES blocks (number) ON DELETE CASCADE,
+        event_index INTEGER NOT NULL,
+        topics BLOB NOT NULL,
+        data BLOB NOT NULL,
+        name TEXT,
+        fields TEXT,
+        PRIMARY KEY (block_number, event_index)
+    );
+    CREATE INDEX IF NOT EXISTS events_by_name ON events (name);
+    CREATE TABLE IF NOT EXISTS payments (
+        payment_id INTEGER PRIMARY KEY,
+        block_number INTEGER NOT NULL REFERENCES blocks (number) ON DELETE CASCADE,
+        event_index INTEGER NOT NULL,
+        sender TEXT NOT NULL,
+        recipient TEXT NOT NULL,
+        amount TEXT NOT NULL,
+        currency TEXT NOT NULL,
+        voice_command TEXT NOT NULL,
+        locale TEXT,
+        timestamp INTEGER NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS payments_by_sender ON payments (sender, timestamp);
+    CREATE INDEX IF NOT EXISTS payments_by_recipient ON payments (recipient, timestamp);
+    DROP INDEX IF EXISTS payments_by_currency;
+    CREATE INDEX IF NOT EXISTS payments_by_upper_currency ON payments (upper(currency), timestamp);
+    CREATE INDEX IF NOT EXISTS payments_by_timestamp ON payments (timestamp);
+    CREATE TABLE IF NOT EXISTS payment_changes (
+        block_number INTEGER NOT NULL REFERENCES blocks (number) ON DELETE CASCADE,
+        event_index INTEGER NOT NULL,
+        payment_id INTEGER NOT NULL,
+        change TEXT NOT NULL,
+        total_refunded TEXT,
+        dispute_id INTEGER,
+        PRIMARY KEY (block_number, event_index)
+    );
+    CREATE INDEX IF NOT EXISTS payment_changes_by_payment ON payment_changes (payment_id);
+    CREATE INDEX IF NOT EXISTS payment_changes_by_dispute ON payment_changes (dispute_id);
+";
+
+/// A block's number and hash
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockRef {
+    pub number: u64,
+    pub hash: H256,
+}
+
+/// A block and the events the indexed contract emitted in it
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndexedBlock {
+    pub number: u64,
+    pub hash: H256,
+    pub parent_hash: H256,
+    pub events: Vec<IndexedEvent>,
+}
+
+/// A `Contracts::ContractEmitted` event of the indexed contract
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndexedEvent {
+    /// Position among all of the block's events
+    pub index: u32,
+    pub topics: Vec<H256>,
+    /// The SCALE-encoded event, as emitted
+    pub data: Vec<u8>,
+    /// The event decoded with the contract metadata, or `None` if it could
+    /// not be, for instance because the metadata is out of date
+    pub decoded: Option<DecodedEvent>,
+}
+
+/// An indexed payment
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Payment {
+    pub payment_id: u64,
+    pub block_number: u64,
+    pub sender: AccountId32,
+    pub recipient: AccountId32,
+    /// Amount in the currency's smallest unit
+    #[serde(serialize_with = "as_string")]
+    pub amount: u128,
+    pub currency: String,
+    pub voice_command: String,
+    pub locale: Option<String>,
+    /// Milliseconds since the Unix epoch
+    pub timestamp: u64,
+    /// Whether the block the payment was recorded in is finalized
+    pub finalized: bool,
+    pub status: Status,
+    /// Amount refunded to the sender so far
+    #[serde(serialize_with = "as_string")]
+    pub refunded_amount: u128,
+}
+
+impl Payment {
+    /// Applies a stored change, as the contract applied the event
+    fn apply(&mut self, change: &str, total_refunded: Option<u128>) {
+        let settled = |refunded: u128, amount: u128| {
+            if refunded == 0 {
+                Status::Completed
+            } else if refunded >= amount {
+                Status::Refunded
+            } else {
+                Status::PartiallyRefunded
+            }
+        };
+        match change {
+            "pending" => self.status = Status::PendingConfirmation,
+            "expired" => self.status = Status::Expired,
+            "disputed" => self.status = Status::Disputed,
+            "confirmed" | "dispute_closed" => {
+                self.status = settled(self.refunded_amount, self.amount)
+            }
+            "refunded" => {
+                self.refunded_amount = total_refunded.unwrap_or(self.refunded_amount);
+                if self.status != Status::Disputed {
+                    self.status = settled(self.refunded_amount, self.amount);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+impl From<&Payment> for echopay_export::Payment {
+    fn from(payment: &Payment) -> Self {
+        Self {
+            id: payment.payment_id,
+            sender: payment.sender.to_string(),
+            recipient: payment.recipient.to_string(),
+            amount: payment.amount,
+            currency: payment.currency.clone(),
+            voice_command: payment.voice_command.clone(),
+            locale: payment.locale.clone(),
+            timestamp: payment.timestamp,
+            status: payment.status,
+            refunded: payment.refunded_amount,
+        }
+    }
+}
+
+/// A stored contract event
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StoredEvent {
+    pub block_number: u64,
+    pub event_index: u32,
+    /// Name of the event, or `None` if it could not be decoded
+    pub name: Option<String>,
+    pub fields: Option<Json>,
+    /// The SCALE-encoded event, hex-encoded
+    pub data: String,
+    pub finalized: bool,
+}
+
+/// Which payments to return; unset fields match everything
+#[derive(Debug, Clone, Default)]
+pub struct PaymentQuery {
+    pub sender: Option<AccountId32>,
+    pub recipient: Option<AccountId32>,
+    /// Currency code, compared without regard to case
+    pub currency: Option<String>,
+    /// Earliest timestamp, inclusive
+    pub from: Option<u64>,
+    /// Latest timestamp, exclusive
+    pub until: Option<u64>,
+    /// Only payments in finalized blocks
+    pub finalized_only: bool,
+    /// Most payments to return
+    pub limit: Option<u32>,
+}
+
+/// An SQLite database of one contract's events
+pub struct Store {
+    conn: Connection,
+}
+
+impl Store {
+    /// Opens or creates the database at `path` for `contract`
+    ///
+    /// Fails if the database already indexes a different contract.
+    pub fn open(path: &Path, contract: &AccountId32) -> Result<Self> {
+        let conn =
+            Connection::open(path).with_context(|| format!("failed to open {}", path.display()))?;
+        Self::init(conn, contract)
+    }
+
+    /// An empty database held in memory
+    pub fn open_in_memory(contract: &AccountId32) -> Result<Self> {
+        Self::init(Connection::open_in_memory()?, contract)
+    }
+
+    /// Opens an existing database, whichever contract it indexes, to query it
+    pub fn open_existing(path: &Path) -> Result<Self> {
+        let conn = Connection::open_with_flags(path, rusqlite::OpenFlags::SQLITE_OPEN_READ_WRITE)
+            .with_context(|| format!("failed to open {}", path.display()))?;
+        conn.pragma_update(None, "foreign_keys", true)?;
+        Ok(Self { conn })
+    }
+
+    fn init(conn: Connection, contract: &AccountId32) -> Result<Self> {
+        conn.pragma_update(None, "foreign_keys", true)?;
+        conn.execute_batch(SCHEMA)?;
+        let contract = contract.to_string();
+        let indexed: Option<String> = conn
+            .query_row("SELECT value FROM meta WHERE key = 'contract'", [], |row| {
+                row.get(0)
+            })
+            .optional()?;
+        match indexed {
+            Some(indexed) if indexed != contract => {
+                bail!("the database indexes contract {indexed}, not {contract}")
+            }
+            Some(_) => {}
+            None => {
+                conn.execute(
+                    "INSERT INTO meta (key, value) VALUES ('contract', ?1)",
+                    [&contract],
+                )?;
+            }
+        }
+        Ok(Self { conn })
+    }
+
+    /// The highest indexed block
+    pub fn head(&self) -> Result<Option<BlockRef>> {
+        self.block_where("1 ORDER BY number DESC LIMIT 1", [])
+    }
+
+    /// The highest finalized indexed block
+    pub fn finalized_head(&self) -> Result<Option<BlockRef>> {
+        self.block_where("finalized ORDER BY number DESC LIMIT 1", [])
+    }
+
+    /// The hash of the indexed block at `number`
+    pub fn block_hash(&self, number: u64) -> Result<Option<H256>> {
+        Ok(self
+            .block_where("number = ?1", [to_sql(number)?])?
+            .map(|block| block.hash))
+    }
+
+    fn block_where<P: rusqlite::Params>(
+        &self,
+        condition: &str,
+        params: P,
+    ) -> Result<Option<BlockRef>> {
+        let sql = format!("SELECT number, hash FROM blocks WHERE {condition}");
+        Ok(self
+            .conn
+            .query_row(&sql, params, |row| {
+                Ok((row.get::<_, i64>(0)?, row.get::<_, Vec<u8>>(1)?))
+            })
+            .optional()?
+            .map(|(number, hash)| BlockRef {
+                number: number as u64,
+                hash: H256::from_slice(&hash),
+            }))
+    }
+
+    /// Stores a block with its events and the payments they record
+    ///
+    /// The block must be the first indexed or extend the highest one; undo
+    /// replaced blocks with [`Store::rollback`] first.
+    pub fn insert(&mut self, block: &IndexedBlock) -> Result<()> {
+        if let Some(head) = self.head()? {
+            if block.number != head.number + 1 || block.parent_hash != head.hash {
+                bail!(
+                    "block {} ({:?}) does not extend the indexed head {} ({:?})",
+                    block.number,
+                    block.hash,
+                    head.number,
+                    head.hash
+                );
+            }
+        }
+
+        let tx = self.conn.transaction()?;
+        let number = to_sql(block.number)?;
+        tx.execute(
+            "INSERT INTO blocks (number, hash, parent_hash) VALUES (?1, ?2, ?3)",
+            params![number, block.hash.as_bytes(), block.parent_hash.as_bytes()],
+        )?;
+        for event in &block.events {
+            let topics: Vec<u8> = event.topics.iter().flat_map(|t| t.0).collect();
+            let (name, fields) = match &event.decoded {
+                Some(decoded) => (
+                    Some(decoded.name.as_str()),
+                    Some(Json::Object(decoded.fields.clone()).to_string()),
+                ),
+                None => (None, None),
+            };
+            tx.execute(
+                "INSERT INTO events (block_number, event_index, topics, data, name, fields)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![number, event.index, topics, event.data, name, fields],
+            )?;
+
+            let Some(decoded) = &event.decoded else {
+                continue;
+            };
+            if decoded.name != "PaymentRecorded" {
+                let change = PaymentChange::from_event(decoded)
+                    .with_context(|| format!("in block {}", block.number))?;
+                if let Some(change) = change {
+                    insert_change(&tx, number, event.index, change)?;
+                }
+                continue;
+            }
+            let payment = PaymentRecorded::from_event(decoded)
+                .with_context(|| format!("in block {}", block.number))?;
+            tx.execute(
+                "INSERT INTO payments (payment_id, block_number, event_index, sender, recipient,
+                     amount, currency, voice_command, locale, timestamp)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+                params![
+                    to_sql(payment.payment_id)?,
+                    number,
+                    event.index,
+                    payment.sender.to_string(),
+                    payment.recipient.to_string(),
+                    payment.amount.to_string(),
+                    payment.currency,
+                    payment.voice_command,
+                    payment.locale,
+                    to_sql(payment.timestamp)?,
+                ],
+            )?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Undoes every block above `number`, with its events and payments
+    ///
+    /// Fails without changing anything if one of them is finalized.
+    ///
+    /// # Returns
+    /// * `Result<usize>` - How many blocks were undone
+    pub fn rollback(&mut self, number: u64) -> Result<usize> {
+        if let Some(finalized) = self.finalized_head()? {
+            if finalized.number > number {
+                bail!(
+                    "cannot undo finalized block {} ({:?})",
+                    finalized.number,
+                    finalized.hash
+                );
+            }
+        }
+        Ok(self
+            .conn
+            .execute("DELETE FROM blocks WHERE number > ?1", [to_sql(number)?])?)
+    }
+
+    /// Marks the indexed blocks up to and including `number` as finalized
+    pub fn finalize(&mut self, number: u64) -> Result<()> {
+        self.conn.execute(
+            "UPDATE blocks SET finalized = 1 WHERE number <= ?1 AND NOT finalized",
+            [to_sql(number)?],
+        )?;
+        Ok(())
+    }
+
+    /// Payments matching `query`, oldest first
+    pub fn payments(&self, query: &PaymentQuery) -> Result<Vec<Payment>> {
+        let mut sql = String::from(
+            "SELECT p.payment_id, p.block_number, p.sender, p.recipient, p.amount, p.currency,
+                 p.voice_command, p.locale, p.timestamp, b.finalized
+             FROM payments p JOIN blocks b ON b.number = p.block_number
+             WHERE 1",
+        );
+        let mut params: Vec<rusqlite::types::Value> = Vec::new();
+        let mut conditions: Vec<(&str, rusqlite::types::Value)> = Vec::new();
+        if let Some(sender) = &query.sender {
+            conditions.push(("p.sender = ?", sender.to_string().into()));
+        }
+        if let Some(recipient) = &query.recipient {
+            conditions.push(("p.recipient = ?", recipient.to_string().into()));
+        }
+        if let Some(currency) = &query.currency {
+            conditions.push(("upper(p.currency) = upper(?)", currency.clone().into()));
+        }
+        if let Some(from) = query.from {
+            conditions.push(("p.timestamp >= ?", to_sql(from)?.into()));
+        }
+        if let Some(until) = query.until {
+            conditions.push(("p.timestamp < ?", to_sql(until)?.into()));
+        }
+        for (clause, value) in conditions {
+            sql.push_str(" AND ");
+            sql.push_str(clause);
+            params.push(value);
+        }
+        if query.finalized_only {
+            sql.push_str(" AND b.finalized");
+        }
+        sql.push_str(" ORDER BY p.timestamp, p.payment_id");
+        if let Some(limit) = query.limit {
+            sql.push_str(&format!(" LIMIT {limit}"));
+        }
+
+        let mut statement = self.conn.prepare(&sql)?;
+        let rows = statement.query_map(rusqlite::params_from_iter(params), payment_from_row)?;
+        let mut payments: Vec<Payment> = rows.collect::<Result<_, _>>()?;
+
+        let mut changes = self.conn.prepare(
+            "SELECT c.change, c.total_refunded
+             FROM payment_changes c JOIN blocks b ON b.number = c.block_number
+             WHERE c.payment_id = ?1 AND (b.finalized OR NOT ?2)
+             ORDER BY c.block_number, c.event_index",
+        )?;
+        for payment in &mut payments {
+            let mut rows =
+                changes.query(params![to_sql(payment.payment_id)?, query.finalized_only])?;
+            while let Some(row) = rows.next()? {
+                let change: String = row.get(0)?;
+                let total_refunded: Option<String> = row.get(1)?;
+                let total_refunded = total_refunded
+                    .map(|total| total.parse())
+                    .transpose()
+                    .context("malformed refunded amount")?;
+                payment.apply(&change, total_refunded);
+            }
+        }
+        Ok(payments)
+    }
+
+    /// Stored events, oldest first, optionally only those named `name`
+    pub fn events(&self, name: Option<&str>, limit: Option<u32>) -> Result<Vec<StoredEvent>> {
+        let mut statement = self.conn.prepare(
+            "SELECT e.block_number, e.event_index, e.name, e.fields, e.data, b.finalized
+             FROM events e JOIN blocks b ON b.number = e.block_number
+             WHERE ?1 IS NULL OR e.name = ?1
+             ORDER BY e.block_number, e.event_index
+             LIMIT ?2",
+        )?;
+        let limit = limit.map_or(-1, i64::from);
+        let rows = statement.query_map(params![name, limit], |row| {
+            let fields: Option<String> = row.get(3)?;
+            Ok(StoredEvent {
+                block_number: row.get::<_, i64>(0)? as u64,
+                event_index: row.get(1)?,
+                name: row.get(2)?,
+                fields: fields.and_then(|fields| serde_json::from_str(&fields).ok()),
+                data: format!("0x{}", hex::encode(row.get::<_, Vec<u8>>(4)?)),
+                finalized: row.get(5)?,
+            })
+        })?;
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+}
+
+/// Stores how an event changes a payment
+///
+/// A closed dispute is stored against the payment its opening named; one
+/// opened before the first indexed block is ignored.
+fn insert_change(
+    tx: &rusqlite::Transaction<'_>,
+    block_number: i64,
+    event_index: u32,
+    change: PaymentChange,
+) -> Result<()> {
+    let (payment_id, name, total_refunded, dispute_id) = match change {
+        PaymentChange::Pending { payment_id } => (payment_id, "pending", None, None),
+        PaymentChange::Confirmed { payment_id } => (payment_id, "confirmed", None, None),
+        PaymentChange::Expired { payment_id } => (payment_id, "expired", None, None),
+        PaymentChange::Refunded {
+            payment_id,
+            total_refunded,
+        } => (
+            payment_id,
+            "refunded",
+            Some(total_refunded.to_string()),
+            None,
+        ),
+        PaymentChange::DisputeOpened {
+            dispute_id,
+            payment_id,
+        } => (payment_id, "disputed", None, Some(dispute_id)),
+        PaymentChange::DisputeClosed { dispute_id } => {
+            let opened: Option<i64> = tx
+                .query_row(
+                    "SELECT payment_id FROM payment_changes
+                     WHERE change = 'disputed' AND dispute_id = ?1",
+                    [to_sql(dispute_id)?],
+                    |row| row.get(0),
+                )
+                .optional()?;
+            let Some(payment_id) = opened else {
+                return Ok(());
+            };
+            (payment_id as u64, "dispute_closed", None, Some(dispute_id))
+        }
+    };
+    tx.execute(
+        "INSERT INTO payment_changes (block_number, event_index, payment_id, change,
+             total_refunded, dispute_id)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![
+            block_number,
+            event_index,
+            to_sql(payment_id)?,
+            name,
+            total_refunded,
+            dispute_id.map(to_sql).transpose()?,
+        ],
+    )?;
+    Ok(())
+}
+
+fn payment_from_row(row: &Row<'_>) -> rusqlite::Result<Payment> {
+    let account = |index: usize| -> rusqlite::Result<AccountId32> {
+        let text: String = row.get(index)?;
+        text.parse().map_err(|_| {
+            rusqlite::Error::FromSqlConversionFailure(
+                index,
+                rusqlite::types::Type::Text,
+                format!("invalid account {text}").into(),
+            )
+        })
+    };
+    let amount: String = row.get(4)?;
+    Ok(Payment {
+        payment_id: row.get::<_, i64>(0)? as u64,
+        block_number: row.get::<_, i64>(1)? as u64,
+        sender: account(2)?,
+        recipient: account(3)?,
+        amount: amount.parse().map_err(|error| {
+            rusqlite::Error::FromSqlConversionFailure(
+                4,
+                rusqlite::types::Type::Text,
+                Box::new(error),
+            )
+        })?,
+        currency: row.get(5)?,
+        voice_command: row.get(6)?,
+        locale: row.get(7)?,
+        timestamp: row.get::<_, i64>(8)? as u64,
+        finalized: row.get(9)?,
+        status: Status::Completed,
+        refunded_amount: 0,
+    })
+}
+
+/// SQLite integers are signed
+fn to_sql(value: u64) -> Result<i64> {
+    i64::try_from(value).with_context(|| format!("{value} does not fit in an SQLite integer"))
+}
+
+fn as_string<S: serde::Serializer>(value: &u128, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{self, account, AccountId, DisputeOutcome, DisputeStatus, Payment};
+
+    const CONTRACT: AccountId32 = AccountId32([0xcc; 32]);
+
+    fn hash(number: u64) -> H256 {
+        let mut hash = H256::from_low_u64_be(number);
+        hash.0[0] = 1;
+        hash
+    }
+
+    /// Block `number` of a linear chain, recording `payments`
+    fn block(number: u64, payments: &[Payment]) -> IndexedBlock {
+        let events: Vec<_> = payments.iter().map(|payment| payment.event(5)).collect();
+        block_with(number, events)
+    }
+
+    /// Block `number` of a linear chain, emitting `events`
+    fn block_with(number: u64, events: Vec<(Vec<H256>, Vec<u8>)>) -> IndexedBlock {
+        let metadata = fixtures::metadata(5);
+        let events = events
+            .into_iter()
+            .enumerate()
+            .map(|(index, (topics, data))| IndexedEvent {
+                index: index as u32 * 2,
+                decoded: Some(metadata.decode_event(&topics, &data).unwrap()),
+                topics,
+                data,
+            })
+            .collect();
+        IndexedBlock {
+            number,
+            hash: hash(number),
+            parent_hash: hash(number.wrapping_sub(1)),
+            events,
+        }
+    }
+
+    fn payment(payment_id: u64, sender: u8, recipient: u8, currency: &str) -> Payment<'_> {
+        Payment {
+            payment_id,
+            sender,
+            recipient,
+            amount: u128::from(payment_id) * 1_000,
+            currency,
+            locale: None,
+            timestamp: 1_000 * payment_id,
+        }
+    }
+
+    fn ids(store: &Store, query: PaymentQuery) -> Vec<u64> {
+        store
+            .payments(&query)
+            .unwrap()
+            .iter()
+            .map(|payment| payment.payment_id)
+            .collect()
+    }
+
+    fn indexed() -> Store {
+        let mut store = Store::open_in_memory(&CONTRACT).unwrap();
+        store
+            .insert(&block(
+                0,
+                &[payment(1, 1, 2, "DOT"), payment(2, 2, 1, "WND")],
+            ))
+            .unwrap();
+        store.insert(&block(1, &[])).unwrap();
+        store
+            .insert(&block(
+                2,
+                &[payment(3, 1, 3, "dot"), payment(4, 1, 2, "USDC")],
+            ))
+            .unwrap();
+        store
+    }
+
+    #[test]
+    fn queries_payments() {
+        let store = indexed();
+        assert_eq!(ids(&store, PaymentQuery::default()), [1, 2, 3, 4]);
+        let sender = PaymentQuery {
+            sender: Some(account(1)),
+            ..Default::default()
+        };
+        assert_eq!(ids(&store, sender.clone()), [1, 3, 4]);
+        let recipient = PaymentQuery {
+            recipient: Some(account(2)),
+            ..sender.clone()
+        };
+        assert_eq!(ids(&store, recipient), [1, 4]);
+        // The contract keeps currencies as given, so case is ignored on both sides
+        let currency = PaymentQuery {
+            currency: Some("dot".into()),
+            ..Default::default()
+        };
+        assert_eq!(ids(&store, currency), [1, 3]);
+        let currency = PaymentQuery {
+            currency: Some("DOT".into()),
+            ..Default::default()
+        };
+        assert_eq!(ids(&store, currency), [1, 3]);
+        let time = PaymentQuery {
+            from: Some(2_000),
+            until: Some(4_000),
+            ..Default::default()
+        };
+        assert_eq!(ids(&store, time), [2, 3]);
+        let limit = PaymentQuery {
+            limit: Some(1),
+            ..sender
+        };
+        assert_eq!(ids(&store, limit), [1]);
+
+        let payment = &store.payments(&PaymentQuery::default()).unwrap()[2];
+        assert_eq!(payment.block_number, 2);
+        assert_eq!(payment.recipient, account(3));
+        assert_eq!(payment.amount, 3_000);
+        assert_eq!(payment.voice_command, "send 3000 dot to 3");
+        assert!(!payment.finalized);
+
+        let exported = echopay_export::Payment::from(payment);
+        assert_eq!(exported.id, 3);
+        assert_eq!(exported.sender, account(1).to_string());
+        assert_eq!(exported.timestamp, 3_000);
+    }
+
+    #[test]
+    fn queries_events() {
+        let store = indexed();
+        let events = store.events(Some("PaymentRecorded"), None).unwrap();
+        assert_eq!(events.len(), 4);
+        assert_eq!((events[3].block_number, events[3].event_index), (2, 2));
+        assert_eq!(events[3].fields.as_ref().unwrap()["currency"], "USDC");
+        assert!(store.events(Some("InvoicePaid"), None).unwrap().is_empty());
+        assert_eq!(store.events(None, Some(3)).unwrap().len(), 3);
+    }
+
+    #[test]
+    fn rolls_back_unfinalized_blocks() {
+        let mut store = indexed();
+        assert!(store.insert(&block(4, &[])).is_err());
+
+        store.finalize(0).unwrap();
+        assert_eq!(store.finalized_head().unwrap().map(|b| b.number), Some(0));
+        let finalized = PaymentQuery {
+            finalized_only: true,
+            ..Default::default()
+        };
+        assert_eq!(ids(&store, finalized.clone()), [1, 2]);
+
+        assert_eq!(store.rollback(0).unwrap(), 2);
+        assert_eq!(
+            store.head().unwrap(),
+            Some(BlockRef {
+                number: 0,
+                hash: hash(0)
+            })
+        );
+        assert_eq!(ids(&store, PaymentQuery::default()), [1, 2]);
+        assert_eq!(store.events(None, None).unwrap().len(), 2);
+
+        store.insert(&block(1, &[payment(5, 4, 4, "KSM")])).unwrap();
+        assert_eq!(ids(&store, PaymentQuery::default()), [1, 2, 5]);
+        assert_eq!(ids(&store, finalized), [1, 2]);
+
+        store.finalize(1).unwrap();
+        assert!(store.rollback(0).is_err());
+        assert_eq!(ids(&store, PaymentQuery::default()), [1, 2, 5]);
+    }
+
+    #[test]
+    fn derives_status_from_changes() {
+        let mut store = indexed();
+        let refund = |payment_id: u64, amount: u128, total: u128| {
+            let fields = (
+                AccountId([1; 32]),
+                AccountId([2; 32]),
+                payment_id,
+                amount,
+                total,
+                "oops",
+            );
+            fixtures::payment_change(5, "PaymentRefunded", fields)
+        };
+        let status = |store: &Store, query: &PaymentQuery| -> Vec<(Status, u128)> {
+            store
+                .payments(query)
+                .unwrap()
+                .iter()
+                .map(|p| (p.status, p.refunded_amount))
+                .collect()
+        };
+        store
+            .insert(&block_with(
+                3,
+                vec![
+                    refund(1, 400, 400),
+                    refund(2, 2_000, 2_000),
+                    fixtures::payment_change(
+                        5,
+                        "PaymentPendingConfirmation",
+                        (3u64, AccountId([1; 32]), 40u8, 9u64),
+                    ),
+                    fixtures::payment_change(
+                        5,
+                        "PendingPaymentExpired",
+                        (4u64, AccountId([1; 32])),
+                    ),
+                    fixtures::payment_change(
+                        5,
+                        "DisputeOpened",
+                        (
+                            7u64,
+                            AccountId([1; 32]),
+                            AccountId([2; 32]),
+                            1u64,
+                            [0u8; 32],
+                            9u64,
+                        ),
+                    ),
+                ],
+            ))
+            .unwrap();
+        store.finalize(3).unwrap();
+        store
+            .insert(&block_with(
+                4,
+                vec![
+                    refund(1, 100, 500),
+                    fixtures::payment_change(5, "PaymentConfirmed", (3u64, AccountId([1; 32]))),
+                ],
+            ))
+            .unwrap();
+        let all = PaymentQuery::default();
+        assert_eq!(
+            status(&store, &all),
+            [
+                (Status::Disputed, 500),
+                (Status::Refunded, 2_000),
+                (Status::Completed, 0),
+                (Status::Expired, 0),
+            ]
+        );
+        let finalized = PaymentQuery {
+            finalized_only: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            status(&store, &finalized)[..2],
+            [(Status::Disputed, 400), (Status::Refunded, 2_000)]
+        );
+
+        store
+            .insert(&block_with(
+                5,
+                vec![fixtures::payment_change(
+                    5,
+                    "DisputeClosed",
+                    (
+                        7u64,
+                        DisputeStatus::Resolved(DisputeOutcome::RejectClaim),
+                        0u128,
+                    ),
+                )],
+            ))
+            .unwrap();
+        assert_eq!(status(&store, &all)[0], (Status::PartiallyRefunded, 500));
+        let exported = echopay_export::Payment::from(&store.payments(&all).unwrap()[0]);
+        assert_eq!(
+            (exported.status, exported.refunded),
+            (Status::PartiallyRefunded, 500)
+        );
+
+        store.rollback(3).unwrap();
+        assert_eq!(status(&store, &all)[0], (Status::Disputed, 400));
+        assert_eq!(status(&store, &all)[2], (Status::PendingConfirmation, 0));
+    }
+
+    #[test]
+    fn keeps_to_one_contract() {
+        let path =
+            std::env::temp_dir().join(format!("echopay-index-{}.sqlite", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        {
+            let mut store = Store::open(&path, &CONTRACT).unwrap();
+            store.insert(&block(0, &[payment(1, 1, 2, "DOT")])).unwrap();
+        }
+        assert!(Store::open(&path, &account(1)).is_err());
+        let store = Store::open(&path, &CONTRACT).unwrap();
+        assert_eq!(ids(&store, PaymentQuery::default()), [1]);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
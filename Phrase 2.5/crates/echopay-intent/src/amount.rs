@@ -0,0 +1,183 @@
+//! Exact decimal amounts as spoken by the user
+
+use std::{fmt, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+/// A non-negative decimal amount such as `2.5`, kept exact
+///
+/// The value is `digits / 10^scale`, so `2.5` is `{ digits: 25, scale: 1 }`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(into = "String", try_from = "String")]
+pub struct Amount {
+    digits: u128,
+    scale: u8,
+}
+
+/// Reason a string is not a valid amount
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AmountError {
+    /// Not of the form `123` or `123.45`
+    Malformed,
+    /// Too many digits to represent
+    Overflow,
+    /// Finer than the currency's smallest unit, or with no exact decimal form
+    TooPrecise,
+}
+
+impl fmt::Display for AmountError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Malformed => f.write_str("amount must be digits with an optional decimal point"),
+            Self::Overflow => f.write_str("amount has too many digits"),
+            Self::TooPrecise => f.write_str("amount is finer than the currency's smallest unit"),
+        }
+    }
+}
+
+impl std::error::Error for AmountError {}
+
+impl Amount {
+    /// Builds an amount of `digits / 10^scale`
+    pub const fn new(digits: u128, scale: u8) -> Self {
+        Self { digits, scale }
+    }
+
+    /// The amount's digits, without the decimal point
+    pub const fn digits(&self) -> u128 {
+        self.digits
+    }
+
+    /// Number of digits after the decimal point
+    pub const fn scale(&self) -> u8 {
+        self.scale
+    }
+
+    /// Whether the amount is zero
+    pub const fn is_zero(&self) -> bool {
+        self.digits == 0
+    }
+
+    /// Builds the amount `units` of a currency's smallest unit make, in whole units
+    pub fn from_base_units(units: u128, decimals: u8) -> Self {
+        Self::new(units, decimals).normalized()
+    }
+
+    /// Divides the amount by `10^places`, which is always exact
+    pub fn scaled_down(self, places: u8) -> Result<Self, AmountError> {
+        let scale = self
+            .scale
+            .checked_add(places)
+            .ok_or(AmountError::Overflow)?;
+        Ok(Self::new(self.digits, scale).normalized())
+    }
+
+    /// The same amount without trailing zeros after the decimal point
+    pub fn normalized(self) -> Self {
+        let Self {
+            mut digits,
+            mut scale,
+        } = self;
+        while scale > 0 && digits.is_multiple_of(10) {
+            digits /= 10;
+            scale -= 1;
+        }
+        Self { digits, scale }
+    }
+
+    /// Converts to the smallest unit of a currency with `decimals` decimal places
+    ///
+    /// Fails rather than round when the amount has more significant decimal
+    /// places than the currency.
+    pub fn to_base_units(&self, decimals: u8) -> Result<u128, AmountError> {
+        let Self { digits, scale } = self.normalized();
+        let shift = decimals.checked_sub(scale).ok_or(AmountError::TooPrecise)?;
+        10u128
+            .checked_pow(shift.into())
+            .and_then(|factor| digits.checked_mul(factor))
+            .ok_or(AmountError::Overflow)
+    }
+
+    /// Approximate value, for display and scoring only
+    pub fn to_f64(&self) -> f64 {
+        self.digits as f64 / 10f64.powi(self.scale.into())
+    }
+}
+
+impl FromStr for Amount {
+    type Err = AmountError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let (whole, fraction) = text.split_once('.').unwrap_or((text, ""));
+        let all_digits = |part: &str| part.bytes().all(|byte| byte.is_ascii_digit());
+        if whole.is_empty() || !all_digits(whole) || !all_digits(fraction) || text.ends_with('.') {
+            return Err(AmountError::Malformed);
+        }
+
+        let scale = u8::try_from(fraction.len()).map_err(|_| AmountError::Overflow)?;
+        let digits = whole
+            .bytes()
+            .chain(fraction.bytes())
+            .try_fold(0u128, |value, byte| {
+                value.checked_mul(10)?.checked_add(u128::from(byte - b'0'))
+            })
+            .ok_or(AmountError::Overflow)?;
+        Ok(Self { digits, scale })
+    }
+}
+
+impl fmt::Display for Amount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let divisor = 10u128.pow(self.scale.into());
+        let whole = self.digits / divisor;
+        let fraction = self.digits % divisor;
+        if self.scale == 0 {
+            write!(f, "{whole}")
+        } else {
+            write!(
+                f,
+                "{whole}.{fraction:0width$}",
+                width = usize::from(self.scale)
+            )
+        }
+    }
+}
+
+impl From<Amount> for String {
+    fn from(amount: Amount) -> Self {
+        amount.to_string()
+    }
+}
+
+impl TryFrom<String> for Amount {
+    type Error = AmountError;
+
+    fn try_from(text: String) -> Result<Self, Self::Error> {
+        text.parse()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_and_prints_decimals() {
+        let amount: Amount = "2.50".parse().unwrap();
+        assert_eq!(amount, Amount::new(250, 2));
+        assert_eq!(amount.to_string(), "2.50");
+        assert_eq!("0.05".parse::<Amount>().unwrap().to_string(), "0.05");
+        assert_eq!("".parse::<Amount>(), Err(AmountError::Malformed));
+        assert_eq!("1.".parse::<Amount>(), Err(AmountError::Malformed));
+        assert_eq!(".5".parse::<Amount>(), Err(AmountError::Malformed));
+    }
+
+    #[test]
+    fn converts_to_base_units() {
+        let amount: Amount = "2.5".parse().unwrap();
+        assert_eq!(amount.to_base_units(10), Ok(25_000_000_000));
+        assert_eq!(amount.to_base_units(0), Err(AmountError::TooPrecise));
+        assert_eq!("2.000".parse::<Amount>().unwrap().to_base_units(0), Ok(2));
+        assert_eq!(Amount::from_base_units(2_500, 10).to_string(), "0.00000025");
+    }
+}
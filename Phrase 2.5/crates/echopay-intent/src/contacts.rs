@@ -0,0 +1,216 @@
+//! The user's address book and fuzzy matching of spoken names against it
+
+use serde::{Deserialize, Serialize};
+
+use crate::phonetic::double_metaphone;
+
+/// Lowest score a fuzzy match must exceed
+const FUZZY_THRESHOLD: f64 = 0.6;
+
+/// Weight of how a name sounds, against how it is spelled, in a candidate's score
+const PHONETIC_WEIGHT: f64 = 0.6;
+
+/// A named address the user can pay by voice
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Contact {
+    /// Lowercase name the contact is spoken as
+    pub name: String,
+    /// SS58 address of the contact
+    pub address: String,
+}
+
+/// A contact a spoken name may refer to
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Candidate {
+    /// Lowercase name of the contact
+    pub name: String,
+    /// SS58 address of the contact
+    pub address: String,
+    /// How likely the spoken name means this contact, from 0 to 1
+    pub score: f64,
+}
+
+/// Contacts in the order they were added
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ContactBook {
+    contacts: Vec<Contact>,
+}
+
+impl ContactBook {
+    /// An empty address book
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The development accounts Alice, Bob and Charlie, as preloaded by the web app
+    pub fn with_dev_accounts() -> Self {
+        let mut book = Self::new();
+        book.add("alice", "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY");
+        book.add("bob", "5FHneW46xGXgs5mUiveU4sbTyGBzmstUspZC92UhjJM694ty");
+        book.add(
+            "charlie",
+            "5FLSigC9HGRKVhB9FiEo4Y3koPsNmBmLJbpXg2mp1hXcS59Y",
+        );
+        book
+    }
+
+    /// Adds a contact, replacing the address of an existing contact with the same name
+    pub fn add(&mut self, name: &str, address: &str) {
+        let name = name.to_lowercase();
+        match self
+            .contacts
+            .iter_mut()
+            .find(|contact| contact.name == name)
+        {
+            Some(contact) => contact.address = address.to_owned(),
+            None => self.contacts.push(Contact {
+                name,
+                address: address.to_owned(),
+            }),
+        }
+    }
+
+    /// Removes a contact, returning whether it existed
+    pub fn remove(&mut self, name: &str) -> bool {
+        let name = name.to_lowercase();
+        let before = self.contacts.len();
+        self.contacts.retain(|contact| contact.name != name);
+        self.contacts.len() != before
+    }
+
+    /// Looks up a contact by exact name, ignoring case
+    pub fn get(&self, name: &str) -> Option<&Contact> {
+        let name = name.to_lowercase();
+        self.contacts.iter().find(|contact| contact.name == name)
+    }
+
+    /// All contacts in the order they were added
+    pub fn iter(&self) -> impl Iterator<Item = &Contact> {
+        self.contacts.iter()
+    }
+
+    /// Finds the contact whose name is most similar to `name`
+    ///
+    /// This is the first of [`ContactBook::candidates`].
+    pub fn find_fuzzy(&self, name: &str) -> Option<&Contact> {
+        let best = self.candidates(name).into_iter().next()?;
+        self.get(&best.name)
+    }
+
+    /// Contacts `name` may refer to, most likely first
+    ///
+    /// Each contact is scored by spelling and by sound, so that "Jon" finds
+    /// "John" and "Cait" finds "Kate". Only candidates scoring above 0.6 are
+    /// returned; ties keep the order contacts were added in.
+    pub fn candidates(&self, name: &str) -> Vec<Candidate> {
+        let name = name.to_lowercase();
+        let mut candidates: Vec<Candidate> = self
+            .contacts
+            .iter()
+            .map(|contact| Candidate {
+                name: contact.name.clone(),
+                address: contact.address.clone(),
+                score: match_score(&name, &contact.name),
+            })
+            .filter(|candidate| candidate.score > FUZZY_THRESHOLD)
+            .collect();
+        candidates.sort_by(|a, b| b.score.total_cmp(&a.score));
+        candidates
+    }
+}
+
+/// How likely a spoken name means a contact's name, from 0 to 1
+///
+/// Blends spelling and sound similarity, but never scores below the spelling
+/// similarity alone.
+pub fn match_score(spoken: &str, name: &str) -> f64 {
+    let spelling = similarity(spoken, name);
+    let blended =
+        PHONETIC_WEIGHT * phonetic_similarity(spoken, name) + (1.0 - PHONETIC_WEIGHT) * spelling;
+    spelling.max(blended)
+}
+
+/// Similarity of the closest pair of Double Metaphone codes of two names
+pub fn phonetic_similarity(a: &str, b: &str) -> f64 {
+    let a = double_metaphone(a);
+    let b = double_metaphone(b);
+    if a.primary.is_empty() || b.primary.is_empty() {
+        return 0.0;
+    }
+    [&a.primary, &a.alternate]
+        .into_iter()
+        .flat_map(|a| [&b.primary, &b.alternate].map(|b| similarity(a, b)))
+        .fold(0.0, f64::max)
+}
+
+/// Number of single-character edits that turn `a` into `b`
+pub fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(a_char != *b_char);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+    row[b.len()]
+}
+
+/// Edit-distance similarity between 0 (nothing in common) and 1 (equal)
+pub fn similarity(a: &str, b: &str) -> f64 {
+    let longest = a.chars().count().max(b.chars().count());
+    if longest == 0 {
+        return 1.0;
+    }
+    (longest - levenshtein(a, b)) as f64 / longest as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn measures_edit_distance() {
+        assert_eq!(levenshtein("kitten", "sitting"), 3);
+        assert_eq!(levenshtein("", "bob"), 3);
+        assert_eq!(levenshtein("alice", "alice"), 0);
+        assert!((similarity("alise", "alice") - 0.8).abs() < 1e-9);
+    }
+
+    #[test]
+    fn finds_closest_contact() {
+        let book = ContactBook::with_dev_accounts();
+        assert_eq!(book.find_fuzzy("charly").unwrap().name, "charlie");
+        assert_eq!(book.find_fuzzy("Alise").unwrap().name, "alice");
+        assert!(book.find_fuzzy("dave").is_none());
+    }
+
+    #[test]
+    fn ranks_names_that_sound_alike() {
+        let mut book = ContactBook::new();
+        book.add("John", "5DAAnrj7VHTznn2AWBemMuyBwZWs6FNFjdyVXUeYum3PTXFy");
+        book.add("Jan", "5HGjWAeFDfFCWPsjFQdVV2Msvz2XtMktvgocEZcCj68kUMaw");
+        book.add("Kate", "5CiPPseXPECbkjWCa6MnjNokrgYjMqmKndv2rSnekmSK2DjL");
+
+        let candidates = book.candidates("jon");
+        assert_eq!(candidates.len(), 2);
+        assert_eq!(candidates[0].name, "john");
+        assert_eq!(candidates[1].name, "jan");
+        assert!(candidates[0].score > candidates[1].score);
+
+        assert_eq!(book.find_fuzzy("cait").unwrap().name, "kate");
+        assert!(book.candidates("bob").is_empty());
+    }
+
+    #[test]
+    fn adds_and_removes_contacts_ignoring_case() {
+        let mut book = ContactBook::new();
+        book.add("Dave", "5DAAnrj7VHTznn2AWBemMuyBwZWs6FNFjdyVXUeYum3PTXFy");
+        assert!(book.get("dave").is_some());
+        assert!(book.remove("DAVE"));
+        assert!(!book.remove("dave"));
+    }
+}
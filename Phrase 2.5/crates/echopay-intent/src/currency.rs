@@ -0,0 +1,87 @@
+//! Currencies EchoPay accepts and the words users say for them
+
+/// A currency and how it is spoken
+#[derive(Debug, PartialEq, Eq)]
+pub struct Currency {
+    /// Uppercase code recorded with payments, e.g. `DOT`
+    pub code: &'static str,
+    /// Decimal places of the smallest unit
+    pub decimals: u8,
+    /// Lowercase words that name the currency, singular and plural
+    pub words: &'static [&'static str],
+    /// Lowercase words that name its smallest unit
+    pub base_unit_words: &'static [&'static str],
+}
+
+const PLANCK: &[&str] = &["planck", "plancks"];
+const WEI: &[&str] = &["wei"];
+
+/// Every supported currency; DOT is the default when none is spoken
+pub static CURRENCIES: [Currency; 6] = [
+    Currency {
+        code: "DOT",
+        decimals: 10,
+        words: &["dot", "dots", "polkadot", "polkadots"],
+        base_unit_words: PLANCK,
+    },
+    Currency {
+        code: "WND",
+        decimals: 12,
+        words: &["wnd", "westend", "westends"],
+        base_unit_words: PLANCK,
+    },
+    Currency {
+        code: "USDC",
+        decimals: 6,
+        words: &["usdc"],
+        base_unit_words: &[],
+    },
+    Currency {
+        code: "KSM",
+        decimals: 12,
+        words: &["ksm", "kusama", "kusamas"],
+        base_unit_words: PLANCK,
+    },
+    Currency {
+        code: "GLMR",
+        decimals: 18,
+        words: &["glmr", "glimmer", "glimmers"],
+        base_unit_words: WEI,
+    },
+    Currency {
+        code: "ASTR",
+        decimals: 18,
+        words: &["astr", "astar", "astars"],
+        base_unit_words: WEI,
+    },
+];
+
+impl Currency {
+    /// DOT, assumed when no currency is spoken
+    pub fn default_currency() -> &'static Currency {
+        &CURRENCIES[0]
+    }
+
+    /// Looks up a currency by code, ignoring case
+    pub fn by_code(code: &str) -> Option<&'static Currency> {
+        CURRENCIES
+            .iter()
+            .find(|currency| currency.code.eq_ignore_ascii_case(code))
+    }
+
+    /// Looks up a currency by a lowercase word that names it
+    pub fn by_word(word: &str) -> Option<&'static Currency> {
+        CURRENCIES
+            .iter()
+            .find(|currency| currency.words.contains(&word))
+    }
+
+    /// Looks up the first currency whose smallest unit a lowercase word names
+    ///
+    /// "planck" is taken as DOT's and "wei" as GLMR's.
+    pub fn by_base_unit_word(word: &str) -> Option<&'static Currency> {
+        CURRENCIES
+            .iter()
+            .find(|currency| currency.base_unit_words.contains(&word))
+    }
+}
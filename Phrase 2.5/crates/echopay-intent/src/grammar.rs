@@ -0,0 +1,179 @@
+//! The words and patterns of one language's voice commands
+//!
+//! A [`Grammar`] is plain data: the parser tries its [`Rule`] patterns in
+//! order and reads amounts with its [`NumberWords`]. The built-in grammars
+//! cover English, Spanish, Portuguese and Mandarin transcribed as pinyin;
+//! other languages can be added by building a `Grammar` and passing it to
+//! [`Parser::with_grammar`](crate::Parser::with_grammar).
+
+mod en;
+mod es;
+mod pt;
+mod zh;
+
+pub use en::ENGLISH;
+pub use es::SPANISH;
+pub use pt::PORTUGUESE;
+pub use zh::MANDARIN_PINYIN;
+
+/// Every built-in grammar, English first
+pub static GRAMMARS: [&Grammar; 4] = [&ENGLISH, &SPANISH, &PORTUGUESE, &MANDARIN_PINYIN];
+
+/// What a grammar pattern recognises, and the named groups it captures
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rule {
+    /// A payment; captures `amount` and `recipient`, and optionally `currency`
+    Pay,
+    /// Saving a contact; captures `name` and optionally `address`
+    AddContact,
+    /// Deleting a contact; captures `name`
+    RemoveContact,
+    /// Listing the contacts
+    ListContacts,
+    /// Asking for the balance
+    QueryBalance,
+    /// Asking for past transactions
+    QueryHistory,
+    /// Asking for the network's status
+    QueryStatus,
+    /// Opening the settings screen
+    OpenSettings,
+    /// Changing security settings or preferences
+    UpdateSettings,
+    /// Turning a feature on; captures `feature`
+    EnableFeature,
+    /// Turning a feature off; captures `feature`
+    DisableFeature,
+}
+
+/// Commands in one language
+#[derive(Debug, Clone, Copy)]
+pub struct Grammar {
+    /// BCP 47 tag of the language, recorded with the payments it parses
+    pub locale: &'static str,
+    /// Patterns tried in order, the first match deciding the intent
+    ///
+    /// Matching ignores case and runs on the transcript with accents and
+    /// tone marks removed, so patterns are written without them: `envia`
+    /// also matches "envía".
+    pub rules: &'static [(Rule, &'static str)],
+    /// How amounts are spoken
+    pub numbers: NumberWords,
+    /// Names for currencies besides their codes and English names, such as
+    /// `("bo ka", "DOT")`; a name may be several words
+    pub currency_names: &'static [(&'static str, &'static str)],
+    /// Spoken feature names and the English name reported for each
+    pub features: &'static [(&'static str, &'static str)],
+    /// Example commands shown for low-confidence transcripts containing any
+    /// of the paired keywords
+    pub suggestions: &'static [(&'static [&'static str], &'static [&'static str])],
+    /// Example commands shown when no keyword matches
+    pub fallback_suggestions: &'static [&'static str],
+}
+
+/// How a language speaks numbers
+///
+/// All words are lowercase and unaccented.
+#[derive(Debug, Clone, Copy)]
+pub struct NumberWords {
+    /// Separates whole and fractional digits in numerals; the other of `.`
+    /// and `,` groups thousands
+    pub decimal_separator: char,
+    /// Words with a value of their own, such as "five", "twenty", or "a" as
+    /// in "a thousand"
+    pub values: &'static [(&'static str, u128)],
+    /// Words that multiply the number said just before them, or one if
+    /// none was: "hundred", or "shi" and "bai" in Mandarin
+    pub multipliers: &'static [(&'static str, u128)],
+    /// Words that multiply everything said before them: "thousand", "million"
+    pub scales: &'static [(&'static str, u128)],
+    /// Words followed by the digits after the decimal point: "point"
+    pub points: &'static [&'static str],
+    /// Fraction words and their denominators: "half" is 2, "quarter" 4
+    pub fractions: &'static [(&'static str, u128)],
+    /// Words joining a whole number to a fraction, as "and" in "one and a half"
+    pub conjunctions: &'static [&'static str],
+    /// Words that may follow a fraction, as "of a" in "three quarters of a dot"
+    pub fillers: &'static [&'static str],
+}
+
+impl Grammar {
+    /// The built-in grammar for a BCP 47 tag, matched on its language
+    ///
+    /// `es-MX` and `es` both give [`SPANISH`]; any `zh` tag gives
+    /// [`MANDARIN_PINYIN`], as transcripts are expected in pinyin.
+    pub fn for_locale(tag: &str) -> Option<&'static Grammar> {
+        let language = primary_language(tag);
+        GRAMMARS
+            .iter()
+            .copied()
+            .find(|grammar| primary_language(grammar.locale).eq_ignore_ascii_case(language))
+    }
+
+    /// The currency code a lowercase phrase names in this language
+    pub fn currency_code(&self, phrase: &str) -> Option<&'static str> {
+        self.currency_names
+            .iter()
+            .find(|(name, _)| *name == phrase)
+            .map(|(_, code)| *code)
+    }
+}
+
+fn primary_language(tag: &str) -> &str {
+    tag.split(['-', '_']).next().unwrap_or_default()
+}
+
+/// Strips accents and pinyin tone marks, keeping everything else as is
+pub(crate) fn fold(text: &str) -> String {
+    text.chars()
+        .map(|c| {
+            if c.is_ascii() {
+                return c;
+            }
+            let lower = c.to_lowercase().next().unwrap_or(c);
+            let plain = match lower {
+                'á' | 'à' | 'â' | 'ã' | 'ä' | 'ā' | 'ǎ' => 'a',
+                'é' | 'è' | 'ê' | 'ë' | 'ē' | 'ě' => 'e',
+                'í' | 'ì' | 'î' | 'ï' | 'ī' | 'ǐ' => 'i',
+                'ó' | 'ò' | 'ô' | 'õ' | 'ö' | 'ō' | 'ǒ' => 'o',
+                'ú' | 'ù' | 'û' | 'ü' | 'ū' | 'ǔ' | 'ǖ' | 'ǘ' | 'ǚ' | 'ǜ' => 'u',
+                'ç' => 'c',
+                'ñ' => 'n',
+                _ => return c,
+            };
+            if c.is_uppercase() {
+                plain.to_ascii_uppercase()
+            } else {
+                plain
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn picks_grammars_by_language() {
+        assert_eq!(Grammar::for_locale("es-MX").map(|g| g.locale), Some("es"));
+        assert_eq!(Grammar::for_locale("pt_BR").map(|g| g.locale), Some("pt"));
+        assert_eq!(
+            Grammar::for_locale("zh-CN").map(|g| g.locale),
+            Some(MANDARIN_PINYIN.locale)
+        );
+        assert_eq!(Grammar::for_locale("EN").map(|g| g.locale), Some("en"));
+        assert!(Grammar::for_locale("fr-FR").is_none());
+    }
+
+    #[test]
+    fn folds_accents_and_tone_marks() {
+        assert_eq!(fold("Envía cinco DOT"), "Envia cinco DOT");
+        assert_eq!(
+            fold("Transfira três vírgula cinco"),
+            "Transfira tres virgula cinco"
+        );
+        assert_eq!(fold("zhuǎn wǔ gè DOT gěi Lǚ"), "zhuan wu ge DOT gei Lu");
+        assert_eq!(fold("Añade"), "Anade");
+    }
+}
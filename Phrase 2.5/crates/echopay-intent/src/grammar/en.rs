@@ -0,0 +1,177 @@
+//! English, the web app's grammar
+
+use super::{Grammar, NumberWords, Rule};
+
+/// English commands: "send 5 DOT to Alice", "give Bob two thousand planck"
+pub static ENGLISH: Grammar = Grammar {
+    locale: "en",
+    rules: &[
+        // The web app's payment patterns, then the same shapes with the
+        // amount spoken in words ("send half a dot to Bob")
+        (
+            Rule::Pay,
+            r"(?:send|pay|transfer)\s+(?P<amount>\d+(?:\.\d+)?)\s*(?P<currency>dot|wnd|usdc|ksm)?\s+to\s+(?P<recipient>[a-zA-Z0-9]+)",
+        ),
+        (
+            Rule::Pay,
+            r"(?:give|send)\s+(?P<recipient>[a-zA-Z0-9]+)\s+(?P<amount>\d+(?:\.\d+)?)\s*(?P<currency>dot|wnd|usdc|ksm)?",
+        ),
+        (
+            Rule::Pay,
+            r"(?:transfer|pay)\s+(?P<amount>\d+(?:\.\d+)?)\s+(?:dollars?\s+worth\s+of\s+)?(?P<currency>dot|wnd|usdc|ksm)?\s+to\s+(?P<recipient>[a-zA-Z0-9]+)",
+        ),
+        (
+            Rule::Pay,
+            r"(?:send|pay|transfer|give)\s+(?P<amount>.+?)\s+to\s+(?P<recipient>[a-zA-Z0-9]+)",
+        ),
+        (
+            Rule::Pay,
+            r"(?:give|send)\s+(?P<recipient>[a-zA-Z0-9]+)\s+(?P<amount>.+)",
+        ),
+        (
+            Rule::AddContact,
+            r"(?:add|create)\s+contact\s+(?P<name>[a-zA-Z0-9]+)(?:\s+with\s+address\s+(?P<address>[a-zA-Z0-9]+))?",
+        ),
+        (
+            Rule::RemoveContact,
+            r"(?:remove|delete)\s+contact\s+(?P<name>[a-zA-Z0-9]+)",
+        ),
+        (
+            Rule::ListContacts,
+            r"(?:show|list|display)\s+(?:my\s+)?contacts",
+        ),
+        (
+            Rule::QueryBalance,
+            r"(?:what|show|check|display)(?:'s|\s+is)?\s+my\s+balance",
+        ),
+        (
+            Rule::QueryHistory,
+            r"(?:show|display|list)\s+(?:my\s+)?(?:transaction\s+)?history",
+        ),
+        (
+            Rule::QueryStatus,
+            r"(?:check|show|what)(?:'s|\s+is)?\s+(?:the\s+)?(?:network\s+)?status",
+        ),
+        (
+            Rule::QueryBalance,
+            r"(?:how\s+much|what)\s+(?:money|funds|balance)\s+(?:do\s+i\s+have|have\s+i)",
+        ),
+        (Rule::OpenSettings, r"(?:open|show|go\s+to)\s+settings"),
+        (
+            Rule::UpdateSettings,
+            r"(?:change|update|modify)\s+(?:my\s+)?(?:security|preferences)",
+        ),
+        (
+            Rule::DisableFeature,
+            r"(?:disable|turn\s+off)\s+(?P<feature>biometric|voice\s+verification)",
+        ),
+        (
+            Rule::EnableFeature,
+            r"(?:enable|turn\s+on)\s+(?P<feature>biometric|voice\s+verification)",
+        ),
+    ],
+    numbers: NumberWords {
+        decimal_separator: '.',
+        values: &[
+            ("zero", 0),
+            ("oh", 0),
+            ("o", 0),
+            ("a", 1),
+            ("an", 1),
+            ("one", 1),
+            ("two", 2),
+            ("three", 3),
+            ("four", 4),
+            ("five", 5),
+            ("six", 6),
+            ("seven", 7),
+            ("eight", 8),
+            ("nine", 9),
+            ("ten", 10),
+            ("eleven", 11),
+            ("twelve", 12),
+            ("thirteen", 13),
+            ("fourteen", 14),
+            ("fifteen", 15),
+            ("sixteen", 16),
+            ("seventeen", 17),
+            ("eighteen", 18),
+            ("nineteen", 19),
+            ("twenty", 20),
+            ("thirty", 30),
+            ("forty", 40),
+            ("fifty", 50),
+            ("sixty", 60),
+            ("seventy", 70),
+            ("eighty", 80),
+            ("ninety", 90),
+        ],
+        multipliers: &[("hundred", 100), ("hundreds", 100)],
+        scales: &[
+            ("thousand", 1_000),
+            ("thousands", 1_000),
+            ("k", 1_000),
+            ("million", 1_000_000),
+            ("millions", 1_000_000),
+            ("billion", 1_000_000_000),
+            ("billions", 1_000_000_000),
+        ],
+        points: &["point"],
+        fractions: &[
+            ("half", 2),
+            ("halves", 2),
+            ("third", 3),
+            ("thirds", 3),
+            ("quarter", 4),
+            ("quarters", 4),
+            ("fifth", 5),
+            ("fifths", 5),
+            ("tenth", 10),
+            ("tenths", 10),
+            ("hundredth", 100),
+            ("hundredths", 100),
+            ("thousandth", 1_000),
+            ("thousandths", 1_000),
+        ],
+        conjunctions: &["and"],
+        fillers: &["of", "a", "an"],
+    },
+    currency_names: &[],
+    features: &[
+        ("biometric", "biometric"),
+        ("voice verification", "voice verification"),
+    ],
+    suggestions: &[
+        (
+            &[
+                "send", "pay", "transfer", "0", "1", "2", "3", "4", "5", "6", "7", "8", "9",
+            ],
+            &[
+                r#"Try: "Send 5 DOT to Alice""#,
+                r#"Try: "Pay 10 WND to Bob""#,
+                r#"Try: "Transfer 2.5 DOT to Charlie""#,
+            ],
+        ),
+        (
+            &["balance", "check", "show", "what"],
+            &[
+                r#"Try: "What's my balance?""#,
+                r#"Try: "Show transaction history""#,
+                r#"Try: "Check network status""#,
+            ],
+        ),
+        (
+            &["contact", "add", "list"],
+            &[
+                r#"Try: "Add contact Alice""#,
+                r#"Try: "Show my contacts""#,
+                r#"Try: "Remove contact Bob""#,
+            ],
+        ),
+    ],
+    fallback_suggestions: &[
+        r#"Try: "Send 5 DOT to Alice""#,
+        r#"Try: "What's my balance?""#,
+        r#"Try: "Show my contacts""#,
+    ],
+};
@@ -0,0 +1,199 @@
+//! Spanish
+
+use super::{Grammar, NumberWords, Rule};
+
+/// Spanish commands: "envía cinco DOT a Alice", "paga a Bob dos coma cinco WND"
+pub static SPANISH: Grammar = Grammar {
+    locale: "es",
+    rules: &[
+        (
+            Rule::Pay,
+            r"\b(?:envia|enviale|enviar|envie|manda|mandale|mandar|paga|pagale|pagar|transfiere|transferir|dale)\s+a\s+(?P<recipient>[a-zA-Z0-9]+)\s+(?P<amount>.+)",
+        ),
+        (
+            Rule::Pay,
+            r"\b(?:envia|enviar|envie|manda|mandar|paga|pagar|transfiere|transferir|da)\s+(?P<amount>.+?)\s+a\s+(?P<recipient>[a-zA-Z0-9]+)",
+        ),
+        (
+            Rule::AddContact,
+            r"\b(?:agrega|agregar|anade|anadir|crea|crear|guarda|guardar)\s+(?:el\s+|un\s+)?contacto\s+(?P<name>[a-zA-Z0-9]+)(?:\s+con\s+(?:la\s+)?direccion\s+(?P<address>[a-zA-Z0-9]+))?",
+        ),
+        (
+            Rule::RemoveContact,
+            r"\b(?:elimina|eliminar|borra|borrar|quita|quitar)\s+(?:el\s+)?contacto\s+(?P<name>[a-zA-Z0-9]+)",
+        ),
+        (
+            Rule::ListContacts,
+            r"\b(?:muestra|mostrar|muestrame|lista|listar|ver)\s+(?:mis\s+|los\s+)?contactos",
+        ),
+        (
+            Rule::QueryBalance,
+            r"\b(?:cual\s+es\s+|muestra\s+|muestrame\s+|ver\s+|consulta\s+|consultar\s+)(?:mi\s+|el\s+)?saldo",
+        ),
+        (
+            Rule::QueryHistory,
+            r"\b(?:muestra|muestrame|mostrar|ver|consulta)\s+(?:mi\s+|el\s+)?historial",
+        ),
+        (
+            Rule::QueryStatus,
+            r"\b(?:estado|situacion)\s+de\s+(?:la\s+)?red",
+        ),
+        (Rule::QueryBalance, r"\bcuanto\s+(?:dinero|saldo)\s+tengo"),
+        (
+            Rule::OpenSettings,
+            r"\b(?:abre|abrir|ir\s+a|ve\s+a|muestra)\s+(?:la\s+)?(?:configuracion|ajustes)",
+        ),
+        (
+            Rule::UpdateSettings,
+            r"\b(?:cambia|cambiar|actualiza|actualizar|modifica|modificar)\s+(?:mi\s+|mis\s+|la\s+|las\s+)?(?:seguridad|preferencias)",
+        ),
+        (
+            Rule::DisableFeature,
+            r"\b(?:desactiva|desactivar|deshabilita|deshabilitar|apaga|apagar)\s+(?:la\s+)?(?P<feature>biometria|verificacion\s+(?:de|por)\s+voz)",
+        ),
+        (
+            Rule::EnableFeature,
+            r"\b(?:activa|activar|habilita|habilitar|enciende|encender)\s+(?:la\s+)?(?P<feature>biometria|verificacion\s+(?:de|por)\s+voz)",
+        ),
+    ],
+    numbers: NumberWords {
+        decimal_separator: ',',
+        values: &[
+            ("cero", 0),
+            ("un", 1),
+            ("una", 1),
+            ("uno", 1),
+            ("dos", 2),
+            ("tres", 3),
+            ("cuatro", 4),
+            ("cinco", 5),
+            ("seis", 6),
+            ("siete", 7),
+            ("ocho", 8),
+            ("nueve", 9),
+            ("diez", 10),
+            ("once", 11),
+            ("doce", 12),
+            ("trece", 13),
+            ("catorce", 14),
+            ("quince", 15),
+            ("dieciseis", 16),
+            ("diecisiete", 17),
+            ("dieciocho", 18),
+            ("diecinueve", 19),
+            ("veinte", 20),
+            ("veintiun", 21),
+            ("veintiuno", 21),
+            ("veintiuna", 21),
+            ("veintidos", 22),
+            ("veintitres", 23),
+            ("veinticuatro", 24),
+            ("veinticinco", 25),
+            ("veintiseis", 26),
+            ("veintisiete", 27),
+            ("veintiocho", 28),
+            ("veintinueve", 29),
+            ("treinta", 30),
+            ("cuarenta", 40),
+            ("cincuenta", 50),
+            ("sesenta", 60),
+            ("setenta", 70),
+            ("ochenta", 80),
+            ("noventa", 90),
+            ("cien", 100),
+            ("ciento", 100),
+            ("doscientos", 200),
+            ("doscientas", 200),
+            ("trescientos", 300),
+            ("trescientas", 300),
+            ("cuatrocientos", 400),
+            ("cuatrocientas", 400),
+            ("quinientos", 500),
+            ("quinientas", 500),
+            ("seiscientos", 600),
+            ("seiscientas", 600),
+            ("setecientos", 700),
+            ("setecientas", 700),
+            ("ochocientos", 800),
+            ("ochocientas", 800),
+            ("novecientos", 900),
+            ("novecientas", 900),
+        ],
+        multipliers: &[],
+        scales: &[
+            ("mil", 1_000),
+            ("k", 1_000),
+            ("millon", 1_000_000),
+            ("millones", 1_000_000),
+        ],
+        points: &["coma", "punto"],
+        fractions: &[
+            ("medio", 2),
+            ("media", 2),
+            ("tercio", 3),
+            ("tercios", 3),
+            ("cuarto", 4),
+            ("cuartos", 4),
+            ("quinto", 5),
+            ("quintos", 5),
+            ("decimo", 10),
+            ("decimos", 10),
+            ("centesimo", 100),
+            ("centesimos", 100),
+        ],
+        conjunctions: &["y"],
+        fillers: &["de", "un", "una"],
+    },
+    currency_names: &[],
+    features: &[
+        ("biometria", "biometric"),
+        ("verificacion de voz", "voice verification"),
+        ("verificacion por voz", "voice verification"),
+    ],
+    suggestions: &[
+        (
+            &[
+                "envia",
+                "paga",
+                "transfiere",
+                "manda",
+                "0",
+                "1",
+                "2",
+                "3",
+                "4",
+                "5",
+                "6",
+                "7",
+                "8",
+                "9",
+            ],
+            &[
+                r#"Prueba: "Envía 5 DOT a Alice""#,
+                r#"Prueba: "Paga 10 WND a Bob""#,
+                r#"Prueba: "Transfiere 2,5 DOT a Charlie""#,
+            ],
+        ),
+        (
+            &["saldo", "muestra", "ver", "cual", "historial"],
+            &[
+                r#"Prueba: "¿Cuál es mi saldo?""#,
+                r#"Prueba: "Muestra mi historial""#,
+                r#"Prueba: "Estado de la red""#,
+            ],
+        ),
+        (
+            &["contacto", "agrega", "lista"],
+            &[
+                r#"Prueba: "Agrega el contacto Alice""#,
+                r#"Prueba: "Muestra mis contactos""#,
+                r#"Prueba: "Elimina el contacto Bob""#,
+            ],
+        ),
+    ],
+    fallback_suggestions: &[
+        r#"Prueba: "Envía 5 DOT a Alice""#,
+        r#"Prueba: "¿Cuál es mi saldo?""#,
+        r#"Prueba: "Muestra mis contactos""#,
+    ],
+};
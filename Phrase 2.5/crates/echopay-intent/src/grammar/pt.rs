@@ -0,0 +1,182 @@
+//! Portuguese, as spoken in Brazil and Portugal
+
+use super::{Grammar, NumberWords, Rule};
+
+/// Portuguese commands: "envie cinco DOT para a Alice", "pague dois vírgula cinco WND pro Bob"
+pub static PORTUGUESE: Grammar = Grammar {
+    locale: "pt",
+    rules: &[
+        (
+            Rule::Pay,
+            r"\b(?:envia|enviar|envie|manda|mandar|mande|paga|pagar|pague|transfere|transferir|transfira)\s+(?:para|pra|pro)\s+(?:o\s+|a\s+)?(?P<recipient>[a-zA-Z0-9]+)\s+(?P<amount>.+)",
+        ),
+        (
+            Rule::Pay,
+            r"\b(?:envia|enviar|envie|manda|mandar|mande|paga|pagar|pague|transfere|transferir|transfira)\s+(?P<amount>.+?)\s+(?:para|pra|pro|ao|a)\s+(?:o\s+|a\s+)?(?P<recipient>[a-zA-Z0-9]+)",
+        ),
+        (
+            Rule::AddContact,
+            r"\b(?:adiciona|adicionar|adicione|cria|criar|crie|salva|salvar|salve)\s+(?:o\s+|um\s+)?contato\s+(?P<name>[a-zA-Z0-9]+)(?:\s+com\s+(?:o\s+)?endereco\s+(?P<address>[a-zA-Z0-9]+))?",
+        ),
+        (
+            Rule::RemoveContact,
+            r"\b(?:remove|remover|remova|apaga|apagar|apague|exclui|excluir|exclua)\s+(?:o\s+)?contato\s+(?P<name>[a-zA-Z0-9]+)",
+        ),
+        (
+            Rule::ListContacts,
+            r"\b(?:mostra|mostrar|mostre|lista|listar|liste|ver)\s+(?:os\s+)?(?:meus\s+)?contatos",
+        ),
+        (
+            Rule::QueryBalance,
+            r"\b(?:qual\s+e\s+|mostra\s+|mostrar\s+|mostre\s+|ver\s+|consulta\s+|consultar\s+|consulte\s+)(?:o\s+)?(?:meu\s+)?saldo",
+        ),
+        (
+            Rule::QueryHistory,
+            r"\b(?:mostra|mostrar|mostre|ver|consulta|consulte)\s+(?:o\s+)?(?:meu\s+)?historico",
+        ),
+        (
+            Rule::QueryStatus,
+            r"\b(?:status|estado|situacao)\s+da\s+rede",
+        ),
+        (
+            Rule::QueryBalance,
+            r"\bquanto\s+(?:dinheiro|saldo)\s+(?:eu\s+)?tenho",
+        ),
+        (
+            Rule::OpenSettings,
+            r"\b(?:abre|abrir|abra|ir\s+para|va\s+para|mostra|mostre)\s+(?:as\s+)?(?:configuracoes|definicoes)",
+        ),
+        (
+            Rule::UpdateSettings,
+            r"\b(?:muda|mudar|mude|altera|alterar|altere|atualiza|atualizar|atualize)\s+(?:a\s+|as\s+|minha\s+|minhas\s+)?(?:seguranca|preferencias)",
+        ),
+        (
+            Rule::DisableFeature,
+            r"\b(?:desativa|desativar|desative|desliga|desligar|desligue)\s+(?:a\s+)?(?P<feature>biometria|verificacao\s+(?:de|por)\s+voz)",
+        ),
+        (
+            Rule::EnableFeature,
+            r"\b(?:ativa|ativar|ative|liga|ligar|ligue)\s+(?:a\s+)?(?P<feature>biometria|verificacao\s+(?:de|por)\s+voz)",
+        ),
+    ],
+    numbers: NumberWords {
+        decimal_separator: ',',
+        values: &[
+            ("zero", 0),
+            ("um", 1),
+            ("uma", 1),
+            ("dois", 2),
+            ("duas", 2),
+            ("tres", 3),
+            ("quatro", 4),
+            ("cinco", 5),
+            ("seis", 6),
+            ("sete", 7),
+            ("oito", 8),
+            ("nove", 9),
+            ("dez", 10),
+            ("onze", 11),
+            ("doze", 12),
+            ("treze", 13),
+            ("catorze", 14),
+            ("quatorze", 14),
+            ("quinze", 15),
+            ("dezesseis", 16),
+            ("dezasseis", 16),
+            ("dezessete", 17),
+            ("dezassete", 17),
+            ("dezoito", 18),
+            ("dezenove", 19),
+            ("dezanove", 19),
+            ("vinte", 20),
+            ("trinta", 30),
+            ("quarenta", 40),
+            ("cinquenta", 50),
+            ("sessenta", 60),
+            ("setenta", 70),
+            ("oitenta", 80),
+            ("noventa", 90),
+            ("cem", 100),
+            ("cento", 100),
+            ("duzentos", 200),
+            ("duzentas", 200),
+            ("trezentos", 300),
+            ("trezentas", 300),
+            ("quatrocentos", 400),
+            ("quatrocentas", 400),
+            ("quinhentos", 500),
+            ("quinhentas", 500),
+            ("seiscentos", 600),
+            ("seiscentas", 600),
+            ("setecentos", 700),
+            ("setecentas", 700),
+            ("oitocentos", 800),
+            ("oitocentas", 800),
+            ("novecentos", 900),
+            ("novecentas", 900),
+        ],
+        multipliers: &[],
+        scales: &[
+            ("mil", 1_000),
+            ("k", 1_000),
+            ("milhao", 1_000_000),
+            ("milhoes", 1_000_000),
+        ],
+        points: &["virgula", "ponto"],
+        fractions: &[
+            ("meio", 2),
+            ("meia", 2),
+            ("terco", 3),
+            ("tercos", 3),
+            ("quarto", 4),
+            ("quartos", 4),
+            ("quinto", 5),
+            ("quintos", 5),
+            ("decimo", 10),
+            ("decimos", 10),
+            ("centesimo", 100),
+            ("centesimos", 100),
+        ],
+        conjunctions: &["e"],
+        fillers: &["de", "um", "uma"],
+    },
+    currency_names: &[],
+    features: &[
+        ("biometria", "biometric"),
+        ("verificacao de voz", "voice verification"),
+        ("verificacao por voz", "voice verification"),
+    ],
+    suggestions: &[
+        (
+            &[
+                "envi", "pag", "transf", "mand", "0", "1", "2", "3", "4", "5", "6", "7", "8", "9",
+            ],
+            &[
+                r#"Tente: "Envie 5 DOT para Alice""#,
+                r#"Tente: "Pague 10 WND para Bob""#,
+                r#"Tente: "Transfira 2,5 DOT para Charlie""#,
+            ],
+        ),
+        (
+            &["saldo", "mostr", "ver", "qual", "historico"],
+            &[
+                r#"Tente: "Qual é o meu saldo?""#,
+                r#"Tente: "Mostre o meu histórico""#,
+                r#"Tente: "Status da rede""#,
+            ],
+        ),
+        (
+            &["contato", "adicion", "lista"],
+            &[
+                r#"Tente: "Adicione o contato Alice""#,
+                r#"Tente: "Mostre os meus contatos""#,
+                r#"Tente: "Remova o contato Bob""#,
+            ],
+        ),
+    ],
+    fallback_suggestions: &[
+        r#"Tente: "Envie 5 DOT para Alice""#,
+        r#"Tente: "Qual é o meu saldo?""#,
+        r#"Tente: "Mostre os meus contatos""#,
+    ],
+};
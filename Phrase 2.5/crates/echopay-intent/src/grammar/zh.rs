@@ -0,0 +1,125 @@
+//! Mandarin, transcribed as pinyin
+//!
+//! Syllables may be written with or without tone marks. Command words may
+//! run together ("lianxiren" or "lian xi ren"), but the syllables of a
+//! spoken number must be separated by spaces, as speech recognisers emit
+//! them: "er shi wu" is 25.
+
+use super::{Grammar, NumberWords, Rule};
+
+/// Mandarin commands: "zhuan wu ge DOT gei Alice" (转五个DOT给Alice),
+/// "gei Bob fa liang bai WND" (给Bob发两百WND)
+pub static MANDARIN_PINYIN: Grammar = Grammar {
+    locale: "zh-Latn-pinyin",
+    rules: &[
+        (
+            Rule::Pay,
+            r"\b(?:zhuan\s*zhang|zhuan|fa\s*song|fa|zhi\s*fu|fu)\s*gei\s+(?P<recipient>[a-zA-Z0-9]+)\s+(?P<amount>.+)",
+        ),
+        (
+            Rule::Pay,
+            r"\bgei\s+(?P<recipient>[a-zA-Z0-9]+)\s+(?:zhuan\s*zhang|zhuan|fa\s*song|fa|zhi\s*fu|fu)\s+(?P<amount>.+)",
+        ),
+        (
+            Rule::Pay,
+            r"\b(?:zhuan\s*zhang|zhuan|fa\s*song|fa|zhi\s*fu|fu)\s+(?P<amount>.+?)\s+gei\s+(?P<recipient>[a-zA-Z0-9]+)",
+        ),
+        (
+            Rule::AddContact,
+            r"\b(?:tian\s*jia|xin\s*jian|bao\s*cun)\s*lian\s*xi\s*ren\s+(?P<name>[a-zA-Z0-9]+)(?:\s+di\s*zhi\s+(?P<address>[a-zA-Z0-9]+))?",
+        ),
+        (
+            Rule::RemoveContact,
+            r"\b(?:shan\s*chu|yi\s*chu)\s*lian\s*xi\s*ren\s+(?P<name>[a-zA-Z0-9]+)",
+        ),
+        (
+            Rule::ListContacts,
+            r"\b(?:xian\s*shi|cha\s*kan|lie\s*chu)\s*(?:wo\s*de\s*)?(?:suo\s*you\s*)?lian\s*xi\s*ren",
+        ),
+        (Rule::QueryBalance, r"\byu\s*e\b"),
+        (
+            Rule::QueryHistory,
+            r"\b(?:jiao\s*yi\s*(?:ji\s*lu|li\s*shi)|li\s*shi\s*ji\s*lu)",
+        ),
+        (Rule::QueryStatus, r"\bwang\s*luo\s*zhuang\s*tai"),
+        (Rule::OpenSettings, r"\bda\s*kai\s*she\s*zhi"),
+        (
+            Rule::UpdateSettings,
+            r"\b(?:geng\s*gai|xiu\s*gai|geng\s*xin)\s*(?:wo\s*de\s*)?(?:an\s*quan|pian\s*hao)",
+        ),
+        (
+            Rule::DisableFeature,
+            r"\b(?:guan\s*bi|jin\s*yong)\s*(?P<feature>sheng\s*wu\s*shi\s*bie|yu\s*yin\s*yan\s*zheng)",
+        ),
+        (
+            Rule::EnableFeature,
+            r"\b(?:kai\s*qi|qi\s*yong|da\s*kai)\s*(?P<feature>sheng\s*wu\s*shi\s*bie|yu\s*yin\s*yan\s*zheng)",
+        ),
+    ],
+    numbers: NumberWords {
+        decimal_separator: '.',
+        values: &[
+            ("ling", 0),
+            ("yi", 1),
+            ("er", 2),
+            ("liang", 2),
+            ("san", 3),
+            ("si", 4),
+            ("wu", 5),
+            ("liu", 6),
+            ("qi", 7),
+            ("ba", 8),
+            ("jiu", 9),
+        ],
+        multipliers: &[("shi", 10), ("bai", 100), ("qian", 1_000)],
+        scales: &[("wan", 10_000), ("k", 1_000)],
+        points: &["dian"],
+        fractions: &[("ban", 2)],
+        // The measure word in "yi ge ban" (一个半, one and a half) and "wu ge DOT"
+        conjunctions: &["ge"],
+        fillers: &["ge"],
+    },
+    currency_names: &[
+        ("bo ka", "DOT"),
+        ("boka", "DOT"),
+        ("bo ka bi", "DOT"),
+        ("ku sa ma", "KSM"),
+    ],
+    features: &[
+        ("sheng wu shi bie", "biometric"),
+        ("yu yin yan zheng", "voice verification"),
+    ],
+    suggestions: &[
+        (
+            &[
+                "zhuan", "fa", "fu", "gei", "0", "1", "2", "3", "4", "5", "6", "7", "8", "9",
+            ],
+            &[
+                r#"试试: "zhuan wu ge DOT gei Alice""#,
+                r#"试试: "gei Bob fa shi ge WND""#,
+                r#"试试: "zhuan er dian wu ge DOT gei Charlie""#,
+            ],
+        ),
+        (
+            &["cha", "xian shi", "yu e", "ji lu"],
+            &[
+                r#"试试: "cha kan yu e""#,
+                r#"试试: "xian shi jiao yi ji lu""#,
+                r#"试试: "wang luo zhuang tai""#,
+            ],
+        ),
+        (
+            &["lian xi ren", "lianxiren", "tian jia"],
+            &[
+                r#"试试: "tian jia lian xi ren Alice""#,
+                r#"试试: "xian shi lian xi ren""#,
+                r#"试试: "shan chu lian xi ren Bob""#,
+            ],
+        ),
+    ],
+    fallback_suggestions: &[
+        r#"试试: "zhuan wu ge DOT gei Alice""#,
+        r#"试试: "cha kan yu e""#,
+        r#"试试: "xian shi lian xi ren""#,
+    ],
+};
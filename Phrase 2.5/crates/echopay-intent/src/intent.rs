@@ -0,0 +1,100 @@
+//! What a parsed voice command asks EchoPay to do
+
+use serde::{Deserialize, Serialize};
+
+use crate::{amount::Amount, contacts::Candidate};
+
+/// Who a payment is addressed to
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Recipient {
+    /// A saved contact, matched exactly or by how the name is spelled or sounds
+    Contact { name: String, address: String },
+    /// An SS58 address spoken or pasted verbatim
+    Address { address: String },
+    /// A name that sounds or is spelled like several contacts, none clearly
+    /// the best; the user should pick one of the candidates
+    Ambiguous {
+        name: String,
+        candidates: Vec<Candidate>,
+    },
+    /// A name that matches no contact and is not an address
+    Unresolved { name: String },
+}
+
+impl Recipient {
+    /// The recipient's address, if it could be resolved
+    pub fn address(&self) -> Option<&str> {
+        match self {
+            Self::Contact { address, .. } | Self::Address { address } => Some(address),
+            Self::Ambiguous { .. } | Self::Unresolved { .. } => None,
+        }
+    }
+}
+
+/// A voice command, classified
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Intent {
+    /// Send `amount` whole units of `currency` (an uppercase code such as `DOT`)
+    ///
+    /// `record_payment` takes the amount in the currency's smallest unit; get
+    /// it with [`Amount::to_base_units`] and [`Currency::decimals`](crate::Currency::decimals).
+    Pay {
+        amount: Amount,
+        currency: String,
+        recipient: Recipient,
+    },
+    /// Save a contact, with its address if one was spoken
+    AddContact {
+        name: String,
+        address: Option<String>,
+    },
+    /// Delete a saved contact
+    RemoveContact { name: String },
+    /// Show the saved contacts
+    ListContacts,
+    /// Show the account's balance
+    QueryBalance,
+    /// Show past transactions
+    QueryHistory,
+    /// Show the network's status
+    QueryStatus,
+    /// Open the settings screen
+    OpenSettings,
+    /// Change security settings or preferences
+    UpdateSettings,
+    /// Turn a feature such as `biometric` or `voice verification` on or off
+    SetFeature { feature: String, enabled: bool },
+    /// Nothing the parser recognises
+    Unknown,
+}
+
+impl Intent {
+    /// The command's category as named by the web app: `payment`, `contact`,
+    /// `query`, `settings` or `unknown`
+    pub fn category(&self) -> &'static str {
+        match self {
+            Self::Pay { .. } => "payment",
+            Self::AddContact { .. } | Self::RemoveContact { .. } | Self::ListContacts => "contact",
+            Self::QueryBalance | Self::QueryHistory | Self::QueryStatus => "query",
+            Self::OpenSettings | Self::UpdateSettings | Self::SetFeature { .. } => "settings",
+            Self::Unknown => "unknown",
+        }
+    }
+}
+
+/// Result of parsing one transcript
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ParsedCommand {
+    /// What the command asks for
+    pub intent: Intent,
+    /// How sure the parser is, from 0 to 1
+    pub confidence: f64,
+    /// Example commands to show the user when confidence is below 0.7
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub suggestions: Vec<String>,
+    /// BCP 47 tag of the grammar that parsed the command, to record with the
+    /// payment as its `locale`
+    pub locale: String,
+}
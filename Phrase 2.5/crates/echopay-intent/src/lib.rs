@@ -0,0 +1,69 @@
+//! Voice command parsing for EchoPay
+//!
+//! Turns a speech transcript into a typed [`Intent`] with a confidence score,
+//! using the same grammar as the web app's `CommandParsingService`: payments
+//! ("send 5 DOT to Alice", "give Bob 2 WND"), contact management, balance,
+//! history and status queries, and settings. Recipients are resolved against a
+//! [`ContactBook`], falling back to SS58 addresses and then to names spelled
+//! or sounding like a contact. When several contacts fit about equally well
+//! the recipient is [`Recipient::Ambiguous`] and lists them, so the caller can
+//! ask the user which one was meant.
+//!
+//! ```
+//! use echopay_intent::{Intent, Parser};
+//!
+//! let parsed = Parser::default().parse("Send 2.5 DOT to Alice");
+//! assert!(matches!(parsed.intent, Intent::Pay { .. }));
+//! assert_eq!(parsed.intent.category(), "payment");
+//! ```
+//!
+//! Commands in other languages are read with a [`Grammar`]: the built-in
+//! [`SPANISH`], [`PORTUGUESE`] and [`MANDARIN_PINYIN`] ones, picked by BCP 47
+//! tag with [`Parser::for_locale`], or one supplied by the caller. Each has
+//! its own patterns, number words and currency names, and the tag is
+//! returned in [`ParsedCommand::locale`] so it can be stored with the
+//! payment.
+//!
+//! ```
+//! use echopay_intent::{ContactBook, Intent, Parser};
+//!
+//! let parser = Parser::for_locale(ContactBook::with_dev_accounts(), "es-MX").unwrap();
+//! let parsed = parser.parse("Envía dos coma cinco DOT a Alice");
+//! assert!(matches!(parsed.intent, Intent::Pay { .. }));
+//! assert_eq!(parsed.locale, "es");
+//! ```
+//!
+//! Unlike the web app, the parser
+//! - picks the intent from the pattern that matched rather than from keywords
+//!   anywhere in the transcript, so "remove contact Addison" is a removal and
+//!   "display my contacts" or "modify preferences" are recognised;
+//! - keeps amounts exact instead of converting them to floating point, and
+//!   also understands amounts in words such as "half a DOT" or "1.5k planck"
+//!   (see [`SpokenAmount`]) and every currency in [`CURRENCIES`];
+//! - rejects amounts finer than the currency's smallest unit rather than
+//!   rounding them, so they never reach `record_payment`;
+//! - keeps the case of spoken addresses, which SS58 depends on.
+//!
+//! `tests/corpus.jsonl` pins the expected result of representative transcripts.
+
+mod amount;
+mod contacts;
+mod currency;
+mod grammar;
+mod intent;
+mod parser;
+mod phonetic;
+mod spoken;
+
+pub use amount::{Amount, AmountError};
+pub use contacts::{
+    levenshtein, match_score, phonetic_similarity, similarity, Candidate, Contact, ContactBook,
+};
+pub use currency::{Currency, CURRENCIES};
+pub use grammar::{
+    Grammar, NumberWords, Rule, ENGLISH, GRAMMARS, MANDARIN_PINYIN, PORTUGUESE, SPANISH,
+};
+pub use intent::{Intent, ParsedCommand, Recipient};
+pub use parser::Parser;
+pub use phonetic::{double_metaphone, Codes};
+pub use spoken::SpokenAmount;
@@ -0,0 +1,356 @@
+//! Matching transcripts against a command grammar
+
+use regex::{Captures, Regex};
+
+use crate::{
+    contacts::ContactBook,
+    currency::Currency,
+    grammar::{fold, Grammar, Rule, ENGLISH},
+    intent::{Intent, ParsedCommand, Recipient},
+    spoken::SpokenAmount,
+};
+
+/// Below this confidence the user is shown example commands
+const SUGGESTION_THRESHOLD: f64 = 0.7;
+
+/// Candidates scoring within this of the best make a recipient ambiguous
+const AMBIGUITY_MARGIN: f64 = 0.1;
+
+/// Most example commands shown at once
+const MAX_SUGGESTIONS: usize = 3;
+
+const SS58_PATTERN: &str = r"^[1-9A-HJ-NP-Za-km-z]{47,48}$";
+
+/// Turns transcripts into intents, resolving recipients against a contact book
+#[derive(Debug, Clone)]
+pub struct Parser {
+    contacts: ContactBook,
+    grammar: Grammar,
+    rules: Vec<(Rule, Regex)>,
+    ss58: Regex,
+}
+
+impl Parser {
+    /// An English parser that resolves recipients against `contacts`
+    pub fn new(contacts: ContactBook) -> Self {
+        Self::with_grammar(contacts, ENGLISH).expect("built-in grammar is valid")
+    }
+
+    /// A parser for commands in `grammar`'s language
+    ///
+    /// Fails if one of the grammar's patterns is not a valid regex.
+    pub fn with_grammar(contacts: ContactBook, grammar: Grammar) -> Result<Self, regex::Error> {
+        let rules = grammar
+            .rules
+            .iter()
+            .map(|(rule, pattern)| Ok((*rule, Regex::new(&format!("(?i){pattern}"))?)))
+            .collect::<Result<_, regex::Error>>()?;
+        Ok(Self {
+            contacts,
+            grammar,
+            rules,
+            ss58: Regex::new(SS58_PATTERN).expect("built-in pattern is valid"),
+        })
+    }
+
+    /// A parser using the built-in grammar for a BCP 47 tag such as `pt-BR`,
+    /// if there is one
+    pub fn for_locale(contacts: ContactBook, tag: &str) -> Option<Self> {
+        let grammar = Grammar::for_locale(tag)?;
+        Some(Self::with_grammar(contacts, *grammar).expect("built-in grammar is valid"))
+    }
+
+    /// The grammar transcripts are matched against
+    pub fn grammar(&self) -> &Grammar {
+        &self.grammar
+    }
+
+    /// The contact book recipients are resolved against
+    pub fn contacts(&self) -> &ContactBook {
+        &self.contacts
+    }
+
+    /// Mutable access to the contact book, e.g. to apply an `AddContact` intent
+    pub fn contacts_mut(&mut self) -> &mut ContactBook {
+        &mut self.contacts
+    }
+
+    /// Classifies a transcript
+    ///
+    /// The grammar's rules are tried in order and the first match wins; a
+    /// payment rule whose amount cannot be read, or is finer than its
+    /// currency's smallest unit, is passed over.
+    pub fn parse(&self, transcript: &str) -> ParsedCommand {
+        let transcript = fold(transcript.trim());
+        let (intent, confidence) = self
+            .rules
+            .iter()
+            .find_map(|(rule, pattern)| self.apply(*rule, &pattern.captures(&transcript)?))
+            .unwrap_or((Intent::Unknown, 0.1));
+        let confidence = confidence.min(1.0);
+        let suggestions = if confidence < SUGGESTION_THRESHOLD {
+            self.suggestions(&transcript.to_lowercase())
+        } else {
+            Vec::new()
+        };
+        ParsedCommand {
+            intent,
+            confidence,
+            suggestions,
+            locale: self.grammar.locale.to_owned(),
+        }
+    }
+
+    /// The intent a rule's match stands for and how sure the parser is of it
+    fn apply(&self, rule: Rule, captures: &Captures<'_>) -> Option<(Intent, f64)> {
+        Some(match rule {
+            Rule::Pay => return self.payment(captures),
+            Rule::AddContact => (
+                Intent::AddContact {
+                    name: named(captures, "name").to_lowercase(),
+                    address: captures
+                        .name("address")
+                        .map(|address| address.as_str().to_owned()),
+                },
+                0.85,
+            ),
+            Rule::RemoveContact => (
+                Intent::RemoveContact {
+                    name: named(captures, "name").to_lowercase(),
+                },
+                0.85,
+            ),
+            Rule::ListContacts => (Intent::ListContacts, 0.85),
+            Rule::QueryBalance => (Intent::QueryBalance, 0.9),
+            Rule::QueryHistory => (Intent::QueryHistory, 0.9),
+            Rule::QueryStatus => (Intent::QueryStatus, 0.9),
+            Rule::OpenSettings => (Intent::OpenSettings, 0.8),
+            Rule::UpdateSettings => (Intent::UpdateSettings, 0.8),
+            Rule::EnableFeature | Rule::DisableFeature => (
+                Intent::SetFeature {
+                    feature: self.feature(named(captures, "feature")),
+                    enabled: rule == Rule::EnableFeature,
+                },
+                0.8,
+            ),
+        })
+    }
+
+    fn payment(&self, captures: &Captures<'_>) -> Option<(Intent, f64)> {
+        let phrase = format!(
+            "{} {}",
+            named(captures, "amount"),
+            named(captures, "currency")
+        );
+        let spoken = SpokenAmount::parse_in(&phrase, &self.grammar).ok()?;
+        let (amount, currency) = spoken.in_whole_units(Currency::default_currency()).ok()?;
+
+        // Finer than the currency's smallest unit, so no payment could carry it
+        amount.to_base_units(currency.decimals).ok()?;
+        let mut confidence = 0.9;
+        if amount.is_zero() {
+            confidence *= 0.5;
+        }
+        let (recipient, factor) = self.resolve(named(captures, "recipient"));
+        confidence *= factor;
+
+        Some((
+            Intent::Pay {
+                amount,
+                currency: currency.code.to_owned(),
+                recipient,
+            },
+            confidence,
+        ))
+    }
+
+    /// Resolves a spoken recipient and the factor it scales confidence by
+    fn resolve(&self, name: &str) -> (Recipient, f64) {
+        if let Some(contact) = self.contacts.get(name) {
+            return (contact_recipient(contact), 1.1);
+        }
+        if self.ss58.is_match(name) {
+            return (
+                Recipient::Address {
+                    address: name.to_owned(),
+                },
+                0.9,
+            );
+        }
+        let candidates = self.contacts.candidates(name);
+        match candidates.as_slice() {
+            [] => (
+                Recipient::Unresolved {
+                    name: name.to_lowercase(),
+                },
+                0.5,
+            ),
+            [best, next, ..] if best.score - next.score < AMBIGUITY_MARGIN => (
+                Recipient::Ambiguous {
+                    name: name.to_lowercase(),
+                    candidates,
+                },
+                0.6,
+            ),
+            [best, ..] => (
+                Recipient::Contact {
+                    name: best.name.clone(),
+                    address: best.address.clone(),
+                },
+                0.8,
+            ),
+        }
+    }
+
+    /// The English name of a spoken feature, or the words as spoken if the
+    /// grammar has no name for them
+    fn feature(&self, spoken: &str) -> String {
+        let spoken = words(spoken).to_lowercase();
+        let squashed = |text: &str| text.split_whitespace().collect::<String>();
+        self.grammar
+            .features
+            .iter()
+            .find(|(name, _)| squashed(name) == squashed(&spoken))
+            .map_or(spoken, |(_, english)| (*english).to_owned())
+    }
+
+    /// Example commands related to the words of a lowercased transcript
+    fn suggestions(&self, command: &str) -> Vec<String> {
+        let mentions = |words: &[&str]| words.iter().any(|word| command.contains(word));
+        let mut suggestions: Vec<&str> = self
+            .grammar
+            .suggestions
+            .iter()
+            .filter(|(keywords, _)| mentions(keywords))
+            .flat_map(|(_, examples)| examples.iter().copied())
+            .collect();
+        if suggestions.is_empty() {
+            suggestions.extend(self.grammar.fallback_suggestions);
+        }
+
+        suggestions
+            .into_iter()
+            .take(MAX_SUGGESTIONS)
+            .map(String::from)
+            .collect()
+    }
+}
+
+impl Default for Parser {
+    /// An English parser preloaded with the development contacts
+    fn default() -> Self {
+        Self::new(ContactBook::with_dev_accounts())
+    }
+}
+
+fn named<'t>(captures: &Captures<'t>, name: &str) -> &'t str {
+    captures.name(name).map_or("", |group| group.as_str())
+}
+
+fn contact_recipient(contact: &crate::contacts::Contact) -> Recipient {
+    Recipient::Contact {
+        name: contact.name.clone(),
+        address: contact.address.clone(),
+    }
+}
+
+/// Collapses runs of whitespace to single spaces
+fn words(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::amount::Amount;
+
+    #[test]
+    fn keeps_the_case_of_spoken_addresses() {
+        let address = "5DAAnrj7VHTznn2AWBemMuyBwZWs6FNFjdyVXUeYum3PTXFy";
+        let parsed = Parser::default().parse(&format!("send 1 dot to {address}"));
+        assert_eq!(
+            parsed.intent,
+            Intent::Pay {
+                amount: Amount::new(1, 0),
+                currency: String::from("DOT"),
+                recipient: Recipient::Address {
+                    address: address.to_owned()
+                },
+            }
+        );
+    }
+
+    #[test]
+    fn resolves_recipients_added_at_runtime() {
+        let mut parser = Parser::default();
+        assert!(matches!(
+            parser.parse("pay 3 to dave").intent,
+            Intent::Pay {
+                recipient: Recipient::Unresolved { .. },
+                ..
+            }
+        ));
+        parser
+            .contacts_mut()
+            .add("Dave", "5DAAnrj7VHTznn2AWBemMuyBwZWs6FNFjdyVXUeYum3PTXFy");
+        let parsed = parser.parse("pay 3 to dave");
+        assert_eq!(parsed.confidence, 0.9 * 1.1);
+        assert!(parsed.suggestions.is_empty());
+    }
+
+    #[test]
+    fn asks_which_of_similar_contacts_was_meant() {
+        let mut contacts = ContactBook::new();
+        contacts.add("John", "5DAAnrj7VHTznn2AWBemMuyBwZWs6FNFjdyVXUeYum3PTXFy");
+        contacts.add("Jan", "5HGjWAeFDfFCWPsjFQdVV2Msvz2XtMktvgocEZcCj68kUMaw");
+        contacts.add("Kate", "5CiPPseXPECbkjWCa6MnjNokrgYjMqmKndv2rSnekmSK2DjL");
+        let parser = Parser::new(contacts);
+
+        let Intent::Pay { recipient, .. } = parser.parse("send 1 dot to jon").intent else {
+            panic!("not a payment");
+        };
+        let Recipient::Ambiguous { candidates, .. } = recipient else {
+            panic!("recipient should be ambiguous: {recipient:?}");
+        };
+        let names: Vec<_> = candidates.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, ["john", "jan"]);
+
+        let Intent::Pay { recipient, .. } = parser.parse("send 1 dot to cait").intent else {
+            panic!("not a payment");
+        };
+        assert_eq!(
+            recipient.address(),
+            Some("5CiPPseXPECbkjWCa6MnjNokrgYjMqmKndv2rSnekmSK2DjL")
+        );
+    }
+
+    #[test]
+    fn reports_the_locale_and_accepts_custom_grammars() {
+        assert_eq!(Parser::default().parse("show my contacts").locale, "en");
+        let spanish = Parser::for_locale(ContactBook::with_dev_accounts(), "es-MX").unwrap();
+        assert_eq!(spanish.parse("muestra mis contactos").locale, "es");
+
+        static ITALIAN: Grammar = Grammar {
+            locale: "it",
+            rules: &[(
+                Rule::Pay,
+                r"\b(?:invia|paga)\s+(?P<amount>.+?)\s+a\s+(?P<recipient>[a-zA-Z0-9]+)",
+            )],
+            numbers: crate::grammar::NumberWords {
+                values: &[("cinque", 5)],
+                ..ENGLISH.numbers
+            },
+            ..ENGLISH
+        };
+        let italian = Parser::with_grammar(ContactBook::with_dev_accounts(), ITALIAN).unwrap();
+        let parsed = italian.parse("invia cinque DOT a Bob");
+        assert_eq!(parsed.intent.category(), "payment");
+        assert_eq!(parsed.locale, "it");
+
+        let broken = Grammar {
+            rules: &[(Rule::ListContacts, "(")],
+            ..ITALIAN
+        };
+        assert!(Parser::with_grammar(ContactBook::new(), broken).is_err());
+    }
+}
@@ -0,0 +1,666 @@
+//! Double Metaphone sound codes, for names the speech engine spells differently
+//!
+//! A port of Lawrence Philips' Double Metaphone as found in Apache Commons
+//! Codec. Each word gets a primary code and an alternate code for a second
+//! plausible pronunciation, both at most four characters long. "Jon" and
+//! "John" both encode to `JN`, "Kate" and "Cait" to `KT`.
+
+/// Longest code produced
+const MAX_LENGTH: usize = 4;
+
+/// Primary and alternate pronunciation of a word
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Codes {
+    pub primary: String,
+    pub alternate: String,
+}
+
+/// Encodes a word; anything but ASCII letters, `Ç`, `Ñ` and spaces is ignored
+pub fn double_metaphone(word: &str) -> Codes {
+    let chars: Vec<char> = word
+        .trim()
+        .to_uppercase()
+        .chars()
+        .filter(|c| c.is_ascii_uppercase() || matches!(c, 'Ç' | 'Ñ' | ' '))
+        .collect();
+    let mut encoder = Encoder {
+        slavo_germanic: false,
+        chars,
+        primary: String::new(),
+        alternate: String::new(),
+    };
+    encoder.slavo_germanic = encoder.contains_anywhere(&["W", "K", "CZ", "WITZ"]);
+    encoder.encode();
+    encoder.primary.truncate(MAX_LENGTH);
+    encoder.alternate.truncate(MAX_LENGTH);
+    Codes {
+        primary: encoder.primary,
+        alternate: encoder.alternate,
+    }
+}
+
+struct Encoder {
+    chars: Vec<char>,
+    slavo_germanic: bool,
+    primary: String,
+    alternate: String,
+}
+
+impl Encoder {
+    fn encode(&mut self) {
+        let len = self.len();
+        let mut index: isize = 0;
+        if self.has(0, 2, &["GN", "KN", "PN", "WR", "PS"]) {
+            index = 1;
+        }
+        if self.at(0) == 'X' {
+            self.add("S");
+            index = 1;
+        }
+
+        while index < len && !self.complete() {
+            index = match self.at(index) {
+                'A' | 'E' | 'I' | 'O' | 'U' | 'Y' => {
+                    if index == 0 {
+                        self.add("A");
+                    }
+                    index + 1
+                }
+                'B' => self.single("P", index, 'B'),
+                'Ç' => {
+                    self.add("S");
+                    index + 1
+                }
+                'C' => self.c(index),
+                'D' => self.d(index),
+                'F' => self.single("F", index, 'F'),
+                'G' => self.g(index),
+                'H' => self.h(index),
+                'J' => self.j(index),
+                'K' => self.single("K", index, 'K'),
+                'L' => self.l(index),
+                'M' => {
+                    self.add("M");
+                    if self.m_is_doubled(index) {
+                        index + 2
+                    } else {
+                        index + 1
+                    }
+                }
+                'N' => self.single("N", index, 'N'),
+                'Ñ' => {
+                    self.add("N");
+                    index + 1
+                }
+                'P' => self.p(index),
+                'Q' => self.single("K", index, 'Q'),
+                'R' => self.r(index),
+                'S' => self.s(index),
+                'T' => self.t(index),
+                'V' => self.single("F", index, 'V'),
+                'W' => self.w(index),
+                'X' => self.x(index),
+                'Z' => self.z(index),
+                _ => index + 1,
+            };
+        }
+    }
+
+    fn len(&self) -> isize {
+        self.chars.len() as isize
+    }
+
+    fn at(&self, index: isize) -> char {
+        usize::try_from(index)
+            .ok()
+            .and_then(|index| self.chars.get(index))
+            .copied()
+            .unwrap_or('\0')
+    }
+
+    fn is_vowel(&self, index: isize) -> bool {
+        matches!(self.at(index), 'A' | 'E' | 'I' | 'O' | 'U' | 'Y')
+    }
+
+    /// Whether the `length` characters from `start` are one of `options`
+    fn has(&self, start: isize, length: isize, options: &[&str]) -> bool {
+        if start < 0 || start + length > self.len() {
+            return false;
+        }
+        let slice: String = self.chars[start as usize..(start + length) as usize]
+            .iter()
+            .collect();
+        options.contains(&slice.as_str())
+    }
+
+    fn contains_anywhere(&self, options: &[&str]) -> bool {
+        let word: String = self.chars.iter().collect();
+        options.iter().any(|option| word.contains(option))
+    }
+
+    fn complete(&self) -> bool {
+        self.primary.len() >= MAX_LENGTH && self.alternate.len() >= MAX_LENGTH
+    }
+
+    fn add(&mut self, code: &str) {
+        self.add_both(code, code);
+    }
+
+    fn add_both(&mut self, primary: &str, alternate: &str) {
+        self.primary.push_str(primary);
+        self.alternate.push_str(alternate);
+    }
+
+    /// Adds `code` for a letter that sounds the same when doubled
+    fn single(&mut self, code: &str, index: isize, letter: char) -> isize {
+        self.add(code);
+        if self.at(index + 1) == letter {
+            index + 2
+        } else {
+            index + 1
+        }
+    }
+
+    fn starts_germanic(&self) -> bool {
+        self.has(0, 4, &["VAN ", "VON "]) || self.has(0, 3, &["SCH"])
+    }
+
+    fn c(&mut self, index: isize) -> isize {
+        if self.c_is_k_in_ach(index) {
+            self.add("K");
+            index + 2
+        } else if index == 0 && self.has(index, 6, &["CAESAR"]) {
+            self.add("S");
+            index + 2
+        } else if self.has(index, 2, &["CH"]) {
+            self.ch(index)
+        } else if self.has(index, 2, &["CZ"]) && !self.has(index - 2, 4, &["WICZ"]) {
+            self.add_both("S", "X");
+            index + 2
+        } else if self.has(index + 1, 3, &["CIA"]) {
+            self.add("X");
+            index + 3
+        } else if self.has(index, 2, &["CC"]) && !(index == 1 && self.at(0) == 'M') {
+            self.cc(index)
+        } else if self.has(index, 2, &["CK", "CG", "CQ"]) {
+            self.add("K");
+            index + 2
+        } else if self.has(index, 2, &["CI", "CE", "CY"]) {
+            if self.has(index, 3, &["CIO", "CIE", "CIA"]) {
+                self.add_both("S", "X");
+            } else {
+                self.add("S");
+            }
+            index + 2
+        } else {
+            self.add("K");
+            if self.has(index + 1, 2, &[" C", " Q", " G"]) {
+                index + 3
+            } else if self.has(index + 1, 1, &["C", "K", "Q"])
+                && !self.has(index + 1, 2, &["CE", "CI"])
+            {
+                index + 2
+            } else {
+                index + 1
+            }
+        }
+    }
+
+    /// "-ACH-" as in "Bacher" and "Macher", or "Chianti"
+    fn c_is_k_in_ach(&self, index: isize) -> bool {
+        if self.has(index, 4, &["CHIA"]) {
+            return true;
+        }
+        if index <= 1 || self.is_vowel(index - 2) || !self.has(index - 1, 3, &["ACH"]) {
+            return false;
+        }
+        let next = self.at(index + 2);
+        (next != 'I' && next != 'E') || self.has(index - 2, 6, &["BACHER", "MACHER"])
+    }
+
+    fn cc(&mut self, index: isize) -> isize {
+        if self.has(index + 2, 1, &["I", "E", "H"]) && !self.has(index + 2, 2, &["HU"]) {
+            if (index == 1 && self.at(index - 1) == 'A')
+                || self.has(index - 1, 5, &["UCCEE", "UCCES"])
+            {
+                self.add("KS");
+            } else {
+                self.add("X");
+            }
+            index + 3
+        } else {
+            self.add("K");
+            index + 2
+        }
+    }
+
+    fn ch(&mut self, index: isize) -> isize {
+        if index > 0 && self.has(index, 4, &["CHAE"]) {
+            self.add_both("K", "X");
+        } else if self.ch_is_greek(index) || self.ch_is_k(index) {
+            self.add("K");
+        } else if index > 0 {
+            if self.has(0, 2, &["MC"]) {
+                self.add("K");
+            } else {
+                self.add_both("X", "K");
+            }
+        } else {
+            self.add("X");
+        }
+        index + 2
+    }
+
+    /// Greek roots such as "chemistry" and "chorus"
+    fn ch_is_greek(&self, index: isize) -> bool {
+        index == 0
+            && (self.has(index + 1, 5, &["HARAC", "HARIS"])
+                || self.has(index + 1, 3, &["HOR", "HYM", "HIA", "HEM"]))
+            && !self.has(0, 5, &["CHORE"])
+    }
+
+    /// Germanic and other words where "CH" sounds like "K"
+    fn ch_is_k(&self, index: isize) -> bool {
+        self.starts_germanic()
+            || self.has(index - 2, 6, &["ORCHES", "ARCHIT", "ORCHID"])
+            || self.has(index + 2, 1, &["T", "S"])
+            || ((self.has(index - 1, 1, &["A", "O", "U", "E"]) || index == 0)
+                && (self.has(
+                    index + 2,
+                    1,
+                    &["L", "R", "N", "M", "B", "H", "F", "V", "W", " "],
+                ) || index + 1 == self.len() - 1))
+    }
+
+    fn d(&mut self, index: isize) -> isize {
+        if self.has(index, 2, &["DG"]) {
+            if self.has(index + 2, 1, &["I", "E", "Y"]) {
+                self.add("J");
+                index + 3
+            } else {
+                self.add("TK");
+                index + 2
+            }
+        } else if self.has(index, 2, &["DT", "DD"]) {
+            self.add("T");
+            index + 2
+        } else {
+            self.add("T");
+            index + 1
+        }
+    }
+
+    fn g(&mut self, index: isize) -> isize {
+        if self.at(index + 1) == 'H' {
+            return self.gh(index);
+        }
+        if self.at(index + 1) == 'N' {
+            if index == 1 && self.is_vowel(0) && !self.slavo_germanic {
+                self.add_both("KN", "N");
+            } else if !self.has(index + 2, 2, &["EY"])
+                && self.at(index + 1) != 'Y'
+                && !self.slavo_germanic
+            {
+                self.add_both("N", "KN");
+            } else {
+                self.add("KN");
+            }
+            index + 2
+        } else if self.has(index + 1, 2, &["LI"]) && !self.slavo_germanic {
+            self.add_both("KL", "L");
+            index + 2
+        } else if self.g_is_k_or_j(index) {
+            self.add_both("K", "J");
+            index + 2
+        } else if self.has(index + 1, 1, &["E", "I", "Y"])
+            || self.has(index - 1, 4, &["AGGI", "OGGI"])
+        {
+            if self.starts_germanic() || self.has(index + 1, 2, &["ET"]) {
+                self.add("K");
+            } else if self.has(index + 1, 3, &["IER"]) {
+                self.add("J");
+            } else {
+                self.add_both("J", "K");
+            }
+            index + 2
+        } else if self.at(index + 1) == 'G' {
+            self.add("K");
+            index + 2
+        } else {
+            self.add("K");
+            index + 1
+        }
+    }
+
+    /// "G" that may be hard or soft, as in "Gerald" and "Gyorgy"
+    fn g_is_k_or_j(&self, index: isize) -> bool {
+        let start = index == 0
+            && (self.at(index + 1) == 'Y'
+                || self.has(
+                    index + 1,
+                    2,
+                    &[
+                        "ES", "EP", "EB", "EL", "EY", "IB", "IL", "IN", "IE", "EI", "ER",
+                    ],
+                ));
+        let inner = (self.has(index + 1, 2, &["ER"]) || self.at(index + 1) == 'Y')
+            && !self.has(0, 6, &["DANGER", "RANGER", "MANGER"])
+            && !self.has(index - 1, 1, &["E", "I"])
+            && !self.has(index - 1, 3, &["RGY", "OGY"]);
+        start || inner
+    }
+
+    fn gh(&mut self, index: isize) -> isize {
+        if index > 0 && !self.is_vowel(index - 1) {
+            self.add("K");
+        } else if index == 0 {
+            self.add(if self.at(index + 2) == 'I' { "J" } else { "K" });
+        } else if (index > 1 && self.has(index - 2, 1, &["B", "H", "D"]))
+            || (index > 2 && self.has(index - 3, 1, &["B", "H", "D"]))
+            || (index > 3 && self.has(index - 4, 1, &["B", "H"]))
+        {
+            // Silent, as in "Hugh" and "bough"
+        } else if index > 2
+            && self.at(index - 1) == 'U'
+            && self.has(index - 3, 1, &["C", "G", "L", "R", "T"])
+        {
+            // As in "laugh" and "tough"
+            self.add("F");
+        } else if self.at(index - 1) != 'I' {
+            self.add("K");
+        }
+        index + 2
+    }
+
+    fn h(&mut self, index: isize) -> isize {
+        if (index == 0 || self.is_vowel(index - 1)) && self.is_vowel(index + 1) {
+            self.add("H");
+            index + 2
+        } else {
+            index + 1
+        }
+    }
+
+    fn j(&mut self, index: isize) -> isize {
+        if self.has(index, 4, &["JOSE"]) || self.has(0, 4, &["SAN "]) {
+            if (index == 0 && self.at(index + 4) == ' ')
+                || self.len() == 4
+                || self.has(0, 4, &["SAN "])
+            {
+                self.add("H");
+            } else {
+                self.add_both("J", "H");
+            }
+            return index + 1;
+        }
+
+        if index == 0 {
+            self.add_both("J", "A");
+        } else if self.is_vowel(index - 1)
+            && !self.slavo_germanic
+            && matches!(self.at(index + 1), 'A' | 'O')
+        {
+            self.add_both("J", "H");
+        } else if index == self.len() - 1 {
+            self.add_both("J", "");
+        } else if !self.has(index + 1, 1, &["L", "T", "K", "S", "N", "M", "B", "Z"])
+            && !self.has(index - 1, 1, &["S", "K", "L"])
+        {
+            self.add("J");
+        }
+        if self.at(index + 1) == 'J' {
+            index + 2
+        } else {
+            index + 1
+        }
+    }
+
+    fn l(&mut self, index: isize) -> isize {
+        if self.at(index + 1) != 'L' {
+            self.add("L");
+            return index + 1;
+        }
+        // Spanish "-illo", "-illa" and "-alle" are silent in the primary code
+        let len = self.len();
+        let spanish = (index == len - 3 && self.has(index - 1, 4, &["ILLO", "ILLA", "ALLE"]))
+            || ((self.has(len - 2, 2, &["AS", "OS"]) || self.has(len - 1, 1, &["A", "O"]))
+                && self.has(index - 1, 4, &["ALLE"]));
+        if spanish {
+            self.add_both("L", "");
+        } else {
+            self.add("L");
+        }
+        index + 2
+    }
+
+    fn m_is_doubled(&self, index: isize) -> bool {
+        self.at(index + 1) == 'M'
+            || (self.has(index - 1, 3, &["UMB"])
+                && (index + 1 == self.len() - 1 || self.has(index + 2, 2, &["ER"])))
+    }
+
+    fn p(&mut self, index: isize) -> isize {
+        if self.at(index + 1) == 'H' {
+            self.add("F");
+            index + 2
+        } else {
+            self.add("P");
+            if self.has(index + 1, 1, &["P", "B"]) {
+                index + 2
+            } else {
+                index + 1
+            }
+        }
+    }
+
+    fn r(&mut self, index: isize) -> isize {
+        // French final "-ier" is silent in the primary code
+        if index == self.len() - 1
+            && !self.slavo_germanic
+            && self.has(index - 2, 2, &["IE"])
+            && !self.has(index - 4, 2, &["ME", "MA"])
+        {
+            self.add_both("", "R");
+        } else {
+            self.add("R");
+        }
+        if self.at(index + 1) == 'R' {
+            index + 2
+        } else {
+            index + 1
+        }
+    }
+
+    fn s(&mut self, index: isize) -> isize {
+        if self.has(index - 1, 3, &["ISL", "YSL"]) {
+            index + 1
+        } else if index == 0 && self.has(index, 5, &["SUGAR"]) {
+            self.add_both("X", "S");
+            index + 1
+        } else if self.has(index, 2, &["SH"]) {
+            if self.has(index + 1, 4, &["HEIM", "HOEK", "HOLM", "HOLZ"]) {
+                self.add("S");
+            } else {
+                self.add("X");
+            }
+            index + 2
+        } else if self.has(index, 3, &["SIO", "SIA"]) || self.has(index, 4, &["SIAN"]) {
+            if self.slavo_germanic {
+                self.add("S");
+            } else {
+                self.add_both("S", "X");
+            }
+            index + 3
+        } else if (index == 0 && self.has(index + 1, 1, &["M", "N", "L", "W"]))
+            || self.has(index + 1, 1, &["Z"])
+        {
+            self.add_both("S", "X");
+            if self.has(index + 1, 1, &["Z"]) {
+                index + 2
+            } else {
+                index + 1
+            }
+        } else if self.has(index, 2, &["SC"]) {
+            self.sc(index)
+        } else {
+            if index == self.len() - 1 && self.has(index - 2, 2, &["AI", "OI"]) {
+                self.add_both("", "S");
+            } else {
+                self.add("S");
+            }
+            if self.has(index + 1, 1, &["S", "Z"]) {
+                index + 2
+            } else {
+                index + 1
+            }
+        }
+    }
+
+    fn sc(&mut self, index: isize) -> isize {
+        if self.at(index + 2) == 'H' {
+            if self.has(index + 3, 2, &["OO", "ER", "EN", "UY", "ED", "EM"]) {
+                if self.has(index + 3, 2, &["ER", "EN"]) {
+                    self.add_both("X", "SK");
+                } else {
+                    self.add("SK");
+                }
+            } else if index == 0 && !self.is_vowel(3) && self.at(3) != 'W' {
+                self.add_both("X", "S");
+            } else {
+                self.add("X");
+            }
+        } else if self.has(index + 2, 1, &["I", "E", "Y"]) {
+            self.add("S");
+        } else {
+            self.add("SK");
+        }
+        index + 3
+    }
+
+    fn t(&mut self, index: isize) -> isize {
+        if self.has(index, 4, &["TION"]) || self.has(index, 3, &["TIA", "TCH"]) {
+            self.add("X");
+            index + 3
+        } else if self.has(index, 2, &["TH"]) || self.has(index, 3, &["TTH"]) {
+            if self.has(index + 2, 2, &["OM", "AM"]) || self.starts_germanic() {
+                self.add("T");
+            } else {
+                self.add_both("0", "T");
+            }
+            index + 2
+        } else {
+            self.add("T");
+            if self.has(index + 1, 1, &["T", "D"]) {
+                index + 2
+            } else {
+                index + 1
+            }
+        }
+    }
+
+    fn w(&mut self, index: isize) -> isize {
+        if self.has(index, 2, &["WR"]) {
+            self.add("R");
+            return index + 2;
+        }
+        if index == 0 && (self.is_vowel(index + 1) || self.has(index, 2, &["WH"])) {
+            if self.is_vowel(index + 1) {
+                self.add_both("A", "F");
+            } else {
+                self.add("A");
+            }
+            index + 1
+        } else if (index == self.len() - 1 && self.is_vowel(index - 1))
+            || self.has(index - 1, 5, &["EWSKI", "EWSKY", "OWSKI", "OWSKY"])
+            || self.has(0, 3, &["SCH"])
+        {
+            self.add_both("", "F");
+            index + 1
+        } else if self.has(index, 4, &["WICZ", "WITZ"]) {
+            self.add_both("TS", "FX");
+            index + 4
+        } else {
+            index + 1
+        }
+    }
+
+    fn x(&mut self, index: isize) -> isize {
+        if index == 0 {
+            self.add("S");
+            return index + 1;
+        }
+        // Silent in French endings such as "-eaux"
+        let french = index == self.len() - 1
+            && (self.has(index - 3, 3, &["IAU", "EAU"]) || self.has(index - 2, 2, &["AU", "OU"]));
+        if !french {
+            self.add("KS");
+        }
+        if self.has(index + 1, 1, &["C", "X"]) {
+            index + 2
+        } else {
+            index + 1
+        }
+    }
+
+    fn z(&mut self, index: isize) -> isize {
+        if self.at(index + 1) == 'H' {
+            self.add("J");
+            return index + 2;
+        }
+        if self.has(index + 1, 2, &["ZO", "ZI", "ZA"])
+            || (self.slavo_germanic && index > 0 && self.at(index - 1) != 'T')
+        {
+            self.add_both("S", "TS");
+        } else {
+            self.add("S");
+        }
+        if self.at(index + 1) == 'Z' {
+            index + 2
+        } else {
+            index + 1
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn codes(word: &str) -> (String, String) {
+        let codes = double_metaphone(word);
+        (codes.primary, codes.alternate)
+    }
+
+    #[test]
+    fn encodes_reference_words() {
+        let cases = [
+            ("Thompson", "TMPS", "TMPS"),
+            ("Smith", "SM0", "XMT"),
+            ("Schmidt", "XMT", "SMT"),
+            ("Jose", "HS", "HS"),
+            ("Xavier", "SF", "SFR"),
+            ("Caesar", "SSR", "SSR"),
+            ("Knight", "NT", "NT"),
+            ("Philip", "FLP", "FLP"),
+            ("Charlie", "XRL", "XRL"),
+            ("Gnarly", "NRL", "NRL"),
+        ];
+        for (word, primary, alternate) in cases {
+            assert_eq!(
+                codes(word),
+                (primary.to_owned(), alternate.to_owned()),
+                "{word}"
+            );
+        }
+    }
+
+    #[test]
+    fn matches_misheard_names() {
+        assert_eq!(double_metaphone("Jon"), double_metaphone("John"));
+        assert_eq!(double_metaphone("Kate"), double_metaphone("Cait"));
+        assert_eq!(
+            double_metaphone("Stephen").primary,
+            double_metaphone("Steven").primary
+        );
+    }
+}
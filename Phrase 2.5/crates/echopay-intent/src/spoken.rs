@@ -0,0 +1,521 @@
+//! Amounts as people say them: "five point two dot", "half a DOT",
+//! "two thousand five hundred planck" or "1.5k"
+//!
+//! Numbers may be digits (with `,` thousands separators and a `k`, `m` or `b`
+//! suffix), number words, or a mix ("2 thousand"). Fractions are spoken as
+//! "half a dot", "three quarters of a dot" or "one and a half dot". Values
+//! are computed exactly; an amount with no exact decimal form, such as
+//! "a third of a dot", is rejected rather than rounded.
+//!
+//! Those are the English words; [`SpokenAmount::parse_in`] reads amounts
+//! with another [`Grammar`]'s number words, decimal separator and currency
+//! names, as in "dos coma cinco DOT" or "wu ge bo ka".
+
+use std::{str::FromStr, sync::OnceLock};
+
+use regex::Regex;
+
+use crate::{
+    amount::{Amount, AmountError},
+    currency::Currency,
+    grammar::{fold, Grammar, NumberWords, ENGLISH},
+};
+
+/// An amount and the unit it was spoken in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SpokenAmount {
+    /// The number as spoken
+    pub amount: Amount,
+    /// The currency named, if any
+    pub currency: Option<&'static Currency>,
+    /// Whether the amount counts the currency's smallest unit, e.g. planck
+    pub in_base_units: bool,
+}
+
+impl SpokenAmount {
+    /// Parses an amount spoken in `grammar`'s language, e.g. "dos coma cinco DOT" in Spanish
+    pub fn parse_in(phrase: &str, grammar: &Grammar) -> Result<Self, AmountError> {
+        let phrase = fold(phrase).to_lowercase();
+        let mut tokens = tokenize(&phrase, &grammar.numbers)?;
+
+        let mut currency = None;
+        let mut in_base_units = false;
+        for count in (1..=MAX_CURRENCY_WORDS.min(tokens.len())).rev() {
+            let tail = &tokens[tokens.len() - count..];
+            let Some(words) = tail
+                .iter()
+                .map(|token| match token {
+                    Token::Word(word) => Some(*word),
+                    Token::Number(_) => None,
+                })
+                .collect::<Option<Vec<_>>>()
+            else {
+                continue;
+            };
+            let name = words.join(" ");
+            if let Some(named) = grammar.currency_code(&name).and_then(Currency::by_code) {
+                currency = Some(named);
+            } else if let Some(named) = Currency::by_word(&name) {
+                currency = Some(named);
+            } else if let Some(named) = Currency::by_base_unit_word(&name) {
+                currency = Some(named);
+                in_base_units = true;
+            }
+            if currency.is_some() {
+                tokens.truncate(tokens.len() - count);
+                break;
+            }
+        }
+
+        let amount = number(&tokens, &grammar.numbers)?.to_amount()?;
+        Ok(Self {
+            amount,
+            currency,
+            in_base_units,
+        })
+    }
+
+    /// The amount in whole units of its currency, or of `default` if none was named
+    pub fn in_whole_units(
+        &self,
+        default: &'static Currency,
+    ) -> Result<(Amount, &'static Currency), AmountError> {
+        let currency = self.currency.unwrap_or(default);
+        let amount = if self.in_base_units {
+            self.amount.scaled_down(currency.decimals)?
+        } else {
+            self.amount.normalized()
+        };
+        Ok((amount, currency))
+    }
+
+    /// The amount in the smallest unit of its currency, or of `default` if none was named
+    ///
+    /// Fails with [`AmountError::TooPrecise`] for amounts finer than that
+    /// unit, such as "half a planck".
+    pub fn to_base_units(&self, default: &'static Currency) -> Result<u128, AmountError> {
+        let (amount, currency) = self.in_whole_units(default)?;
+        amount.to_base_units(currency.decimals)
+    }
+}
+
+impl FromStr for SpokenAmount {
+    type Err = AmountError;
+
+    /// Parses an amount spoken in English
+    fn from_str(phrase: &str) -> Result<Self, Self::Err> {
+        Self::parse_in(phrase, &ENGLISH)
+    }
+}
+
+/// Most words in a currency name, as in "bo ka bi"
+const MAX_CURRENCY_WORDS: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Token<'a> {
+    Number(Ratio),
+    Word(&'a str),
+}
+
+/// Splits a lowercase phrase into numerals and words
+///
+/// A `k`, `m` or `b` written straight after a numeral multiplies it.
+fn tokenize<'a>(phrase: &'a str, numbers: &NumberWords) -> Result<Vec<Token<'a>>, AmountError> {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    let pattern =
+        PATTERN.get_or_init(|| Regex::new(r"\d(?:[\d,.]*\d)?|\p{L}+").expect("pattern is valid"));
+
+    let mut tokens = Vec::new();
+    let mut previous_end = None;
+    for found in pattern.find_iter(phrase) {
+        let text = found.as_str();
+        let adjacent = previous_end == Some(found.start());
+        previous_end = Some(found.end());
+
+        if text.starts_with(|c: char| c.is_ascii_digit()) {
+            tokens.push(Token::Number(numeral(text, numbers.decimal_separator)?));
+            continue;
+        }
+        if let (true, Some(factor)) = (adjacent, suffix(text)) {
+            if let Some(Token::Number(value)) = tokens.last_mut() {
+                *value = value.mul(Ratio::whole(factor))?;
+                continue;
+            }
+        }
+        tokens.push(Token::Word(text));
+    }
+    Ok(tokens)
+}
+
+/// Parses digits such as `2,500.75`, or `2.500,75` where `,` is the decimal separator
+fn numeral(text: &str, decimal_separator: char) -> Result<Ratio, AmountError> {
+    let group_separator = if decimal_separator == ',' { '.' } else { ',' };
+    let (whole, fraction) = text.split_once(decimal_separator).unwrap_or((text, ""));
+    if fraction.contains([decimal_separator, group_separator]) {
+        return Err(AmountError::Malformed);
+    }
+    let mut groups = whole.split(group_separator);
+    let first = groups.next().unwrap_or_default();
+    let grouped = whole.contains(group_separator);
+    if grouped && (first.len() > 3 || groups.any(|group| group.len() != 3)) {
+        return Err(AmountError::Malformed);
+    }
+
+    let digits = whole.replace(group_separator, "");
+    let amount: Amount = if fraction.is_empty() {
+        digits.parse()?
+    } else {
+        format!("{digits}.{fraction}").parse()?
+    };
+    Ratio::from_amount(amount)
+}
+
+/// Value of a number spoken as words, numerals or both
+fn number(tokens: &[Token<'_>], numbers: &NumberWords) -> Result<Ratio, AmountError> {
+    let denominator = |token: &Token<'_>| match token {
+        Token::Word(word) => lookup(numbers.fractions, word),
+        Token::Number(_) => None,
+    };
+    let Some(at) = tokens.iter().position(|token| denominator(token).is_some()) else {
+        return whole(tokens, numbers);
+    };
+    let parts = denominator(&tokens[at]).unwrap_or(1);
+    // Only fillers may follow the fraction, as "of a" in "three quarters of a dot"
+    if !fillers_only(&tokens[at + 1..], numbers) {
+        return Err(AmountError::Malformed);
+    }
+
+    let before = &tokens[..at];
+    let (whole_part, count) = match before.iter().rposition(
+        |token| matches!(token, Token::Word(word) if numbers.conjunctions.contains(word)),
+    ) {
+        Some(and) => (whole(&before[..and], numbers)?, &before[and + 1..]),
+        None => (Ratio::ZERO, before),
+    };
+    let count = if count.is_empty() {
+        Ratio::whole(1)
+    } else {
+        whole(count, numbers)?
+    };
+    whole_part.add(count.div(parts)?)
+}
+
+/// Value of a number without a fraction word, such as "two hundred and five point five"
+fn whole(tokens: &[Token<'_>], numbers: &NumberWords) -> Result<Ratio, AmountError> {
+    if tokens.is_empty() {
+        return Err(AmountError::Malformed);
+    }
+
+    // "two thousand three hundred forty five" is total 2000, section 300, current 45
+    let mut total = Ratio::ZERO;
+    let mut section = Ratio::ZERO;
+    let mut current = Ratio::ZERO;
+    let mut rest = tokens.iter();
+    while let Some(token) = rest.next() {
+        let word = match *token {
+            Token::Number(value) => {
+                current = current.add(value)?;
+                continue;
+            }
+            Token::Word(word) => word,
+        };
+        if numbers.conjunctions.contains(&word) {
+            continue;
+        }
+        if let Some(value) = lookup(numbers.values, word) {
+            current = current.add(Ratio::whole(value))?;
+        } else if let Some(factor) = lookup(numbers.multipliers, word) {
+            section = section.add(current.or_one().mul(Ratio::whole(factor))?)?;
+            current = Ratio::ZERO;
+        } else if let Some(factor) = lookup(numbers.scales, word) {
+            let said = section.add(current)?;
+            total = total.add(said.or_one().mul(Ratio::whole(factor))?)?;
+            section = Ratio::ZERO;
+            current = Ratio::ZERO;
+        } else if numbers.points.contains(&word) {
+            let mut digits = String::new();
+            while let Some(token) = rest.next() {
+                match token {
+                    Token::Word(word) => match digit(word, numbers) {
+                        Some(digit) => digits.push(digit),
+                        // Only fillers may follow, as "ge" in "wu dian wu ge DOT"
+                        None if fillers_only(&[*token], numbers)
+                            && fillers_only(rest.as_slice(), numbers) =>
+                        {
+                            break
+                        }
+                        None => return Err(AmountError::Malformed),
+                    },
+                    Token::Number(value) if value.den == 1 => {
+                        digits.push_str(&value.num.to_string())
+                    }
+                    Token::Number(_) => return Err(AmountError::Malformed),
+                }
+            }
+            if digits.is_empty() {
+                return Err(AmountError::Malformed);
+            }
+            current = current.add(numeral(&format!("0.{digits}"), '.')?)?;
+        } else {
+            return Err(AmountError::Malformed);
+        }
+    }
+    total.add(section)?.add(current)
+}
+
+fn fillers_only(tokens: &[Token<'_>], numbers: &NumberWords) -> bool {
+    tokens
+        .iter()
+        .all(|token| matches!(token, Token::Word(word) if numbers.fillers.contains(word)))
+}
+
+fn lookup(words: &[(&str, u128)], word: &str) -> Option<u128> {
+    words
+        .iter()
+        .find(|(candidate, _)| *candidate == word)
+        .map(|(_, value)| *value)
+}
+
+/// A single digit after the decimal point
+fn digit(word: &str, numbers: &NumberWords) -> Option<char> {
+    lookup(numbers.values, word)
+        .filter(|value| *value < 10)
+        .and_then(|value| char::from_digit(value as u32, 10))
+}
+
+/// Multiplier written straight after a numeral, as in `1.5k`
+fn suffix(text: &str) -> Option<u128> {
+    match text {
+        "k" => Some(1_000),
+        "m" => Some(1_000_000),
+        "b" => Some(1_000_000_000),
+        _ => None,
+    }
+}
+
+/// An exact non-negative fraction, kept in lowest terms
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Ratio {
+    num: u128,
+    den: u128,
+}
+
+impl Ratio {
+    const ZERO: Self = Self { num: 0, den: 1 };
+
+    const fn whole(value: u128) -> Self {
+        Self { num: value, den: 1 }
+    }
+
+    fn new(num: u128, den: u128) -> Self {
+        let divisor = gcd(num, den).max(1);
+        Self {
+            num: num / divisor,
+            den: den / divisor,
+        }
+    }
+
+    fn from_amount(amount: Amount) -> Result<Self, AmountError> {
+        let den = 10u128
+            .checked_pow(amount.scale().into())
+            .ok_or(AmountError::Overflow)?;
+        Ok(Self::new(amount.digits(), den))
+    }
+
+    /// One when nothing was said yet, as in "hundred" for "a hundred"
+    fn or_one(self) -> Self {
+        if self.num == 0 {
+            Self::whole(1)
+        } else {
+            self
+        }
+    }
+
+    fn add(self, other: Self) -> Result<Self, AmountError> {
+        let num = self
+            .num
+            .checked_mul(other.den)
+            .zip(other.num.checked_mul(self.den))
+            .and_then(|(a, b)| a.checked_add(b));
+        let den = self.den.checked_mul(other.den);
+        match (num, den) {
+            (Some(num), Some(den)) => Ok(Self::new(num, den)),
+            _ => Err(AmountError::Overflow),
+        }
+    }
+
+    fn mul(self, other: Self) -> Result<Self, AmountError> {
+        match (
+            self.num.checked_mul(other.num),
+            self.den.checked_mul(other.den),
+        ) {
+            (Some(num), Some(den)) => Ok(Self::new(num, den)),
+            _ => Err(AmountError::Overflow),
+        }
+    }
+
+    fn div(self, divisor: u128) -> Result<Self, AmountError> {
+        self.mul(Self::new(1, divisor))
+    }
+
+    /// The exact decimal, if the denominator only has factors 2 and 5
+    fn to_amount(self) -> Result<Amount, AmountError> {
+        let mut rest = self.den;
+        let mut twos = 0u8;
+        let mut fives = 0u8;
+        while rest.is_multiple_of(2) {
+            rest /= 2;
+            twos += 1;
+        }
+        while rest.is_multiple_of(5) {
+            rest /= 5;
+            fives += 1;
+        }
+        if rest != 1 {
+            return Err(AmountError::TooPrecise);
+        }
+
+        let scale = twos.max(fives);
+        let factor = 10u128
+            .checked_pow(scale.into())
+            .ok_or(AmountError::Overflow)?
+            / self.den;
+        let digits = self.num.checked_mul(factor).ok_or(AmountError::Overflow)?;
+        Ok(Amount::new(digits, scale))
+    }
+}
+
+fn gcd(mut a: u128, mut b: u128) -> u128 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spoken(phrase: &str) -> (String, Option<&'static str>, bool) {
+        let parsed: SpokenAmount = phrase.parse().unwrap();
+        (
+            parsed.amount.normalized().to_string(),
+            parsed.currency.map(|currency| currency.code),
+            parsed.in_base_units,
+        )
+    }
+
+    #[test]
+    fn parses_number_words() {
+        let cases = [
+            ("five point two dot", "5.2", Some("DOT")),
+            ("twenty-five", "25", None),
+            ("two hundred and fifty ksm", "250", Some("KSM")),
+            ("a thousand", "1000", None),
+            ("one million two hundred thousand", "1200000", None),
+            ("point oh five", "0.05", None),
+            ("3 thousand wnd", "3000", Some("WND")),
+            ("nine point nine nine usdc", "9.99", Some("USDC")),
+        ];
+        for (phrase, amount, currency) in cases {
+            assert_eq!(
+                spoken(phrase),
+                (amount.to_owned(), currency, false),
+                "{phrase}"
+            );
+        }
+    }
+
+    #[test]
+    fn parses_numerals_and_suffixes() {
+        assert_eq!(spoken("1.5k").0, "1500");
+        assert_eq!(spoken("2,500 DOT").0, "2500");
+        assert_eq!(spoken("0.25m").0, "250000");
+        assert_eq!(spoken("5dot"), ("5".to_owned(), Some("DOT"), false));
+        assert_eq!("2,50".parse::<SpokenAmount>(), Err(AmountError::Malformed));
+    }
+
+    #[test]
+    fn parses_fractions() {
+        assert_eq!(spoken("half a DOT"), ("0.5".to_owned(), Some("DOT"), false));
+        assert_eq!(spoken("three quarters of a dot").0, "0.75");
+        assert_eq!(spoken("one and a half dot").0, "1.5");
+        assert_eq!(spoken("two and three tenths").0, "2.3");
+        assert_eq!(
+            "a third of a dot".parse::<SpokenAmount>(),
+            Err(AmountError::TooPrecise)
+        );
+    }
+
+    #[test]
+    fn converts_to_base_units() {
+        let dot = Currency::default_currency();
+        let planck: SpokenAmount = "two thousand five hundred planck".parse().unwrap();
+        assert_eq!(planck.to_base_units(dot), Ok(2_500));
+        assert_eq!(
+            planck.in_whole_units(dot).unwrap().0.to_string(),
+            "0.00000025"
+        );
+
+        let half: SpokenAmount = "half a dot".parse().unwrap();
+        assert_eq!(half.to_base_units(dot), Ok(5_000_000_000));
+
+        let usdc: SpokenAmount = "0.0000001 usdc".parse().unwrap();
+        assert_eq!(usdc.to_base_units(dot), Err(AmountError::TooPrecise));
+        let half_planck: SpokenAmount = "half a planck".parse().unwrap();
+        assert_eq!(half_planck.to_base_units(dot), Err(AmountError::TooPrecise));
+    }
+
+    #[test]
+    fn parses_other_languages() {
+        use crate::grammar::{MANDARIN_PINYIN, PORTUGUESE, SPANISH};
+
+        let cases = [
+            (&SPANISH, "dos coma cinco DOT", "2.5", Some("DOT")),
+            (&SPANISH, "doscientos cincuenta mil", "250000", None),
+            (&SPANISH, "tres cuartos de un dot", "0.75", Some("DOT")),
+            (&SPANISH, "2.500,75", "2500.75", None),
+            (
+                &PORTUGUESE,
+                "vinte e três vírgula cinco ksm",
+                "23.5",
+                Some("KSM"),
+            ),
+            (&PORTUGUESE, "um e meio", "1.5", None),
+            (&MANDARIN_PINYIN, "yi wan wu qian ling er", "15002", None),
+            (
+                &MANDARIN_PINYIN,
+                "sān diǎn yī sì ge bō kǎ",
+                "3.14",
+                Some("DOT"),
+            ),
+            (&MANDARIN_PINYIN, "ban ge ku sa ma", "0.5", Some("KSM")),
+        ];
+        for (grammar, phrase, amount, currency) in cases {
+            let parsed = SpokenAmount::parse_in(phrase, grammar).unwrap();
+            assert_eq!(
+                (
+                    parsed.amount.normalized().to_string(),
+                    parsed.currency.map(|currency| currency.code)
+                ),
+                (amount.to_owned(), currency),
+                "{phrase}"
+            );
+        }
+        assert_eq!(
+            SpokenAmount::parse_in("2.50", &SPANISH),
+            Err(AmountError::Malformed)
+        );
+    }
+
+    #[test]
+    fn rejects_other_words() {
+        for phrase in ["", "dot", "lots of dot", "five apples", "half a dot please"] {
+            assert_eq!(
+                phrase.parse::<SpokenAmount>(),
+                Err(AmountError::Malformed),
+                "{phrase}"
+            );
+        }
+    }
+}
@@ -0,0 +1,53 @@
+//! Checks the parser against the transcripts in `corpus.jsonl`
+
+use echopay_intent::{ContactBook, Intent, Parser};
+use serde::Deserialize;
+
+/// One transcript and the result the parser must produce for it
+#[derive(Deserialize)]
+struct Case {
+    /// BCP 47 tag of the grammar to parse with; English if absent
+    #[serde(default = "english")]
+    locale: String,
+    transcript: String,
+    intent: Intent,
+    confidence: f64,
+    #[serde(default)]
+    suggestions: Vec<String>,
+}
+
+fn english() -> String {
+    String::from("en")
+}
+
+#[test]
+fn parses_corpus() {
+    let corpus = include_str!("corpus.jsonl");
+    let mut failures = Vec::new();
+
+    for (line, text) in corpus.lines().enumerate() {
+        let case: Case = serde_json::from_str(text)
+            .unwrap_or_else(|error| panic!("corpus.jsonl:{}: {error}", line + 1));
+        let parser = Parser::for_locale(ContactBook::with_dev_accounts(), &case.locale)
+            .unwrap_or_else(|| panic!("corpus.jsonl:{}: no grammar for {}", line + 1, case.locale));
+        let parsed = parser.parse(&case.transcript);
+        if parsed.intent != case.intent
+            || (parsed.confidence - case.confidence).abs() > 1e-9
+            || parsed.suggestions != case.suggestions
+        {
+            failures.push(format!(
+                "corpus.jsonl:{}: {:?}\n  expected {:?} ({}) {:?}\n  got      {:?} ({}) {:?}",
+                line + 1,
+                case.transcript,
+                case.intent,
+                case.confidence,
+                case.suggestions,
+                parsed.intent,
+                parsed.confidence,
+                parsed.suggestions,
+            ));
+        }
+    }
+
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+}